@@ -2,21 +2,32 @@
 // the macro generates rust code calling the WinRT COM based on the winmd metadata supplied by the Windows SDK.
 
 // Adding blanket imports with ::* can drastically increase compilation time
+// The metadata comes with Windows, the macro reads it while the build script compiles
+#[cfg(windows)]
 winrt::build!(
   dependencies
       os
   types
-      windows::foundation::{PropertyValue}
+      windows::foundation::{PropertyValue, Size, TimeSpan, TypedEventHandler}
       windows::storage::streams::{
         DataWriter, IDataWriterFactory, IBuffer
       }
-      windows::ui::xaml::{UIElement, RoutedEventHandler, Thickness}
+      windows::ui::{Color}
+      windows::ui::view_management::{UISettings, UIColorType}
+      windows::ui::xaml::{UIElement, RoutedEventHandler, RoutedEventArgs, Thickness, CornerRadius, ElementTheme, Visibility, FocusState, BrushTransition}
       windows::ui::xaml::controls::{
-        Button, IButtonFactory, 
-        IRelativePanelFactory, RelativePanel, 
-        ListBox, IListBoxFactory, ScrollViewer, ScrollMode,
-        TextBlock, 
+        Border,
+        Button, IButtonFactory,
+        CheckBox, ICheckBoxFactory,
+        Control,
+        IRelativePanelFactory, RelativePanel,
+        ListBox, IListBoxFactory, ScrollViewer, ScrollMode, ScrollBarVisibility,
+        TextBlock,
+        TextBox, TextChangedEventHandler, TextChangedEventArgs,
         IListViewFactory, ListView, ListViewSelectionMode,
+        IListViewItemFactory, ListViewItem,
+        ListViewBase, DragItemsCompletedEventArgs,
+        SelectionChangedEventHandler, SelectionChangedEventArgs,
         IStackPanelFactory,
         StackPanel,
         Orientation,
@@ -27,9 +38,12 @@ winrt::build!(
         IGridFactory,
         GridUnitType,
         GridLength,
-        IGridStatics
+        IGridStatics,
+        ToolTipService
       }
+      windows::ui::xaml::input::{PointerEventHandler, PointerRoutedEventArgs}
       windows::ui::xaml::markup::*
+      windows::ui::xaml::media::{SolidColorBrush}
       windows::ui::xaml::media::imaging::{
         SoftwareBitmapSource
       }
@@ -38,11 +52,15 @@ winrt::build!(
         IDesktopWindowXamlSourceFactory,
         WindowsXamlManager
       }
+      windows::management::deployment::PackageManager
+      windows::application_model::Package
       windows::graphics::imaging::{
         SoftwareBitmap, ISoftwareBitmapFactory, BitmapPixelFormat, BitmapAlphaMode
       }
 );
 
 fn main() {
-  build();
+    // the crate is left empty for the other targets
+    #[cfg(windows)]
+    build();
 }
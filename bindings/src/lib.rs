@@ -1 +1,2 @@
-include!(concat!(env!("OUT_DIR"), "/winrt.rs"));
\ No newline at end of file
+#[cfg(target_os = "windows")]
+include!(concat!(env!("OUT_DIR"), "/winrt.rs"));
@@ -1,4 +1,8 @@
 extern crate embed_resource;
 fn main() {
-    embed_resource::compile("browser-selector-rt.rc");
+    // the resource file holds the Windows manifest and version info
+    // thus it's only needed when building for Windows
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows") {
+        embed_resource::compile("browser-selector-rt.rc");
+    }
 }
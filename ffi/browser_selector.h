@@ -0,0 +1,25 @@
+/* C ABI of browser_selector_ffi, see ffi/src/lib.rs */
+#ifndef BROWSER_SELECTOR_H
+#define BROWSER_SELECTOR_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define BS_OK 0
+#define BS_ERROR_INVALID_ARGUMENT 1
+#define BS_ERROR_BROWSER_NOT_FOUND 2
+#define BS_ERROR_NO_CHOICE 3
+#define BS_ERROR_FAILED 4
+
+/* Strings written to the out parameters must be released with bs_free_string */
+int bs_detect_browsers(char **out_json);
+int bs_open_url(const char *url, const char *browser_id);
+int bs_choose(const char *url, char **out_browser_id);
+void bs_free_string(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
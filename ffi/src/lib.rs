@@ -0,0 +1,192 @@
+/*
+  C ABI over the browser-selector library for programs not written in Rust.
+  Every function returns one of the BS_* codes. The strings given back are
+  allocated by this library and have to be released with `bs_free_string`.
+  See browser_selector.h for the declarations.
+*/
+use serde::Serialize;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+
+use browser_selector::error::*;
+use browser_selector::ipc;
+use browser_selector::os::os_browsers::Browser;
+use browser_selector::os::shared::BrowserEntry;
+
+pub const BS_OK: c_int = 0;
+pub const BS_ERROR_INVALID_ARGUMENT: c_int = 1;
+pub const BS_ERROR_BROWSER_NOT_FOUND: c_int = 2;
+pub const BS_ERROR_NO_CHOICE: c_int = 3;
+pub const BS_ERROR_FAILED: c_int = 4;
+
+// The chooser UI is shown by the program, expected next to this library
+const CHOOSER_EXE_NAME: &str = "browser-selector.exe";
+
+/// An entry of the JSON array given by `bs_detect_browsers`
+#[derive(Serialize)]
+struct BrowserInfo {
+    id: String,
+    name: String,
+    is_default: bool,
+}
+
+/// Writes to `out_json` a JSON array with the `id`, `name` and `is_default`
+/// of every browser, including the custom ones from the config.
+///
+/// # Safety
+/// `out_json` has to point to writable memory for a pointer, the string written
+/// there is released with `bs_free_string`.
+#[no_mangle]
+pub unsafe extern "C" fn bs_detect_browsers(out_json: *mut *mut c_char) -> c_int {
+    if out_json.is_null() {
+        return BS_ERROR_INVALID_ARGUMENT;
+    }
+
+    guard(|| {
+        let browser_infos: Vec<BrowserInfo> = load_browsers()?
+            .iter()
+            .map(|browser| BrowserInfo {
+                id: browser.id(),
+                name: browser.display_name(),
+                is_default: browser.is_default(),
+            })
+            .collect();
+
+        write_out_string(out_json, serde_json::to_string(&browser_infos)?)
+    })
+}
+
+/// Opens the `url` in the browser with the given `browser_id`, as found in `bs_detect_browsers`
+///
+/// # Safety
+/// `url` and `browser_id` have to be NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn bs_open_url(url: *const c_char, browser_id: *const c_char) -> c_int {
+    let (url, browser_id) = match (read_in_string(url), read_in_string(browser_id)) {
+        (Some(url), Some(browser_id)) => (url, browser_id),
+        _ => return BS_ERROR_INVALID_ARGUMENT,
+    };
+
+    guard(|| {
+        match load_browsers()?
+            .iter()
+            .find(|browser| browser.id() == browser_id)
+        {
+            Some(browser) => {
+                browser.launch(&url, &[])?;
+                Ok(BS_OK)
+            }
+            None => Ok(BS_ERROR_BROWSER_NOT_FOUND),
+        }
+    })
+}
+
+/// Shows the browser list for the `url`, unless the config decides the browser,
+/// and opens it in the chosen browser whose id is written to `out_browser_id`.
+/// Returns `BS_ERROR_NO_CHOICE` when the window was closed without a choice.
+///
+/// # Safety
+/// `url` has to be a NUL terminated string and `out_browser_id` has to point to writable
+/// memory for a pointer, the string written there is released with `bs_free_string`.
+#[no_mangle]
+pub unsafe extern "C" fn bs_choose(url: *const c_char, out_browser_id: *mut *mut c_char) -> c_int {
+    let url = match read_in_string(url) {
+        Some(url) if !out_browser_id.is_null() => url,
+        _ => return BS_ERROR_INVALID_ARGUMENT,
+    };
+
+    guard(|| {
+        let chooser_exe = library_path()?.with_file_name(CHOOSER_EXE_NAME);
+        match ipc::choose_in_child_process(&chooser_exe, &url)? {
+            Some(choice) => write_out_string(out_browser_id, choice.browser_id),
+            None => Ok(BS_ERROR_NO_CHOICE),
+        }
+    })
+}
+
+/// Releases a string given by any of the functions above
+///
+/// # Safety
+/// `string` has to come from one of the functions above and be released only once.
+#[no_mangle]
+pub unsafe extern "C" fn bs_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+fn load_browsers() -> BSResult<Vec<Browser>> {
+    let config = browser_selector::load_config(&browser_selector::config_directory())?;
+    browser_selector::available_browsers(&config)
+}
+
+/// Runs `f` turning errors and panics into `BS_ERROR_FAILED`,
+/// unwinding across the C ABI is undefined behavior.
+fn guard(f: impl FnOnce() -> BSResult<c_int> + std::panic::UnwindSafe) -> c_int {
+    match std::panic::catch_unwind(f) {
+        Ok(Ok(code)) => code,
+        Ok(Err(e)) => {
+            println!("browser-selector: {}", e);
+            BS_ERROR_FAILED
+        }
+        Err(_) => BS_ERROR_FAILED,
+    }
+}
+
+unsafe fn read_in_string(string: *const c_char) -> Option<String> {
+    match string.is_null() {
+        true => None,
+        false => CStr::from_ptr(string).to_str().ok().map(String::from),
+    }
+}
+
+unsafe fn write_out_string(out: *mut *mut c_char, string: String) -> BSResult<c_int> {
+    match CString::new(string) {
+        Ok(c_string) => {
+            *out = c_string.into_raw();
+            Ok(BS_OK)
+        }
+        Err(_) => Err(BSError::from("The string contains a nul character.")),
+    }
+}
+
+/// Path of this library's file, the program linking it when built as a static library
+#[cfg(target_os = "windows")]
+fn library_path() -> BSResult<std::path::PathBuf> {
+    use std::os::windows::ffi::OsStringExt;
+    use winapi::um::libloaderapi::{
+        GetModuleFileNameW, GetModuleHandleExW, GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS,
+        GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+    };
+
+    let mut module = std::ptr::null_mut();
+    let mut path = vec![0u16; 32768];
+    let path_len = unsafe {
+        // any address inside this module identifies it
+        if GetModuleHandleExW(
+            GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+            library_path as *const () as *const u16,
+            &mut module,
+        ) == 0
+        {
+            return Err(BSError::from("Could not find the library module."));
+        }
+
+        GetModuleFileNameW(module, path.as_mut_ptr(), path.len() as u32) as usize
+    };
+    if path_len == 0 {
+        return Err(BSError::from("Could not read the library path."));
+    }
+    path.truncate(path_len);
+
+    Ok(std::path::PathBuf::from(std::ffi::OsString::from_wide(
+        &path,
+    )))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn library_path() -> BSResult<std::path::PathBuf> {
+    Err(BSError::from(
+        "The chooser program can currently only be found on Windows.",
+    ))
+}
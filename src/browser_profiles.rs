@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::*;
+
+// The `Local State` file of Chromium based browsers lists their profiles
+const LOCAL_STATE_FILE_NAME: &str = "Local State";
+
+// The user data directories under %LOCALAPPDATA% by executable file name
+const USER_DATA_DIRS: [(&str, &str); 5] = [
+    ("chrome", "Google\\Chrome\\User Data"),
+    ("msedge", "Microsoft\\Edge\\User Data"),
+    ("brave", "BraveSoftware\\Brave-Browser\\User Data"),
+    ("chromium", "Chromium\\User Data"),
+    ("vivaldi", "Vivaldi\\User Data"),
+];
+
+/// A profile of a Chromium based browser, opened with `--profile-directory`
+#[derive(Debug, Clone)]
+pub struct BrowserProfile {
+    /// Name of the profile directory inside the user data directory, ie. `Profile 1`
+    pub directory: String,
+
+    /// The name the user gave to the profile
+    pub name: String,
+}
+
+impl BrowserProfile {
+    /// The command line argument opening the browser with this profile
+    pub fn argument(&self) -> String {
+        format!("--profile-directory={}", self.directory)
+    }
+}
+
+/// The profiles of the Chromium based browser at `exe_path`, the `Default` one first.
+/// Empty for the browsers whose user data directory is not known.
+pub fn read_profiles(exe_path: &str) -> BSResult<Vec<BrowserProfile>> {
+    let local_state_path = match user_data_dir(exe_path)? {
+        Some(dir) => dir.join(LOCAL_STATE_FILE_NAME),
+        None => return Ok(Vec::new()),
+    };
+    if !local_state_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let local_state: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&local_state_path)?)?;
+    let info_cache = match local_state["profile"]["info_cache"].as_object() {
+        Some(info_cache) => info_cache,
+        None => bail!(
+            "No profile.info_cache in {}",
+            local_state_path.to_string_lossy()
+        ),
+    };
+
+    let mut profiles: Vec<BrowserProfile> = info_cache
+        .iter()
+        .map(|(directory, info)| BrowserProfile {
+            directory: directory.clone(),
+            name: info["name"].as_str().unwrap_or(directory).to_owned(),
+        })
+        .collect();
+    profiles.sort_by_key(|profile| (profile.directory != "Default", profile.directory.clone()));
+
+    Ok(profiles)
+}
+
+fn user_data_dir(exe_path: &str) -> BSResult<Option<PathBuf>> {
+    let file_name = Path::new(exe_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match USER_DATA_DIRS.iter().find(|(name, _)| *name == file_name) {
+        Some((_, relative_dir)) => {
+            let local_data_dir = crate::os::util::get_local_data_directory()?;
+            Ok(Some(Path::new(&local_data_dir).join(relative_dir)))
+        }
+        None => Ok(None),
+    }
+}
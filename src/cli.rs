@@ -0,0 +1,142 @@
+use crate::config::ThemeMode;
+use crate::url_utils;
+
+// `--browser-arg=<argument>`, the argument can also follow `--browser-arg` on its own
+const BROWSER_ARG_PREFIX: &str = "--browser-arg=";
+
+/// Options given to the program on the command line.
+/// The first argument that is not a flag is the URL to be opened,
+/// the URLs following it are opened in the same browser.
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub url: String,
+
+    // The other URLs given at once, ie. by dragging several links
+    pub extra_urls: Vec<String>,
+
+    // Open the URL in a throwaway browser profile
+    pub temp_profile: bool,
+
+    // Open the URL in a private window of the browsers known to have one
+    pub private: bool,
+
+    // Answer JSON requests from stdin instead of showing the UI, see `ipc`
+    pub stdio: bool,
+
+    // Write the chosen browser to stdout, used by `ipc` when running the UI
+    pub print_choice: bool,
+
+    // Print the browser and the command line that would open the URL instead of running it
+    pub dry_run: bool,
+
+    // Forget the order the browsers were dragged to and exit
+    pub reset_order: bool,
+
+    // Forget the browsers remembered for the given host and exit
+    pub forget: Option<String>,
+
+    // Print which browser would open the given URL and why, without opening it
+    pub test_url: Option<String>,
+
+    // Register the program as a browser in Windows and open the Default apps settings
+    pub register: bool,
+
+    // Remove what `register` wrote to the registry
+    pub unregister: bool,
+
+    // Delete the browser icons cached by `icon_cache` and exit
+    pub clear_icon_cache: bool,
+
+    // Write the config and the preferences to the given file and exit, see `config::export_config`
+    pub export_config: Option<String>,
+
+    // Read the file written by `export_config` into the config and the preferences and exit
+    pub import_config: Option<String>,
+
+    // The imported file replaces the config and the preferences instead of being merged
+    pub replace: bool,
+
+    // Light or dark colors, overrides `mode` under `[theme]` in the config
+    pub theme: Option<ThemeMode>,
+
+    // Stay in the background and show the window for the URL on the clipboard
+    // when the hot key is pressed, see `os::hotkey`
+    pub daemon: bool,
+
+    // Let other windows cover the window, it stays above them by default
+    pub no_topmost: bool,
+
+    // Passed to the chosen browser before the URL, in the order they were given
+    pub browser_args: Vec<String>,
+
+    // Also write the log records to stderr, see `logging`
+    pub verbose: bool,
+
+    // The language of the texts, ie. `de-DE`, overrides the Windows display language
+    pub lang: Option<String>,
+}
+
+impl CliArgs {
+    pub fn parse(arguments: impl Iterator<Item = String>) -> CliArgs {
+        let mut cli_args = CliArgs::default();
+
+        // arg[0] is executable path
+        let mut arguments = arguments.skip(1);
+        while let Some(argument) = arguments.next() {
+            match argument.as_str() {
+                "--temp-profile" => cli_args.temp_profile = true,
+                "--private" => cli_args.private = true,
+                "--stdio" => cli_args.stdio = true,
+                "--print-choice" => cli_args.print_choice = true,
+                "--dry-run" => cli_args.dry_run = true,
+                "--reset-order" => cli_args.reset_order = true,
+                "--register" => cli_args.register = true,
+                "--unregister" => cli_args.unregister = true,
+                "--clear-icon-cache" => cli_args.clear_icon_cache = true,
+                "--daemon" => cli_args.daemon = true,
+                "--no-topmost" => cli_args.no_topmost = true,
+                "--verbose" => cli_args.verbose = true,
+                "--replace" => cli_args.replace = true,
+                "--test-url" => match arguments.next() {
+                    Some(url) => cli_args.test_url = Some(url),
+                    None => println!("Ignoring --test-url which needs a URL after it"),
+                },
+                "--lang" => match arguments.next() {
+                    Some(language) => cli_args.lang = Some(language),
+                    None => println!("Ignoring --lang which needs a language after it, ie. de-DE"),
+                },
+                "--export-config" => match arguments.next() {
+                    Some(path) => cli_args.export_config = Some(path),
+                    None => println!("Ignoring --export-config which needs a file path after it"),
+                },
+                "--import-config" => match arguments.next() {
+                    Some(path) => cli_args.import_config = Some(path),
+                    None => println!("Ignoring --import-config which needs a file path after it"),
+                },
+                "--forget" => match arguments.next() {
+                    Some(host) => cli_args.forget = Some(host.to_lowercase()),
+                    None => println!("Ignoring --forget which needs a host after it"),
+                },
+                "--browser-arg" => match arguments.next() {
+                    Some(browser_arg) => cli_args.browser_args.push(browser_arg),
+                    None => println!("Ignoring --browser-arg which needs an argument after it"),
+                },
+                flag if flag.starts_with(BROWSER_ARG_PREFIX) => cli_args
+                    .browser_args
+                    .push(String::from(&flag[BROWSER_ARG_PREFIX.len()..])),
+                "--theme" => match arguments.next().as_deref().and_then(ThemeMode::from_name) {
+                    Some(mode) => cli_args.theme = Some(mode),
+                    None => println!("Ignoring --theme which needs light, dark or system after it"),
+                },
+                flag if flag.starts_with("--") => println!("Ignoring unknown argument {}", flag),
+                _ if cli_args.url.is_empty() => cli_args.url = argument,
+                _ if url_utils::normalize_url(&argument).is_ok() => {
+                    cli_args.extra_urls.push(argument)
+                }
+                _ => println!("Ignoring extra argument {}", argument),
+            }
+        }
+
+        cli_args
+    }
+}
@@ -0,0 +1,669 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::error::*;
+use crate::os::shared::LaunchMethod;
+use crate::prefs::Prefs;
+use crate::routing::{self, RoutingStep};
+use crate::rules::{self, Rule};
+use crate::sorting::{GroupBy, SortOrder};
+use crate::store::FileStore;
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+// Key of the machine config listing the keys the user config cannot override
+const LOCKED_KEY: &str = "locked";
+
+// The tables of the file written by `export_config`
+const EXPORTED_CONFIG_KEY: &str = "config";
+const EXPORTED_PREFS_KEY: &str = "prefs";
+
+// The `[[rules]]` are told apart by their pattern when the imported config is merged
+const RULES_KEY: &str = "rules";
+const RULE_PATTERN_KEY: &str = "pattern";
+
+/// User preferences read from the `config.toml` file found in the
+/// program's config directory. All fields are optional in the file,
+/// a missing file or a missing field keeps the default behavior.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// When on, the header only shows the scheme and host of the URL,
+    /// the path and query are masked until the mouse hovers the URL.
+    pub mask_url: bool,
+
+    pub temp_profile: TempProfileConfig,
+
+    /// URL routing rules, see `Rule` for how they are matched
+    pub rules: Vec<Rule>,
+
+    /// When only one browser is installed it is opened right away without showing the UI
+    pub auto_open_single: bool,
+
+    /// Programs added to the list next to the detected browsers
+    pub custom_browsers: Vec<CustomBrowser>,
+
+    /// Detected browsers left out of the list, by executable file name or full path
+    pub hidden: Vec<String>,
+
+    /// Pre-selects the browser last chosen for the host of the URL being opened
+    pub remember_per_host: bool,
+
+    /// The hosts ticked "Always use" for also open their subdomains in the same browser,
+    /// by default only the exact host does
+    pub always_use_subdomains: bool,
+
+    pub sort: SortOrder,
+
+    /// Shows the browsers under headers, ie. by vendor, see `GroupBy`
+    pub group_by: GroupBy,
+
+    /// Browsers pinned to the top of the list, by executable file name or full path
+    pub favorites: Vec<String>,
+
+    /// Pre-selects the browser whose window was in the foreground when the link was opened,
+    /// holding Shift while opening the link opens that browser right away.
+    pub prefer_foreground_browser: bool,
+
+    pub theme: ThemeConfig,
+
+    /// Allows selecting several browsers in the list to open the URL in all of them
+    pub multi_select: bool,
+
+    /// Shows the details of the selected browser next to the list
+    pub details_panel: bool,
+
+    /// When set only these browsers are listed, in this order, the others are left out.
+    /// Each entry is a browser id, executable file name or full path.
+    pub only_show: Option<Vec<String>>,
+
+    /// Closes the window without opening the URL when another window is activated,
+    /// hides it in the `--daemon` mode
+    pub close_on_blur: bool,
+
+    /// Numbers the first nine browsers of the list with their number key shortcut
+    pub shortcut_numbers: bool,
+
+    /// Size of the text of the header and of the list in effective pixels,
+    /// see `effective_font_size`
+    pub font_size: f64,
+
+    /// Space in effective pixels added above and below each browser of the list,
+    /// ie. for bigger touch targets
+    pub item_padding: f64,
+
+    /// Highlights the chosen browser for a moment before launching it, off launches it
+    /// as soon as it is chosen
+    pub selection_animation: bool,
+
+    /// The browser opening the URLs of a scheme, ie. `mailto = "thunderbird.exe"`
+    pub scheme_defaults: HashMap<String, String>,
+
+    /// The order in which the mechanisms picking the browser are consulted,
+    /// see `RoutingStep` and `routing::DEFAULT_ROUTING_ORDER`
+    pub routing_order: Vec<RoutingStep>,
+
+    pub countdown: CountdownConfig,
+
+    /// Lists each profile of the Chromium based browsers as an entry of its own
+    pub browser_profiles: bool,
+
+    /// Hands the URLs opened while the window is shown over to it instead of
+    /// showing another window, they are offered one after the other.
+    pub single_instance: bool,
+
+    /// Removes the tracking query parameters, ie. `utm_source`, from the URLs before opening them
+    pub strip_tracking: bool,
+
+    /// Shows where the links of the URL shorteners, ie. bit.ly, lead to once that is known
+    pub resolve_short_urls: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            mask_url: false,
+            temp_profile: TempProfileConfig::default(),
+            rules: Vec::default(),
+            auto_open_single: true,
+            custom_browsers: Vec::default(),
+            hidden: Vec::default(),
+            remember_per_host: false,
+            always_use_subdomains: false,
+            sort: SortOrder::default(),
+            group_by: GroupBy::default(),
+            favorites: Vec::default(),
+            prefer_foreground_browser: false,
+            theme: ThemeConfig::default(),
+            multi_select: false,
+            details_panel: true,
+            only_show: None,
+            close_on_blur: false,
+            shortcut_numbers: false,
+            font_size: DEFAULT_FONT_SIZE,
+            item_padding: 0.,
+            selection_animation: true,
+            scheme_defaults: HashMap::default(),
+            routing_order: routing::DEFAULT_ROUTING_ORDER.to_vec(),
+            countdown: CountdownConfig::default(),
+            browser_profiles: false,
+            single_instance: true,
+            strip_tracking: false,
+            resolve_short_urls: false,
+        }
+    }
+}
+
+/// Settings for opening URLs in throwaway browser profiles (`--temp-profile`)
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TempProfileConfig {
+    /// Directory where the temporary profiles are created,
+    /// when missing a directory inside the OS temp directory is used.
+    pub base_dir: Option<String>,
+
+    /// Removes the temporary profiles created by previous runs when the program starts.
+    pub cleanup: bool,
+}
+
+/// Opening a browser by itself when the user leaves the window alone, under `[countdown]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CountdownConfig {
+    /// Seconds to wait for the user before opening the browser, 0 turns the countdown off
+    pub seconds: u64,
+
+    /// The browser to open, either the executable file name or the full path.
+    /// When missing the browser pre-selected in the list is opened.
+    pub browser: Option<String>,
+}
+
+// Below this the window content becomes hard to read
+const MIN_OPACITY: f64 = 0.5;
+
+// The size XAML gives to the text of the controls, the range keeps the window usable
+const DEFAULT_FONT_SIZE: f64 = 14.;
+const MIN_FONT_SIZE: f64 = 8.;
+const MAX_FONT_SIZE: f64 = 48.;
+
+/// Look of the window under `[theme]`
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Radius in pixels of the window content corners, 0 keeps them square
+    pub corner_radius: f64,
+
+    /// Opacity of the whole window from 0.5 to 1.0, values outside are clamped
+    pub opacity: f64,
+
+    pub mode: ThemeMode,
+}
+
+/// Light or dark colors for the window
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    /// Follows the app mode chosen in the Windows settings, also when it changes
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemeMode {
+    /// The mode with the given name as written in the config file, ie. `dark`
+    pub fn from_name(name: &str) -> Option<ThemeMode> {
+        match name {
+            "system" => Some(ThemeMode::System),
+            "light" => Some(ThemeMode::Light),
+            "dark" => Some(ThemeMode::Dark),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ThemeConfig {
+    fn default() -> ThemeConfig {
+        ThemeConfig {
+            corner_radius: 0.,
+            opacity: 1.,
+            mode: ThemeMode::default(),
+        }
+    }
+}
+
+impl ThemeConfig {
+    /// The configured opacity clamped to the readable range
+    pub fn effective_opacity(&self) -> f64 {
+        match self.opacity.is_nan() {
+            true => 1.,
+            false => self.opacity.clamp(MIN_OPACITY, 1.),
+        }
+    }
+}
+
+/// A program defined by the user under `[[custom_browsers]]`, it can be anything
+/// able to receive a URL as a command line argument, not only a browser.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomBrowser {
+    pub name: String,
+    pub exe_path: String,
+
+    /// Arguments passed to the program, `{url}` is replaced by the URL being opened.
+    /// When no argument has the placeholder the URL is passed as the last argument.
+    #[serde(default)]
+    pub arguments: Vec<String>,
+
+    /// Path to an .ico or .exe file to take the icon from, defaults to `exe_path`
+    pub icon_path: Option<String>,
+
+    #[serde(default)]
+    pub launch: LaunchMethod,
+}
+
+impl Config {
+    /// Reads the config file from the given `config_dir` on top of the one
+    /// from `machine_config_dir`, missing files count as empty ones.
+    ///
+    /// Each top level key of the user config replaces the same key of the machine config
+    /// as a whole, ie. the user `rules` replace the machine `rules`, unless the machine
+    /// config lists the key under `locked` in which case the user value is ignored.
+    /// A machine config which cannot be read is left out, the user one is still loaded.
+    pub fn load(machine_config_dir: Option<&str>, config_dir: &str) -> BSResult<Config> {
+        // a broken machine config leaves the user config working on its own
+        let mut machine_table = match machine_config_dir.map(read_config_table) {
+            Some(Ok(table)) => table,
+            Some(Err(e)) => {
+                log::error!(
+                    "Error loading the machine config from {}, it is ignored. Reason: {}",
+                    machine_config_dir.unwrap_or_default(),
+                    e
+                );
+                toml::value::Table::new()
+            }
+            None => toml::value::Table::new(),
+        };
+        let locked_keys: Vec<String> = match machine_table.remove(LOCKED_KEY) {
+            Some(locked) => locked.try_into()?,
+            None => Vec::new(),
+        };
+
+        let mut table = machine_table;
+        for (key, value) in read_config_table(config_dir)? {
+            if locked_keys.contains(&key) {
+                log::warn!(
+                    "Config warning: {} is locked by the machine config, the user value is ignored.",
+                    key
+                );
+                continue;
+            }
+            table.insert(key, value);
+        }
+
+        let config: Config = toml::Value::Table(table).try_into()?;
+        routing::validate_order(&config.routing_order)?;
+
+        Ok(config)
+    }
+
+    /// The configured font size within the readable range, the default one when not a number
+    pub fn effective_font_size(&self) -> f64 {
+        match self.font_size.is_nan() {
+            true => DEFAULT_FONT_SIZE,
+            false => self.font_size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE),
+        }
+    }
+
+    /// The configured item padding, the negative values count as none
+    pub fn effective_item_padding(&self) -> f64 {
+        match self.item_padding.is_nan() {
+            true => 0.,
+            false => self.item_padding.max(0.),
+        }
+    }
+}
+
+/// The path of the user config file in `config_dir`, the file is created empty when
+/// missing so that it can be opened for editing
+pub fn create_config_file(config_dir: &str) -> BSResult<std::path::PathBuf> {
+    let path = std::path::Path::new(config_dir).join(CONFIG_FILE_NAME);
+    if !path.exists() {
+        std::fs::write(&path, "")?;
+    }
+
+    Ok(path)
+}
+
+/// Writes the user config of `config_dir` and the preferences learned while using the
+/// program, ie. the browsers remembered per host, to the TOML file at `path`, the config
+/// under `[config]` and the preferences under `[prefs]`. The machine config is left out.
+pub fn export_config(config_dir: &str, path: &str) -> BSResult<()> {
+    let prefs = Prefs::load(&FileStore::open(config_dir)?)?;
+
+    let mut exported = toml::value::Table::new();
+    exported.insert(
+        String::from(EXPORTED_CONFIG_KEY),
+        toml::Value::Table(read_config_table(config_dir)?),
+    );
+    exported.insert(
+        String::from(EXPORTED_PREFS_KEY),
+        toml::Value::try_from(&prefs)?,
+    );
+    // as a value the tables are written after the keys of the same level
+    std::fs::write(path, toml::to_string(&toml::Value::Table(exported))?)?;
+
+    Ok(())
+}
+
+/// Reads the file written by `export_config` at `path` into the user config and the
+/// preferences of `config_dir`. They are merged with the current ones, which are kept
+/// where both have a value, unless `replace` in which case the file replaces them.
+/// Nothing is written when the file or the config resulting from it does not load.
+pub fn import_config(config_dir: &str, path: &str, replace: bool) -> BSResult<()> {
+    let mut imported: toml::value::Table = toml::from_str(&std::fs::read_to_string(path)?)?;
+    let imported_config = match imported.remove(EXPORTED_CONFIG_KEY) {
+        Some(toml::Value::Table(table)) => table,
+        Some(_) => bail!("{} in {} is not a table", EXPORTED_CONFIG_KEY, path),
+        None => toml::value::Table::new(),
+    };
+    let imported_prefs: Prefs = match imported.remove(EXPORTED_PREFS_KEY) {
+        Some(prefs) => prefs.try_into()?,
+        None => Prefs::default(),
+    };
+    if let Some(key) = imported.keys().next() {
+        bail!(
+            "{} has an unknown table {}, it was not written by --export-config",
+            path,
+            key
+        );
+    }
+
+    let (config_table, prefs, mut store) = match replace {
+        true => (imported_config, imported_prefs, FileStore::new(config_dir)),
+        false => {
+            let mut config_table = read_config_table(config_dir)?;
+            merge_config_tables(&mut config_table, imported_config, "");
+
+            let store = FileStore::open(config_dir)?;
+            let mut prefs = Prefs::load(&store)?;
+            prefs.merge(imported_prefs);
+            (config_table, prefs, store)
+        }
+    };
+
+    let config_table = toml::Value::Table(config_table);
+    let config: Config = config_table.clone().try_into()?;
+    routing::validate_order(&config.routing_order)?;
+    for warning in rules::validate(&config.rules) {
+        log::warn!("Config warning: {}", warning);
+    }
+
+    // written next to the file then renamed over it like the store does
+    let config_path = std::path::Path::new(config_dir).join(CONFIG_FILE_NAME);
+    let temp_path = config_path.with_extension("toml.tmp");
+    std::fs::create_dir_all(config_dir)?;
+    std::fs::write(&temp_path, toml::to_string(&config_table)?)?;
+    std::fs::rename(&temp_path, &config_path)?;
+
+    prefs.save(&mut store)
+}
+
+/// Adds the values of the `imported` config missing from the `current` one, the lists
+/// get the imported items they do not have and the tables are merged the same way.
+/// A rule is only added when no current rule has its pattern. The other values set
+/// in both are kept, `path` names them in the message telling so.
+fn merge_config_tables(current: &mut toml::value::Table, imported: toml::value::Table, path: &str) {
+    for (key, imported_value) in imported {
+        let key_path = match path.is_empty() {
+            true => key.clone(),
+            false => format!("{}.{}", path, key),
+        };
+        match (current.get_mut(&key), imported_value) {
+            (None, imported_value) => {
+                current.insert(key, imported_value);
+            }
+            (Some(toml::Value::Table(current_table)), toml::Value::Table(imported_table)) => {
+                merge_config_tables(current_table, imported_table, &key_path)
+            }
+            (Some(toml::Value::Array(current_items)), toml::Value::Array(imported_items)) => {
+                for item in imported_items {
+                    let is_same_item = |current_item: &toml::Value| match (
+                        key_path.as_str(),
+                        rule_pattern(current_item),
+                        rule_pattern(&item),
+                    ) {
+                        (RULES_KEY, Some(current_pattern), Some(pattern)) => {
+                            current_pattern == pattern
+                        }
+                        _ => *current_item == item,
+                    };
+                    match current_items
+                        .iter()
+                        .find(|current_item| is_same_item(current_item))
+                    {
+                        None => current_items.push(item),
+                        Some(current_item) if *current_item != item => log::warn!(
+                            "Keeping the {} item {}, the imported one {} is ignored.",
+                            key_path,
+                            current_item,
+                            item
+                        ),
+                        Some(_) => (),
+                    }
+                }
+            }
+            (Some(current_value), imported_value) if *current_value != imported_value => {
+                log::warn!(
+                    "Keeping {} = {}, the imported value {} is ignored.",
+                    key_path,
+                    current_value,
+                    imported_value
+                );
+            }
+            _ => (),
+        }
+    }
+}
+
+fn rule_pattern(rule: &toml::Value) -> Option<&str> {
+    rule.get(RULE_PATTERN_KEY).and_then(toml::Value::as_str)
+}
+
+fn read_config_table(dir: &str) -> BSResult<toml::value::Table> {
+    let path = std::path::Path::new(dir).join(CONFIG_FILE_NAME);
+    if !path.exists() {
+        return Ok(toml::value::Table::new());
+    }
+
+    let contents = std::fs::read_to_string(&path)?;
+    Ok(toml::from_str(&contents)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory of its own for each test holding a `config.toml` with the given `contents`
+    fn config_dir(name: &str, contents: &str) -> String {
+        let dir = std::env::temp_dir().join(format!(
+            "browser-selector-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(CONFIG_FILE_NAME), contents).unwrap();
+        dir.to_string_lossy().into_owned()
+    }
+
+    fn remove_dirs(dirs: &[&str]) {
+        for dir in dirs {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+
+    #[test]
+    fn user_config_replaces_the_machine_keys() {
+        let machine_dir = config_dir(
+            "merge-machine",
+            "mask_url = true\nfavorites = [\"firefox.exe\"]\n",
+        );
+        let user_dir = config_dir("merge-user", "favorites = [\"chrome.exe\"]\n");
+
+        let config = Config::load(Some(&machine_dir), &user_dir);
+        remove_dirs(&[&machine_dir, &user_dir]);
+        let config = config.unwrap();
+        assert!(config.mask_url);
+        assert_eq!(config.favorites, vec![String::from("chrome.exe")]);
+    }
+
+    #[test]
+    fn locked_keys_ignore_the_user_value() {
+        let machine_dir = config_dir(
+            "locked-machine",
+            "locked = [\"favorites\"]\nfavorites = [\"firefox.exe\"]\n",
+        );
+        let user_dir = config_dir(
+            "locked-user",
+            "favorites = [\"chrome.exe\"]\nmask_url = true\n",
+        );
+
+        let config = Config::load(Some(&machine_dir), &user_dir);
+        remove_dirs(&[&machine_dir, &user_dir]);
+        let config = config.unwrap();
+        assert_eq!(config.favorites, vec![String::from("firefox.exe")]);
+        assert!(config.mask_url);
+    }
+
+    #[test]
+    fn broken_machine_config_is_ignored() {
+        let machine_dir = config_dir("broken-machine", "mask_url = \n");
+        let user_dir = config_dir("broken-user", "shortcut_numbers = true\n");
+
+        let config = Config::load(Some(&machine_dir), &user_dir);
+        remove_dirs(&[&machine_dir, &user_dir]);
+        let config = config.unwrap();
+        assert!(config.shortcut_numbers);
+        assert!(!config.mask_url);
+    }
+
+    #[test]
+    fn missing_config_files_give_the_defaults() {
+        let user_dir = config_dir("missing-user", "");
+
+        let config = Config::load(Some("/nonexistent/browser-selector"), &user_dir);
+        remove_dirs(&[&user_dir]);
+        let config = config.unwrap();
+        assert_eq!(
+            config.routing_order,
+            routing::DEFAULT_ROUTING_ORDER.to_vec()
+        );
+        assert!(config.auto_open_single);
+    }
+
+    #[test]
+    fn merge_config_tables_adds_the_missing_values() {
+        let mut current: toml::value::Table = toml::from_str(
+            "mask_url = false\nfavorites = [\"chrome.exe\"]\n[theme]\nopacity = 0.9\n",
+        )
+        .unwrap();
+        let imported: toml::value::Table = toml::from_str(
+            "mask_url = true\nhidden = [\"edge.exe\"]\nfavorites = [\"firefox.exe\", \"chrome.exe\"]\n[theme]\nopacity = 0.5\nmode = \"dark\"\n",
+        )
+        .unwrap();
+
+        merge_config_tables(&mut current, imported, "");
+        let expected: toml::value::Table = toml::from_str(
+            "mask_url = false\nhidden = [\"edge.exe\"]\nfavorites = [\"chrome.exe\", \"firefox.exe\"]\n[theme]\nopacity = 0.9\nmode = \"dark\"\n",
+        )
+        .unwrap();
+        assert_eq!(current, expected);
+    }
+
+    #[test]
+    fn merge_config_tables_keeps_the_current_rule_of_a_pattern() {
+        let mut current: toml::value::Table =
+            toml::from_str("[[rules]]\npattern = \"*.github.com\"\nbrowser = \"firefox.exe\"\n")
+                .unwrap();
+        let imported: toml::value::Table = toml::from_str(
+            "[[rules]]\npattern = \"*.github.com\"\nbrowser = \"chrome.exe\"\n[[rules]]\npattern = \"example.com\"\nbrowser = \"chrome.exe\"\n",
+        )
+        .unwrap();
+
+        merge_config_tables(&mut current, imported, "");
+        let expected: toml::value::Table = toml::from_str(
+            "[[rules]]\npattern = \"*.github.com\"\nbrowser = \"firefox.exe\"\n[[rules]]\npattern = \"example.com\"\nbrowser = \"chrome.exe\"\n",
+        )
+        .unwrap();
+        assert_eq!(current, expected);
+    }
+
+    /// Saves a default browser and a browser chosen for `host` in the prefs of `dir`
+    fn save_prefs(dir: &str, host: &str, browser_id: &str) {
+        let mut prefs = Prefs::default();
+        prefs.set_host_choice(host, browser_id);
+        prefs.default_browser = Some(String::from(browser_id));
+        prefs.save(&mut FileStore::new(dir)).unwrap();
+    }
+
+    /// The config and the prefs of `dir`, the `Debug` output as they cannot be compared
+    fn load_dir(dir: &str) -> (String, String) {
+        let config = Config::load(None, dir).unwrap();
+        let prefs = Prefs::load(&FileStore::open(dir).unwrap()).unwrap();
+        (format!("{:?}", config), format!("{:?}", prefs))
+    }
+
+    #[test]
+    fn import_config_replace_gives_the_exported_config_and_prefs() {
+        let exported_dir = config_dir(
+            "export-replace-from",
+            "mask_url = true\nfavorites = [\"firefox.exe\"]\n[[rules]]\npattern = \"*.github.com\"\nbrowser = \"firefox.exe\"\n",
+        );
+        save_prefs(&exported_dir, "example.com", "firefox-id");
+        let imported_dir = config_dir(
+            "export-replace-to",
+            "shortcut_numbers = true\nfavorites = [\"chrome.exe\"]\n",
+        );
+        save_prefs(&imported_dir, "rust-lang.org", "chrome-id");
+        let path = std::path::Path::new(&exported_dir).join("exported.toml");
+        let path = path.to_string_lossy();
+
+        export_config(&exported_dir, &path).unwrap();
+        import_config(&imported_dir, &path, true).unwrap();
+        let exported = load_dir(&exported_dir);
+        let imported = load_dir(&imported_dir);
+        remove_dirs(&[&exported_dir, &imported_dir]);
+        assert_eq!(imported, exported);
+    }
+
+    #[test]
+    fn import_config_merge_keeps_the_current_values() {
+        let exported_dir = config_dir(
+            "export-merge-from",
+            "mask_url = true\nshortcut_numbers = false\nfavorites = [\"firefox.exe\"]\n",
+        );
+        save_prefs(&exported_dir, "example.com", "firefox-id");
+        let imported_dir = config_dir(
+            "export-merge-to",
+            "shortcut_numbers = true\nfavorites = [\"chrome.exe\"]\n",
+        );
+        save_prefs(&imported_dir, "rust-lang.org", "chrome-id");
+        let path = std::path::Path::new(&exported_dir).join("exported.toml");
+        let path = path.to_string_lossy();
+
+        export_config(&exported_dir, &path).unwrap();
+        import_config(&imported_dir, &path, false).unwrap();
+        let config = Config::load(None, &imported_dir);
+        let prefs = FileStore::open(&imported_dir).and_then(|store| Prefs::load(&store));
+        remove_dirs(&[&exported_dir, &imported_dir]);
+        let (config, prefs) = (config.unwrap(), prefs.unwrap());
+        assert!(config.mask_url);
+        assert!(config.shortcut_numbers);
+        assert_eq!(
+            config.favorites,
+            vec![String::from("chrome.exe"), String::from("firefox.exe")]
+        );
+        assert_eq!(prefs.default_browser.as_deref(), Some("chrome-id"));
+        assert_eq!(prefs.host_choice("rust-lang.org"), Some("chrome-id"));
+        assert_eq!(prefs.host_choice("example.com"), Some("firefox-id"));
+    }
+}
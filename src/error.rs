@@ -20,12 +20,19 @@ impl std::fmt::Display for BSError {
     }
 }
 
+#[cfg(target_os = "windows")]
 impl From<winrt::Error> for BSError {
     fn from(err: winrt::Error) -> Self {
         BSError::new(format!("[WinRT error] code: {} {}", err.code().0, err.message(),).as_str())
     }
 }
 
+impl From<winit::error::OsError> for BSError {
+    fn from(err: winit::error::OsError) -> Self {
+        BSError::new(format!("[Window error] {}", err).as_str())
+    }
+}
+
 impl From<simple_error::SimpleError> for BSError {
     fn from(err: simple_error::SimpleError) -> Self {
         BSError::new(err.as_str())
@@ -37,3 +44,33 @@ impl From<&str> for BSError {
         BSError::new(str)
     }
 }
+
+impl From<std::io::Error> for BSError {
+    fn from(err: std::io::Error) -> Self {
+        BSError::new(format!("[IO error] {}", err).as_str())
+    }
+}
+
+impl From<toml::de::Error> for BSError {
+    fn from(err: toml::de::Error) -> Self {
+        BSError::new(format!("[Config error] {}", err).as_str())
+    }
+}
+
+impl From<toml::ser::Error> for BSError {
+    fn from(err: toml::ser::Error) -> Self {
+        BSError::new(format!("[Config error] {}", err).as_str())
+    }
+}
+
+impl From<log::SetLoggerError> for BSError {
+    fn from(err: log::SetLoggerError) -> Self {
+        BSError::new(format!("[Log error] {}", err).as_str())
+    }
+}
+
+impl From<serde_json::Error> for BSError {
+    fn from(err: serde_json::Error) -> Self {
+        BSError::new(format!("[JSON error] {}", err).as_str())
+    }
+}
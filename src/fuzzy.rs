@@ -0,0 +1,114 @@
+/*
+  Scoring of the typed filter against the browser names. The characters of
+  the filter have to appear in the name in the same order but not next to
+  each other, ie. "ff" matches "Firefox", the score ranks the better matches
+  first: characters following each other or starting a word.
+*/
+
+// Every matched character
+const MATCH_SCORE: i64 = 16;
+
+// A matched character right after the previous matched one
+const CONSECUTIVE_BONUS: i64 = 12;
+
+// A matched character starting a word, ie. the "c" in "Google Chrome"
+const WORD_START_BONUS: i64 = 8;
+
+// Every character skipped between two matched ones, or before the first one
+const GAP_PENALTY: i64 = 1;
+
+/// How well the `needle` matches the `haystack` ignoring the case, higher is better.
+/// `None` when the characters of the `needle` are not all found in order.
+/// An empty `needle` matches everything with a score of 0.
+pub fn fuzzy_score(needle: &str, haystack: &str) -> Option<i64> {
+    let needle: Vec<char> = needle.chars().map(lowercase).collect();
+    let haystack: Vec<char> = haystack.chars().collect();
+
+    let mut score = 0;
+    let mut needle_index = 0;
+    let mut previous_match: Option<usize> = None;
+    for (index, character) in haystack.iter().enumerate() {
+        if needle_index == needle.len() {
+            break;
+        }
+        if lowercase(*character) != needle[needle_index] {
+            continue;
+        }
+
+        score += MATCH_SCORE;
+        let gap = match previous_match {
+            Some(previous) => index - previous - 1,
+            None => index,
+        };
+        match gap {
+            0 if previous_match.is_some() => score += CONSECUTIVE_BONUS,
+            gap => score -= gap as i64 * GAP_PENALTY,
+        }
+        if is_word_start(&haystack, index) {
+            score += WORD_START_BONUS;
+        }
+
+        previous_match = Some(index);
+        needle_index += 1;
+    }
+
+    match needle_index == needle.len() {
+        true => Some(score),
+        false => None,
+    }
+}
+
+/// The lower case `character`, kept as a single character so that
+/// the positions in the name stay the same
+fn lowercase(character: char) -> char {
+    character.to_lowercase().next().unwrap_or(character)
+}
+
+/// Whether the character at `index` starts a word, after a separator or as
+/// the upper case letter of a camel case name
+fn is_word_start(text: &[char], index: usize) -> bool {
+    match index.checked_sub(1).map(|previous| text[previous]) {
+        None => true,
+        Some(previous) if !previous.is_alphanumeric() => true,
+        Some(previous) => previous.is_lowercase() && text[index].is_uppercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_needle_matches_everything() {
+        assert_eq!(fuzzy_score("", "Firefox"), Some(0));
+        assert_eq!(fuzzy_score("", ""), Some(0));
+    }
+
+    #[test]
+    fn characters_have_to_appear_in_order() {
+        assert!(fuzzy_score("ff", "Firefox").is_some());
+        assert!(fuzzy_score("FIREFOX", "firefox").is_some());
+        assert_eq!(fuzzy_score("xf", "Firefox"), None);
+        assert_eq!(fuzzy_score("firefoxes", "Firefox"), None);
+        assert_eq!(fuzzy_score("ff", ""), None);
+    }
+
+    #[test]
+    fn consecutive_characters_score_higher() {
+        let chromium = fuzzy_score("chr", "Chromium").unwrap();
+        let google_chrome = fuzzy_score("chr", "Google Chrome").unwrap();
+        let scattered = fuzzy_score("chr", "Cache Hunter").unwrap();
+        assert!(chromium > google_chrome);
+        assert!(google_chrome > scattered);
+    }
+
+    #[test]
+    fn word_starts_score_higher() {
+        let google_chrome = fuzzy_score("gc", "Google Chrome").unwrap();
+        let gecko = fuzzy_score("gc", "Gecko").unwrap();
+        let camel_case = fuzzy_score("gc", "GoogleChrome").unwrap();
+        let lower_case = fuzzy_score("gc", "googlechrome").unwrap();
+        assert!(google_chrome > gecko);
+        assert!(camel_case > lower_case);
+    }
+}
@@ -0,0 +1,99 @@
+/*
+  The texts shown to the user are looked up by key in a language pack. English
+  is built in, the other languages are TOML files in the `lang` directory of
+  the config directory named after the language, ie. `de.toml` or `pt-BR.toml`.
+  The keys missing from a language pack fall back to English.
+*/
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::error::*;
+
+const ENGLISH_PACK: &str = include_str!("../assets/lang/en.toml");
+const LANGUAGE_PACKS_DIR_NAME: &str = "lang";
+
+// Set once by `init`, or to English on the first lookup before it. Never freed
+// so that the looked up texts can be handed out for the rest of the program.
+static LANGUAGE_PACK: AtomicPtr<HashMap<String, String>> = AtomicPtr::new(std::ptr::null_mut());
+
+/// Picks the language pack of the `language`, ie. `de-DE`, or of the Windows display
+/// language when `None`. The pack of the whole language, ie. `de`, is used when there
+/// is none for the region, English when there is none at all.
+pub fn init(config_dir: &str, language: Option<&str>) {
+    let mut texts = english_texts();
+    let language = language
+        .map(String::from)
+        .or_else(crate::os::util::get_ui_language_name);
+    if let Some(language) = language {
+        match read_language_pack(config_dir, &language) {
+            Ok(Some(translated_texts)) => texts.extend(translated_texts),
+            Ok(None) => (),
+            Err(e) => log::error!("Could not read the {} texts. Reason: {}", language, e),
+        }
+    }
+
+    LANGUAGE_PACK.store(Box::into_raw(Box::new(texts)), Ordering::SeqCst);
+}
+
+/// The text with the given `key` in the language picked by `init`
+pub fn t(key: &'static str) -> &'static str {
+    match language_pack().get(key) {
+        Some(text) => text.as_str(),
+        // only happens when a key is missing from the English pack
+        None => key,
+    }
+}
+
+/// Same as `t` with the `{name}` placeholders of the text replaced by their value
+pub fn t_with(key: &'static str, values: &[(&str, &dyn std::fmt::Display)]) -> String {
+    values
+        .iter()
+        .fold(String::from(t(key)), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+}
+
+fn language_pack() -> &'static HashMap<String, String> {
+    let mut pack = LANGUAGE_PACK.load(Ordering::SeqCst);
+    if pack.is_null() {
+        let english = Box::into_raw(Box::new(english_texts()));
+        pack = match LANGUAGE_PACK.compare_exchange(
+            std::ptr::null_mut(),
+            english,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        ) {
+            Ok(_) => english,
+            // another thread set it first
+            Err(current) => {
+                drop(unsafe { Box::from_raw(english) });
+                current
+            }
+        };
+    }
+
+    unsafe { &*pack }
+}
+
+fn english_texts() -> HashMap<String, String> {
+    toml::from_str(ENGLISH_PACK).expect("The built in English texts are not valid TOML")
+}
+
+/// The texts of the `language`, from the file of its region or of the whole language,
+/// `None` when there is neither
+fn read_language_pack(
+    config_dir: &str,
+    language: &str,
+) -> BSResult<Option<HashMap<String, String>>> {
+    let dir = std::path::Path::new(config_dir).join(LANGUAGE_PACKS_DIR_NAME);
+    let base_language = language.split('-').next().unwrap_or(language);
+    for name in &[language, base_language] {
+        let path = dir.join(format!("{}.toml", name));
+        if path.exists() {
+            let contents = std::fs::read_to_string(&path)?;
+            return Ok(Some(toml::from_str(&contents)?));
+        }
+    }
+
+    Ok(None)
+}
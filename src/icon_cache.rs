@@ -0,0 +1,144 @@
+/*
+  Converting the browser icons to something WinUI can show is one of the slowest
+  parts of the startup, thus the converted pixels are kept in one file per icon.
+  An entry is only used while the modification time of the icon file is the same
+  as when the entry was written.
+*/
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::error::*;
+use crate::os::shared::id_from_path;
+
+const CACHE_DIR_NAME: &str = "icon-cache";
+
+// increased when the layout of the entries changes so that the older ones are ignored
+const ENTRY_VERSION: u8 = 1;
+
+// version, modification seconds and nanoseconds, width, height, premultiplied
+const HEADER_SIZE: usize = 1 + 8 + 4 + 4 + 4 + 1;
+
+/// The pixels of an icon in the BGRA8 format, top-down
+#[derive(Debug, Clone)]
+pub struct IconPixels {
+    pub width: u32,
+    pub height: u32,
+
+    /// Whether the colors are already multiplied by the alpha channel
+    pub premultiplied: bool,
+    pub pixels: Vec<u8>,
+}
+
+/// The directory of the cached icons, kept out of the roaming profile
+pub fn directory() -> BSResult<PathBuf> {
+    Ok(Path::new(&crate::os::util::get_local_data_directory()?)
+        .join(crate::APP_NAME)
+        .join(CACHE_DIR_NAME))
+}
+
+/// The icon cached for the given `icon_path` at `size` pixels, `None` when nothing was cached
+/// or when the file changed since, ie. after the browser was updated.
+pub fn load(icon_path: &str, size: u32) -> Option<IconPixels> {
+    let modified = modified_time(icon_path)?;
+    let entry = std::fs::read(entry_path(icon_path, size).ok()?).ok()?;
+
+    decode(&entry, modified)
+}
+
+/// Keeps the `icon` converted from the given `icon_path` for the next runs, the `size`
+/// it was requested at tells it apart from the same icon read for another DPI
+pub fn store(icon_path: &str, size: u32, icon: &IconPixels) -> BSResult<()> {
+    let (seconds, nanoseconds) = match modified_time(icon_path) {
+        Some(modified) => modified,
+        None => bail!("Cannot read the modification time of {}", icon_path),
+    };
+
+    let mut entry = Vec::with_capacity(HEADER_SIZE + icon.pixels.len());
+    entry.push(ENTRY_VERSION);
+    entry.extend_from_slice(&seconds.to_le_bytes());
+    entry.extend_from_slice(&nanoseconds.to_le_bytes());
+    entry.extend_from_slice(&icon.width.to_le_bytes());
+    entry.extend_from_slice(&icon.height.to_le_bytes());
+    entry.push(icon.premultiplied as u8);
+    entry.extend_from_slice(&icon.pixels);
+
+    let path = entry_path(icon_path, size)?;
+    std::fs::create_dir_all(directory()?)?;
+
+    // written next to the entry and renamed over it so that
+    // a crash midway never leaves a truncated entry behind
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, &entry)?;
+    std::fs::rename(&tmp_path, &path)?;
+
+    Ok(())
+}
+
+/// Deletes every cached icon, they are converted again on the next run
+pub fn clear() -> BSResult<()> {
+    let dir = directory()?;
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+
+    Ok(())
+}
+
+/// Splits the icon index off the registry icon paths, ie. `chrome.exe,0`,
+/// the quotes around the file path are removed
+pub fn split_icon_index(icon_path: &str) -> (&str, Option<i32>) {
+    let (file_path, index) = match icon_path.rfind(',') {
+        Some(comma) => match icon_path[comma + 1..].trim().parse::<i32>() {
+            Ok(index) => (&icon_path[..comma], Some(index)),
+            Err(_) => (icon_path, None),
+        },
+        None => (icon_path, None),
+    };
+
+    (file_path.trim().trim_matches('"'), index)
+}
+
+fn entry_path(icon_path: &str, size: u32) -> BSResult<PathBuf> {
+    Ok(directory()?.join(format!("{}-{}.bgra", id_from_path(icon_path), size)))
+}
+
+/// The modification time of the file at `icon_path` as seconds and
+/// nanoseconds since the Unix epoch, see `split_icon_index`
+fn modified_time(icon_path: &str) -> Option<(u64, u32)> {
+    let (file_path, _) = split_icon_index(icon_path);
+    let modified = std::fs::metadata(file_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?;
+
+    Some((modified.as_secs(), modified.subsec_nanos()))
+}
+
+fn decode(entry: &[u8], (seconds, nanoseconds): (u64, u32)) -> Option<IconPixels> {
+    if entry.len() < HEADER_SIZE || entry[0] != ENTRY_VERSION {
+        return None;
+    }
+
+    let read_u32 = |offset: usize| {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&entry[offset..offset + 4]);
+        u32::from_le_bytes(bytes)
+    };
+    let mut seconds_bytes = [0u8; 8];
+    seconds_bytes.copy_from_slice(&entry[1..9]);
+    if u64::from_le_bytes(seconds_bytes) != seconds || read_u32(9) != nanoseconds {
+        return None;
+    }
+
+    let icon = IconPixels {
+        width: read_u32(13),
+        height: read_u32(17),
+        premultiplied: entry[21] != 0,
+        pixels: entry[HEADER_SIZE..].to_vec(),
+    };
+    match icon.pixels.len() == (icon.width as usize) * (icon.height as usize) * 4 {
+        true => Some(icon),
+        false => None,
+    }
+}
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+
+use crate::config::Config;
+use crate::error::*;
+use crate::os::shared::BrowserEntry;
+use crate::prefs::Prefs;
+use crate::routing;
+use crate::url_utils;
+
+/// Prefix of the stdout line through which `--print-choice` reports the chosen browser,
+/// followed by the browser id and its name, ie. `browser-selector-choice: 1234 Firefox`
+pub const PRINT_CHOICE_PREFIX: &str = "browser-selector-choice:";
+
+/// A browser chosen in the UI shown by `choose_in_child_process`
+#[derive(Debug, Clone)]
+pub struct Choice {
+    pub browser_id: String,
+    pub browser_name: String,
+}
+
+/// A line read from stdin in `--stdio` mode, ie. `{"url": "https://example.com", "choose": true}`
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub url: String,
+
+    /// Show the browser list when no rule decides the browser
+    #[serde(default)]
+    pub choose: bool,
+}
+
+/// A line written to stdout for each request, ie. `{"browser": "Firefox", "launched": true}`
+#[derive(Debug, Default, Serialize)]
+pub struct Response {
+    pub browser: Option<String>,
+    pub launched: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    fn from_error(error: String) -> Response {
+        Response {
+            error: Some(error),
+            ..Response::default()
+        }
+    }
+}
+
+/// Answers newline delimited JSON requests from stdin until stdin is closed.
+///
+/// Without `choose` only the config decides the browser thus no display is needed.
+/// With `choose` the browser list is shown by running this program again as a
+/// child process which reports back the chosen browser with `--print-choice`.
+pub fn run<B: BrowserEntry>(
+    browsers: &[B],
+    config: &Config,
+    prefs: &Prefs,
+    launch: impl Fn(&B, &str) -> BSResult<()>,
+) -> BSResult<()> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => handle_request(&request, browsers, config, prefs, &launch),
+            Err(e) => Response::from_error(format!("Invalid request: {}", e)),
+        };
+
+        let mut stdout = stdout.lock();
+        writeln!(stdout, "{}", serde_json::to_string(&response)?)?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
+
+fn handle_request<B: BrowserEntry>(
+    request: &Request,
+    browsers: &[B],
+    config: &Config,
+    prefs: &Prefs,
+    launch: &impl Fn(&B, &str) -> BSResult<()>,
+) -> Response {
+    let url = match config.strip_tracking {
+        true => url_utils::clean_url(&request.url),
+        false => request.url.clone(),
+    };
+    if let Some(browser) = routing::resolve_browser(browsers, config, prefs, &url) {
+        return match launch(browser, &url) {
+            Ok(_) => Response {
+                browser: Some(browser.display_name()),
+                launched: true,
+                error: None,
+            },
+            Err(e) => Response {
+                browser: Some(browser.display_name()),
+                launched: false,
+                error: Some(e.to_string()),
+            },
+        };
+    }
+
+    if !request.choose {
+        return Response::from_error(String::from("No rule matches the URL."));
+    }
+
+    let chooser_exe = match std::env::current_exe() {
+        Ok(exe_path) => exe_path,
+        Err(e) => return Response::from_error(e.to_string()),
+    };
+
+    match choose_in_child_process(&chooser_exe, &url) {
+        Ok(Some(choice)) => Response {
+            browser: Some(choice.browser_name),
+            launched: true,
+            error: None,
+        },
+        Ok(None) => Response::default(), // the user closed the window
+        Err(e) => Response::from_error(e.to_string()),
+    }
+}
+
+/// Shows the browser list by running the `chooser_exe` program, which opens the `url`
+/// in the chosen browser. Returns `None` when the window was closed without a choice.
+///
+/// The event loop can only run once per process and never returns,
+/// thus every chooser is shown by a new instance of the program.
+pub fn choose_in_child_process(
+    chooser_exe: &std::path::Path,
+    url: &str,
+) -> BSResult<Option<Choice>> {
+    let output = std::process::Command::new(chooser_exe)
+        .arg("--print-choice")
+        .arg(url)
+        .output()?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .find(|line| line.starts_with(PRINT_CHOICE_PREFIX))
+        .map(|line| {
+            let mut parts = line[PRINT_CHOICE_PREFIX.len()..].trim().splitn(2, ' ');
+            Choice {
+                browser_id: String::from(parts.next().unwrap_or_default()),
+                browser_name: String::from(parts.next().unwrap_or_default()),
+            }
+        }))
+}
@@ -0,0 +1,230 @@
+/*
+  The browser detection, the config and the chooser UI are kept in this
+  library so that other programs can embed them, see the `ffi` crate and
+  `run_selector` which shows the list and returns the chosen browser.
+  The `browser-selector` executable is a thin layer over it in main.rs.
+*/
+#[macro_use]
+extern crate simple_error;
+
+pub mod browser_profiles;
+pub mod cli;
+pub mod config;
+pub mod error;
+pub mod fuzzy;
+pub mod i18n;
+pub mod icon_cache;
+pub mod ipc;
+pub mod logging;
+pub mod os;
+pub mod prefs;
+pub mod routing;
+pub mod rules;
+pub mod selector;
+pub mod sorting;
+pub mod stats;
+pub mod store;
+pub mod temp_profile;
+pub mod ui;
+pub mod url_utils;
+
+use crate::error::BSResult;
+use crate::os::os_browsers;
+use crate::os::shared::{BrowserEntry, BrowserProvider};
+
+pub use crate::selector::run_selector;
+
+const APP_NAME: &str = "browser-selector";
+
+/// The directory of `config.toml` and of the learned preferences, created when missing.
+/// The `ENV` environment variable picks a separate directory, ie. for development.
+pub fn config_directory() -> String {
+    let env_name = std::env::var("ENV").unwrap_or("production".to_string());
+    os::util::get_create_config_directory(APP_NAME, &env_name).unwrap_or(
+        std::env::current_dir()
+            .unwrap()
+            .as_os_str()
+            .to_string_lossy()
+            .to_string(),
+    )
+}
+
+/// The directory of the machine wide `config.toml` set up by an administrator
+pub fn machine_config_directory() -> Option<String> {
+    os::util::get_machine_config_directory()
+        .map(|dir| {
+            std::path::Path::new(&dir)
+                .join(APP_NAME)
+                .to_string_lossy()
+                .to_string()
+        })
+        .ok()
+}
+
+/// Reads the machine wide config merged with the user's config, see `config::Config::load`
+pub fn load_config(config_dir: &str) -> BSResult<config::Config> {
+    config::Config::load(machine_config_directory().as_deref(), config_dir)
+}
+
+/// The browsers found on the system, without the `hidden` ones, followed by the custom
+/// ones from the `config`, narrowed down to the `only_show` list when the config has one.
+pub fn available_browsers(config: &config::Config) -> BSResult<Vec<os_browsers::Browser>> {
+    let browsers = os_browsers::SystemBrowserProvider.read_browsers()?;
+    Ok(apply_config(browsers, config, &config.custom_browsers))
+}
+
+/// Same as `available_browsers` without the version and the icon of the detected browsers,
+/// for showing the window before they are read with `os_browsers::read_details`.
+pub fn available_browsers_without_details(
+    config: &config::Config,
+) -> BSResult<Vec<os_browsers::Browser>> {
+    let browsers = os_browsers::read_browsers_without_details()?;
+    Ok(apply_config(browsers, config, &config.custom_browsers))
+}
+
+/// The programs registered for opening the links of the `scheme`, ie. the mail programs
+/// for `mailto`, without their version and icon like `available_browsers_without_details`.
+/// The `hidden` and `only_show` lists apply to them, the custom browsers are left out.
+pub fn available_scheme_handlers(
+    config: &config::Config,
+    scheme: &str,
+) -> BSResult<Vec<os_browsers::Browser>> {
+    let handlers = os_browsers::read_scheme_handlers(scheme)?;
+    Ok(apply_config(handlers, config, &[]))
+}
+
+fn apply_config(
+    mut browsers: Vec<os_browsers::Browser>,
+    config: &config::Config,
+    custom_browsers: &[config::CustomBrowser],
+) -> Vec<os_browsers::Browser> {
+    if config.browser_profiles {
+        browsers = os_browsers::expand_profiles(browsers);
+    }
+    browsers.retain(|browser| {
+        !config
+            .hidden
+            .iter()
+            .any(|reference| browser.is_referenced_by(reference))
+    });
+    browsers.extend(custom_browsers.iter().map(os_browsers::Browser::from));
+
+    let browsers = match &config.only_show {
+        Some(only_show) => filter_only_show(browsers, only_show),
+        None => browsers,
+    };
+    log::debug!(
+        "Listing {} browsers: {}",
+        browsers.len(),
+        browsers
+            .iter()
+            .map(|browser| browser.id())
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+
+    browsers
+}
+
+/// Keeps the browsers referenced in `only_show`, by id, executable name or path,
+/// in the order of the references. Each browser is kept at most once.
+fn filter_only_show<B: BrowserEntry>(browsers: Vec<B>, only_show: &[String]) -> Vec<B> {
+    let mut browsers: Vec<Option<B>> = browsers.into_iter().map(Some).collect();
+    let mut shown_browsers = Vec::<B>::new();
+
+    for reference in only_show {
+        let position = browsers.iter().position(|browser| match browser {
+            Some(browser) => browser.id() == *reference || browser.is_referenced_by(reference),
+            None => false,
+        });
+
+        match position.and_then(|position| browsers[position].take()) {
+            Some(browser) => shown_browsers.push(browser),
+            None => log::warn!(
+                "Config warning: {} under only_show was not found, it is skipped.",
+                reference
+            ),
+        }
+    }
+
+    shown_browsers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::os::shared::{LaunchMethod, TestBrowser};
+
+    fn names<B: BrowserEntry>(browsers: &[B]) -> Vec<String> {
+        browsers
+            .iter()
+            .map(|browser| browser.display_name())
+            .collect()
+    }
+
+    fn custom_browser(name: &str, exe_name: &str) -> config::CustomBrowser {
+        config::CustomBrowser {
+            name: String::from(name),
+            exe_path: format!("C:/Browsers/{}", exe_name),
+            arguments: Vec::new(),
+            icon_path: None,
+            launch: LaunchMethod::Spawn,
+        }
+    }
+
+    fn detected_browsers() -> Vec<os_browsers::Browser> {
+        [
+            custom_browser("Firefox", "firefox.exe"),
+            custom_browser("Google Chrome", "chrome.exe"),
+            custom_browser("Microsoft Edge", "msedge.exe"),
+        ]
+        .iter()
+        .map(os_browsers::Browser::from)
+        .collect()
+    }
+
+    #[test]
+    fn filter_only_show_follows_the_references() {
+        let browsers = vec![
+            TestBrowser::new("Firefox", "firefox.exe"),
+            TestBrowser::new("Google Chrome", "chrome.exe"),
+            TestBrowser::new("Microsoft Edge", "msedge.exe"),
+        ];
+        let only_show = vec![
+            String::from("msedge.exe"),
+            String::from("uninstalled.exe"),
+            browsers[0].id(),
+            String::from("C:/Browsers/firefox.exe"),
+        ];
+
+        let shown = filter_only_show(browsers, &only_show);
+        assert_eq!(names(&shown), vec!["Microsoft Edge", "Firefox"]);
+    }
+
+    #[test]
+    fn apply_config_hides_then_adds_the_custom_browsers() {
+        let config = config::Config {
+            hidden: vec![String::from("chrome.exe")],
+            ..config::Config::default()
+        };
+        let custom_browsers = vec![custom_browser("Portable Chrome", "chrome.exe")];
+
+        let browsers = apply_config(detected_browsers(), &config, &custom_browsers);
+        assert_eq!(
+            names(&browsers),
+            vec!["Firefox", "Microsoft Edge", "Portable Chrome"]
+        );
+    }
+
+    #[test]
+    fn apply_config_hidden_wins_over_only_show() {
+        let config = config::Config {
+            hidden: vec![String::from("chrome.exe")],
+            only_show: Some(vec![String::from("msedge.exe"), String::from("chrome.exe")]),
+            ..config::Config::default()
+        };
+
+        let browsers = apply_config(detected_browsers(), &config, &[]);
+        assert_eq!(names(&browsers), vec!["Microsoft Edge"]);
+    }
+}
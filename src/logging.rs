@@ -0,0 +1,124 @@
+/*
+  The program usually runs without a console, what it does is written to
+  `log.txt` in the config directory to find out afterwards why a browser did
+  not open. Once the file grows over `MAX_LOG_SIZE` it is renamed to
+  `log.old.txt` replacing the previous one, thus at most two files are kept.
+*/
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::*;
+use crate::os::util::get_local_time_text;
+
+const LOG_FILE_NAME: &str = "log.txt";
+const OLD_LOG_FILE_NAME: &str = "log.old.txt";
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+struct FileLogger {
+    dir: PathBuf,
+
+    // `None` after failing to start a new file, nothing more is written then
+    file: Mutex<Option<LogFile>>,
+
+    // also writes the records to stderr, see `--verbose`
+    echo_to_stderr: bool,
+}
+
+struct LogFile {
+    file: File,
+
+    // the bytes in the file, compared to `MAX_LOG_SIZE` before each write
+    size: u64,
+}
+
+/// Sends the records of the `log` macros to the log file in `dir`,
+/// also to stderr when `verbose`
+pub fn init(dir: &str, verbose: bool) -> BSResult<()> {
+    let dir = PathBuf::from(dir);
+    let file = open_log_file(&dir)?;
+    log::set_boxed_logger(Box::new(FileLogger {
+        dir,
+        file: Mutex::new(Some(file)),
+        echo_to_stderr: verbose,
+    }))?;
+    log::set_max_level(log::LevelFilter::Debug);
+
+    Ok(())
+}
+
+fn open_log_file(dir: &Path) -> std::io::Result<LogFile> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(LOG_FILE_NAME))?;
+    let size = file.metadata()?.len();
+
+    Ok(LogFile { file, size })
+}
+
+/// Renames the log file to the old one and starts an empty one,
+/// the log file has to be closed first
+fn rotate(dir: &Path) -> std::io::Result<LogFile> {
+    let old_path = dir.join(OLD_LOG_FILE_NAME);
+    // renaming onto an existing file fails on Windows
+    if old_path.exists() {
+        std::fs::remove_file(&old_path)?;
+    }
+    std::fs::rename(dir.join(LOG_FILE_NAME), old_path)?;
+
+    open_log_file(dir)
+}
+
+impl log::Log for FileLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "{} {:<5} {}\n",
+            get_local_time_text(),
+            record.level(),
+            record.args()
+        );
+        if self.echo_to_stderr {
+            eprint!("{}", line);
+        }
+
+        // a failing log file is not worth stopping the program for
+        let mut log_file = match self.file.lock() {
+            Ok(log_file) => log_file,
+            Err(_) => return,
+        };
+        let is_full = match log_file.as_ref() {
+            Some(log_file) => log_file.size + line.len() as u64 > MAX_LOG_SIZE,
+            None => false,
+        };
+        if is_full {
+            // closes the file which cannot be renamed while open
+            *log_file = None;
+            *log_file = rotate(&self.dir)
+                .map_err(|e| eprintln!("Could not rotate the log file. Reason: {}", e))
+                .ok();
+        }
+        if let Some(log_file) = log_file.as_mut() {
+            if log_file.file.write_all(line.as_bytes()).is_ok() {
+                log_file.size += line.len() as u64;
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut log_file) = self.file.lock() {
+            if let Some(log_file) = log_file.as_mut() {
+                let _ = log_file.file.flush();
+            }
+        }
+    }
+}
@@ -1,91 +1,504 @@
-#[macro_use]
-extern crate simple_error;
-
-mod error;
-mod os_util;
-mod ui;
-
-use ::std::hash::{Hash, Hasher};
-use std::{collections::hash_map::DefaultHasher};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+#[cfg(target_os = "windows")]
+use winit::platform::windows::WindowBuilderExtWindows;
 use winit::{
     event::{Event, WindowEvent},
-    event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    event_loop::{ControlFlow, EventLoop, EventLoopProxy},
+    platform::run_return::EventLoopExtRunReturn,
+    window::{Window, WindowBuilder},
+};
+
+use browser_selector::error::{BSError, BSResult};
+use browser_selector::os::os_browsers;
+use browser_selector::os::shared::BrowserEntry;
+use browser_selector::ui::{BrowserSelectorUI, UserInterface};
+use browser_selector::{
+    cli, config, i18n, icon_cache, ipc, logging, os, prefs, routing, rules, selector, sorting,
+    stats, store, temp_profile, ui, url_utils,
 };
 
-use crate::os_util::os_browsers;
-use ui::{BrowserSelectorUI, UserInterface};
+/// Events posted to the winit event loop from the UI event handlers
+#[derive(Debug, Clone)]
+pub enum BSEvent {
+    /// The user chose the list item with the given uuid
+    BrowserSelected(String, Action),
+
+    /// The user confirmed several list items in the `multi_select` mode
+    MultipleBrowsersSelected(Vec<String>),
+
+    /// The user dragged the list items, holds all the uuids in their new order
+    ListReordered(Vec<String>),
+
+    /// Closes the window without opening the URL
+    Close,
+
+    /// A second of the `countdown` passed, holds the seconds left
+    CountdownTick(u64),
+
+    /// The `countdown` ran out without the user interacting with the window
+    CountdownExpired,
+
+    /// The user interacted with the window which stops the `countdown`
+    CountdownCancelled,
+
+    /// A later launch handed its URL over to this window, see `single_instance`
+    NewUrl(String),
+
+    /// A link was dropped on the window, it replaces the URL being chosen for
+    UrlDropped(String),
+
+    /// The short URL, the first, leads to the second one, see `resolve_short_urls`
+    UrlResolved(String, String),
+
+    /// Puts the URL being chosen for on the clipboard
+    CopyUrl,
+
+    /// The hot key of the `--daemon` mode was pressed, shows the window for the URL
+    /// on the clipboard
+    ClipboardUrlRequested,
+
+    /// The tray icon of the `--daemon` mode was clicked, shows the window as it was left
+    ShowWindow,
+
+    /// Opens the config file from the tray menu
+    OpenSettings,
+
+    /// Ends the `--daemon` mode from the tray menu
+    Quit,
+
+    /// The version and the icon of a browser were read, it can be added to the list
+    BrowserDiscovered(os_browsers::Browser),
+
+    /// All the browsers were discovered, the list can take its final order
+    BrowsersLoaded,
+
+    /// The list item with the given uuid was highlighted long enough, its browser
+    /// can be launched, see `selection_animation`
+    LaunchAfterAnimation(String),
+}
+
+/// What follows the launch of the browser chosen in the list, decided by the modifier key held
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Opens the URL and exits
+    Launch,
+
+    /// Alt: also makes the browser the default one
+    LaunchAndSetDefault,
+
+    /// Ctrl: keeps the window open to open the same URL in another browser
+    LaunchKeepOpen,
+}
+
+impl Action {
+    fn from_modifier_keys() -> Action {
+        if os::util::is_alt_key_down() {
+            Action::LaunchAndSetDefault
+        } else if os::util::is_ctrl_key_down() {
+            Action::LaunchKeepOpen
+        } else {
+            Action::Launch
+        }
+    }
+}
+
+// How long the window stays open to confirm the default browser was changed
+const DEFAULT_CHANGED_NOTICE_DURATION: Duration = Duration::from_millis(1500);
+
+// How long each step of resolving a short URL may take, it happens in the background
+const SHORT_URL_TIMEOUT: Duration = Duration::from_secs(3);
 
-fn main() {
-    std::panic::set_hook(Box::new(|panic_info: &std::panic::PanicInfo| {
-        crate::os_util::output_panic_text(panic_info.to_string());
+// How long the chosen list item is highlighted before its browser is launched
+const SELECTION_ANIMATION_DURATION: Duration = Duration::from_millis(120);
+
+// Focus changes while the window is being shown are not the user leaving it
+const CLOSE_ON_BLUR_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+fn main() -> BSResult<()> {
+    std::panic::set_hook(Box::new(|panic_info: &std::panic::PanicHookInfo| {
+        log::error!("{}", panic_info);
+        os::util::output_panic_text(panic_info.to_string());
         std::process::exit(1);
     }));
 
-    let cli_arg_open_url = {
-        let arguments: Vec<String> = std::env::args().collect();
-        if arguments.len() >= 2 {
-            arguments[1].to_owned() // arg[0] is executable path    
-        } else { 
-            String::new() 
+    // usually there is no console to print the error to
+    run().map_err(|e| {
+        log::error!("{}", e);
+        os::util::output_error_text(e.to_string());
+        e
+    })
+}
+
+/// Everything the program does, the errors returned are shown by `main`.
+/// A launch failing in the event loop ends it, its error is returned once the loop is left.
+fn run() -> BSResult<()> {
+    let cli_args = cli::CliArgs::parse(std::env::args());
+    let cli_arg_open_url = cli_args.url.clone();
+    if cli_args.register || cli_args.unregister {
+        return match cli_args.register {
+            true => os_browsers::register_as_default(),
+            false => os_browsers::unregister(),
+        };
+    }
+    if cli_args.clear_icon_cache {
+        return icon_cache::clear();
+    }
+
+    let config_dir = browser_selector::config_directory();
+    if let Err(e) = logging::init(&config_dir, cli_args.verbose) {
+        println!("Could not open the log file. Reason: {}", e);
+    }
+    log::debug!(
+        "Started with {:?}",
+        std::env::args().skip(1).collect::<Vec<String>>()
+    );
+    i18n::init(&config_dir, cli_args.lang.as_deref());
+    if let Some(path) = &cli_args.export_config {
+        return config::export_config(&config_dir, path);
+    }
+    if let Some(path) = &cli_args.import_config {
+        return config::import_config(&config_dir, path, cli_args.replace);
+    }
+    let mut config = browser_selector::load_config(&config_dir).unwrap_or_else(|e| {
+        log::error!("Error loading config from {}. Reason: {}", config_dir, e);
+        config::Config::default()
+    });
+    if let Some(mode) = cli_args.theme {
+        config.theme.mode = mode;
+    }
+    if cli_args.daemon {
+        // nothing may open by itself while the window waits hidden for the hot key
+        config.countdown.seconds = 0;
+    }
+    // ie. a bare host given by another program gets a scheme, the input which cannot
+    // be made into a URL is shown instead of the list and nothing opens by itself
+    let (cli_arg_open_url, invalid_url_error) = match cli_arg_open_url.is_empty() {
+        true => (cli_arg_open_url, None),
+        false => match url_utils::normalize_url(&cli_arg_open_url) {
+            Ok(url) => (url, None),
+            Err(e) => (cli_arg_open_url, Some(e)),
+        },
+    };
+    if let Some(e) = &invalid_url_error {
+        log::error!("{}", e);
+        config.countdown.seconds = 0;
+    }
+    // also shown in the header so that the user sees what is opened
+    let clean_url = |url: String| match config.strip_tracking {
+        true => url_utils::clean_url(&url),
+        false => url,
+    };
+    let cli_arg_open_url = clean_url(cli_arg_open_url);
+    // opened in the same browser as the first URL, which is the one shown in the header
+    let extra_urls: Vec<String> = cli_args
+        .extra_urls
+        .iter()
+        .filter_map(|url| match url_utils::normalize_url(url) {
+            Ok(url) => Some(url),
+            Err(e) => {
+                log::warn!("Skipping the extra URL {}. Reason: {}", url, e);
+                None
+            }
+        })
+        .map(clean_url)
+        .collect();
+    for warning in rules::validate(&config.rules) {
+        log::warn!("Config warning: {}", warning);
+    }
+
+    let mut store = store::FileStore::open(&config_dir).unwrap_or_else(|e| {
+        log::error!("Error loading preferences. Reason: {}", e);
+        store::FileStore::new(&config_dir)
+    });
+    let mut prefs = prefs::Prefs::load(&store).unwrap_or_else(|e| {
+        log::error!("Error loading preferences. Reason: {}", e);
+        prefs::Prefs::default()
+    });
+    if cli_args.reset_order {
+        prefs.manual_order.clear();
+        return prefs.save(&mut store);
+    }
+    if let Some(host) = &cli_args.forget {
+        if !prefs.forget_host(host) {
+            println!("No browser was remembered for {}.", host);
         }
+        return prefs.save(&mut store);
+    }
+
+    let temp_profiles_dir = temp_profile::base_dir(&config.temp_profile.base_dir);
+    if config.temp_profile.cleanup {
+        temp_profile::cleanup(&temp_profiles_dir);
+    }
+
+    // ie. a mailto: link is offered to the mail programs instead of the web browsers
+    let handler_scheme = url_utils::scheme_of(&cli_arg_open_url)
+        .filter(|scheme| !url_utils::is_browser_scheme(scheme));
+
+    // the version and the icon of the browsers, the slow part, are read once the window is shown
+    let mut browsers: Vec<os_browsers::Browser> = match &handler_scheme {
+        Some(scheme) => browser_selector::available_scheme_handlers(&config, scheme)?,
+        None => browser_selector::available_browsers_without_details(&config)?,
     };
+    let browser_ids: Vec<String> = browsers.iter().map(|browser| browser.id()).collect();
+    // the preferences only keep track of the web browsers
+    if handler_scheme.is_none() && prefs.prune_missing_browsers(&browser_ids) {
+        if let Err(e) = prefs.save(&mut store) {
+            log::error!("Error saving preferences. Reason: {}", e);
+        }
+    }
+    if handler_scheme.is_none() {
+        for warning in routing::validate_references(&browsers, &config) {
+            log::warn!("Config warning: {}", warning);
+        }
+    }
+
+    if let Some(test_url) = &cli_args.test_url {
+        browsers.iter_mut().for_each(os_browsers::read_details);
+        print_routing_decision(&browsers, &config, &prefs, test_url);
+        return Ok(());
+    }
+
+    if cli_args.stdio {
+        browsers.iter_mut().for_each(os_browsers::read_details);
+        return ipc::run(&browsers, &config, &prefs, |browser, url| {
+            launch_browser(browser, &[String::from(url)], &cli_args, &temp_profiles_dir)
+        });
+    }
+
+    // read before our own window is created and becomes the foreground one
+    let foreground_browser = match config.prefer_foreground_browser {
+        true => match os::util::get_foreground_process_exe_path() {
+            Ok(exe_path) => browsers
+                .iter()
+                .find(|browser| browser.is_referenced_by(&exe_path)),
+            Err(e) => {
+                log::error!("Could not read the foreground program. Reason: {}", e);
+                None
+            }
+        },
+        false => None,
+    };
+    let foreground_browser_id = foreground_browser.map(|browser| browser.id());
 
-    // let env_name = std::env::var("ENV").unwrap_or("production".to_string());
-    // let config_dir = os_util::get_create_config_directory("browser-selector", &env_name).unwrap_or(
-    //     std::env::current_dir()
-    //     .unwrap()
-    //     .to_owned()
-    //     .as_os_str()
-    //     .to_string_lossy()
-    //     .to_string()
-    // );
-    // TODO: Reintroduce when config is being used
-
-    let mut ui = BrowserSelectorUI::new().expect("Failed to initialize COM or WinUI");
-    let event_loop = EventLoop::new();
-    let window = WindowBuilder::new()
+    // when the browser is decided by the config skip initializing
+    // the UI which is the slowest part of the startup
+    let decision = match cli_args.daemon {
+        // the URLs come later from the clipboard, the user chooses for them
+        true => routing::Decision::Chooser(None),
+        false => routing::decide(&browsers, &config, &prefs, &cli_arg_open_url),
+    };
+    let routed_browser = decision
+        .browser()
+        .or_else(|| foreground_browser.filter(|_| os::util::is_shift_key_down()))
+        .filter(|_| invalid_url_error.is_none());
+    if let Some(browser) = routed_browser {
+        let open_urls = [vec![cli_arg_open_url.clone()], extra_urls.clone()].concat();
+        return launch_browser(browser, &open_urls, &cli_args, &temp_profiles_dir);
+    }
+
+    // the browser the routing steps after the chooser one pick
+    let routed_preselected_id = match decision {
+        routing::Decision::Chooser(preselected) => preselected.map(|browser| browser.id()),
+        _ => None,
+    };
+
+    // the flags such as --private only apply to the URL they were given with,
+    // the same goes for the URLs given together which are opened in the same browser
+    let single_instance = config.single_instance
+        && !cli_args.private
+        && !cli_args.temp_profile
+        && !cli_args.print_choice
+        && !cli_args.dry_run
+        && !cli_args.daemon
+        && cli_args.browser_args.is_empty()
+        && extra_urls.is_empty()
+        && invalid_url_error.is_none()
+        && handler_scheme.is_none();
+    if single_instance && os::single_instance::send_to_running_instance(&cli_arg_open_url) {
+        return Ok(());
+    }
+
+    let mut ui = BrowserSelectorUI::new()?;
+    let mut event_loop = EventLoop::<BSEvent>::with_user_event();
+    if single_instance {
+        let url_event_proxy = event_loop.create_proxy();
+        if let Err(e) = os::single_instance::listen(move |url| {
+            if let Err(e) = url_event_proxy.send_event(BSEvent::NewUrl(url)) {
+                log::error!("Could not receive the URL of another launch. Reason: {}", e);
+            }
+        }) {
+            log::error!(
+                "Could not receive the URLs of other launches. Reason: {}",
+                e
+            );
+        }
+    }
+    let window_builder = WindowBuilder::new()
         .with_title(format!(
             "{} {}",
             env!("CARGO_PKG_NAME"),
             env!("CARGO_PKG_VERSION")
         ))
-        .build(&event_loop)
-        .unwrap();
-    ui.create(&window)
-        .expect("Failed to initialize WinUI XAML.");
+        .with_visible(!cli_args.daemon)
+        // otherwise it can open behind the window of the link that was clicked
+        .with_always_on_top(!cli_args.no_topmost);
+    // replaced by the drop target accepting links, see `register_url_drop`
+    #[cfg(target_os = "windows")]
+    let window_builder = window_builder.with_drag_and_drop(false);
+    let window = window_builder.build(&event_loop)?;
+    ui.create(&window)?;
+    // compared to the "Opening" line of the single browser path, see the README
+    log::debug!("Window created");
+    let drop_event_proxy = event_loop.create_proxy();
+    if let Err(e) = os::drop_target::register_url_drop(&window, move |url| {
+        if let Err(e) = drop_event_proxy.send_event(BSEvent::UrlDropped(url)) {
+            log::error!("Could not receive the dropped link. Reason: {}", e);
+        }
+    }) {
+        log::error!("Links cannot be dropped on the window. Reason: {}", e);
+    }
+    if let Err(e) = ui.set_theme(&window, &config.theme) {
+        log::error!("Could not apply the theme. Reason: {}", e);
+    }
+    if cli_args.daemon {
+        let hotkey_event_proxy = event_loop.create_proxy();
+        os::hotkey::listen(move || {
+            if let Err(e) = hotkey_event_proxy.send_event(BSEvent::ClipboardUrlRequested) {
+                log::error!("Could not show the window. Reason: {}", e);
+            }
+        })?;
+    }
+    // removed in `Event::LoopDestroyed`, it would otherwise stay until hovered
+    let mut tray_icon = None;
+    if cli_args.daemon {
+        let tray_event_proxy = event_loop.create_proxy();
+        let tooltip = format!("Browser Selector ({})", os::hotkey::HOTKEY_NAME);
+        let shown = os::tray::show(&tooltip, move |command| {
+            let event = match command {
+                os::tray::TrayCommand::ShowWindow => BSEvent::ShowWindow,
+                os::tray::TrayCommand::OpenClipboardUrl => BSEvent::ClipboardUrlRequested,
+                os::tray::TrayCommand::OpenSettings => BSEvent::OpenSettings,
+                os::tray::TrayCommand::Quit => BSEvent::Quit,
+            };
+            if let Err(e) = tray_event_proxy.send_event(event) {
+                log::error!("Could not handle the tray menu. Reason: {}", e);
+            }
+        });
+        match shown {
+            Ok(icon) => tray_icon = Some(icon),
+            Err(e) => log::error!("Could not add the tray icon. Reason: {}", e),
+        }
+    }
+
+    // only needed to order the list by them
+    let launch_counts = match config.sort {
+        sorting::SortOrder::Frequency => stats::load_counts(&config_dir).unwrap_or_else(|e| {
+            log::error!("Error loading the launch counts. Reason: {}", e);
+            HashMap::new()
+        }),
+        _ => HashMap::new(),
+    };
 
-    let browsers: Vec<os_browsers::Browser> =
-        os_browsers::read_system_browsers_sync().expect("Could not read browser list");
+    // the order by name is only approximate until the product names are read
+    sorting::order_list(&mut browsers, &config, &prefs.manual_order, &launch_counts);
 
-    let list_items: Vec<ui::ListItem<os_browsers::Browser>> = browsers
+    ui.show_shortcut_numbers(config.shortcut_numbers)?;
+    ui.set_item_layout(
+        config.effective_font_size(),
+        config.effective_item_padding(),
+    )?;
+
+    // when nothing else decides, the browser the OS would have opened the URL with
+    let system_default_id = browsers
         .iter()
-        .map(ui_list_item_from_browser)
-        .rev()
-        .collect();
+        .find(|browser| browser.is_default())
+        .map(|browser| browser.id());
+    let preselected_id = foreground_browser_id
+        .or(routed_preselected_id)
+        .or(system_default_id);
 
-    ui.set_list(&list_items)
-        .expect("Couldn't populate browsers in the UI.");
-    ui.set_url(cli_arg_open_url.as_str())
-        .expect("Couldn't render URL in the UI.");
+    show_url(&ui, &cli_arg_open_url, &config)?;
+    if let Some(e) = &invalid_url_error {
+        let close_event_proxy = event_loop.create_proxy();
+        ui.show_empty_state(&e.to_string(), i18n::t("close"), move || {
+            if let Err(e) = close_event_proxy.send_event(BSEvent::Close) {
+                log::error!("Could not close the window. Reason: {}", e);
+            }
+        })?;
+    }
+    if !extra_urls.is_empty() {
+        let count = extra_urls.len() + 1;
+        ui.set_action_text(&i18n::t_with("open_links_prompt", &[("count", &count)]))?;
+    } else if let Some(scheme) = &handler_scheme {
+        ui.set_action_text(&i18n::t_with("open_scheme_prompt", &[("scheme", scheme)]))?;
+    }
+    if config.resolve_short_urls {
+        resolve_short_url(&cli_arg_open_url, event_loop.create_proxy());
+    }
+    let event_proxy = event_loop.create_proxy();
     ui.on_list_item_selected(move |uuid| {
-        if let Some(item) = list_items.iter().find(|item| item.uuid == uuid) {
-            let mut command_arguments = item.state.arguments.clone();
-            command_arguments.push(cli_arg_open_url.clone());
+        let event = BSEvent::BrowserSelected(String::from(uuid), Action::from_modifier_keys());
 
-            std::process::Command::new(&item.state.exe_path)
-                .args(command_arguments)
-                .spawn()
-                .expect(
-                    format!("Couldn't run browser program at {}", &item.state.exe_path)
-                        .to_owned()
-                        .as_str(),
-                );
-
-            std::process::exit(0);
+        // handled by the event loop so that exiting goes through the UI teardown
+        if let Err(e) = event_proxy.send_event(event) {
+            log::error!("Could not handle the selection. Reason: {}", e);
         }
-    })
-    .expect("Cannot set on click event handler.");
+    })?;
+    let copy_event_proxy = event_loop.create_proxy();
+    ui.on_copy_url(move || {
+        if let Err(e) = copy_event_proxy.send_event(BSEvent::CopyUrl) {
+            log::error!("Could not copy the URL. Reason: {}", e);
+        }
+    })?;
+    if config.details_panel {
+        ui.show_details_panel()?;
+    }
+    if config.multi_select {
+        let multi_select_event_proxy = event_loop.create_proxy();
+        ui.enable_multi_select(move |uuids| {
+            let event = BSEvent::MultipleBrowsersSelected(uuids);
+            if let Err(e) = multi_select_event_proxy.send_event(event) {
+                log::error!("Could not handle the selection. Reason: {}", e);
+            }
+        })?;
+    }
+    let reorder_event_proxy = event_loop.create_proxy();
+    ui.on_list_reordered(move |uuids| {
+        if let Err(e) = reorder_event_proxy.send_event(BSEvent::ListReordered(uuids)) {
+            log::error!("Could not save the list order. Reason: {}", e);
+        }
+    })?;
+
+    // the browser opened when the countdown runs out, set once all the browsers are loaded
+    // and `None` when the countdown is off
+    let mut countdown_item: Option<ui::ListItem<os_browsers::Browser>> = None;
+    let countdown_cancelled = Arc::new(AtomicBool::new(config.countdown.seconds == 0));
+    if config.countdown.seconds > 0 {
+        let interaction_cancelled = countdown_cancelled.clone();
+        let interaction_event_proxy = event_loop.create_proxy();
+        ui.on_user_interaction(move || {
+            if !interaction_cancelled.swap(true, Ordering::SeqCst) {
+                if let Err(e) = interaction_event_proxy.send_event(BSEvent::CountdownCancelled) {
+                    log::error!("Could not stop the countdown. Reason: {}", e);
+                }
+            }
+        })?;
+    }
+
+    if let Err(e) = ui.fit_to_content(&window) {
+        log::error!(
+            "Could not fit the window to the browser list. Reason: {}",
+            e
+        );
+    }
+    selector::center_on_cursor_monitor(&window);
+    if !cli_args.daemon {
+        os::util::bring_to_foreground(&window);
+    }
+    start_reading_details(browsers, event_loop.create_proxy());
 
     // to load the UI from a xaml file instead:
     // use winrt::ComInterface;
@@ -94,55 +507,790 @@ fn main() {
     // let xaml = fs::read_to_string("src\\main.xaml").expect("Cant read XAML file");
     // let ui_container = XamlReader::load(xaml).expect("Failed loading XAML").query::<UIElement>();
 
-    event_loop.run(move |event, _, control_flow| {
-        *control_flow = ControlFlow::Wait;
+    // filled as the browsers are discovered, see `BSEvent::BrowserDiscovered`
+    let mut list = selector::BrowserList::<os_browsers::Browser>::new(Vec::new());
+    // the selection moved with the arrow keys is kept once the browsers are loaded
+    let mut list_navigated = false;
+
+    // the URLs handed over by later launches, the header shows the one being chosen for
+    let mut pending_urls = VecDeque::<String>::new();
+
+    // the browser chosen while its list item is highlighted, the other choices are ignored
+    let mut launch_pending = false;
+
+    let mut shown_at = Instant::now();
+    let loop_event_proxy = event_loop.create_proxy();
+    let mut exit_at: Option<Instant> = None;
+    // set when a launch fails, the loop exits and `run` returns it to `main`
+    let mut launch_error: Option<BSError> = None;
+    let loop_launch_error = &mut launch_error;
+    event_loop.run_return(move |event, _, control_flow| {
+        *control_flow = match exit_at {
+            Some(deadline) if Instant::now() >= deadline => {
+                exit_at = None;
+                dismiss_window(&window, cli_args.daemon)
+            }
+            Some(deadline) => ControlFlow::WaitUntil(deadline),
+            None => ControlFlow::Wait,
+        };
         match event {
             Event::WindowEvent {
                 event: WindowEvent::CloseRequested,
                 window_id,
-            } if window_id == window.id() => *control_flow = ControlFlow::Exit,
+            } if window_id == window.id() => {
+                *control_flow = dismiss_window(&window, cli_args.daemon)
+            }
+            // the browser was already launched, the window only shows the notice
+            Event::UserEvent(_) if exit_at.is_some() => (),
+            Event::UserEvent(user_event) => {
+                let (uuid, action) = match user_event {
+                    BSEvent::BrowserSelected(..) if launch_pending => return,
+                    BSEvent::BrowserSelected(uuid, Action::Launch)
+                        if config.selection_animation =>
+                    {
+                        if let Err(e) = ui.highlight_list_item(&uuid) {
+                            log::error!("Could not highlight the browser. Reason: {}", e);
+                        }
+                        launch_pending = true;
+                        start_launch_timer(uuid, loop_event_proxy.clone());
+                        return;
+                    }
+                    BSEvent::BrowserSelected(uuid, action) => (uuid, action),
+                    BSEvent::LaunchAfterAnimation(uuid) => {
+                        launch_pending = false;
+                        (uuid, Action::Launch)
+                    }
+                    BSEvent::MultipleBrowsersSelected(uuids) => {
+                        let open_urls = match edited_url(&ui) {
+                            Some(url) => [vec![url], extra_urls.clone()].concat(),
+                            None => return,
+                        };
+                        // every browser is launched before reporting what failed
+                        let errors: Vec<String> = list
+                            .items
+                            .iter()
+                            .filter(|item| uuids.contains(&item.uuid))
+                            .filter_map(|item| {
+                                launch_browser(
+                                    item.state.as_ref(),
+                                    &open_urls,
+                                    &cli_args,
+                                    &temp_profiles_dir,
+                                )
+                                .err()
+                                .map(|e| format!("{}: {}", item.title, e))
+                            })
+                            .collect();
+                        if !errors.is_empty() {
+                            *loop_launch_error = Some(BSError::new(&errors.join("\n")));
+                            *control_flow = ControlFlow::Exit;
+                            return;
+                        }
+
+                        match pending_urls.pop_front() {
+                            Some(next_url) => show_pending_url(
+                                &ui,
+                                &next_url,
+                                pending_urls.len(),
+                                &config,
+                                &loop_event_proxy,
+                            ),
+                            None => *control_flow = dismiss_window(&window, cli_args.daemon),
+                        }
+                        return;
+                    }
+                    BSEvent::Close => {
+                        *control_flow = dismiss_window(&window, cli_args.daemon);
+                        return;
+                    }
+                    BSEvent::ClipboardUrlRequested => {
+                        let url = os::util::read_clipboard_text().unwrap_or_else(|e| {
+                            log::error!("Could not read the clipboard. Reason: {}", e);
+                            String::new()
+                        });
+                        let url = url_utils::normalize_url(&url)
+                            .unwrap_or_else(|_| String::from(url.trim()));
+                        let url = match config.strip_tracking {
+                            true => url_utils::clean_url(&url),
+                            false => url,
+                        };
+                        show_pending_url(&ui, &url, 0, &config, &loop_event_proxy);
+                        if !url_utils::is_plausible_url(&url) {
+                            if let Err(e) = ui.set_action_text(i18n::t("clipboard_no_url")) {
+                                log::error!("Could not show that there is no URL. Reason: {}", e);
+                            }
+                        }
+
+                        // the filter typed the previous time is gone
+                        list.filter.clear();
+                        if let Err(e) = list.refilter(&mut ui) {
+                            log::error!("Could not show the browsers. Reason: {}", e);
+                        }
+
+                        show_window(&window);
+                        shown_at = Instant::now();
+                        return;
+                    }
+                    BSEvent::ShowWindow => {
+                        show_window(&window);
+                        shown_at = Instant::now();
+                        return;
+                    }
+                    BSEvent::OpenSettings => {
+                        let opened = config::create_config_file(&config_dir).and_then(|path| {
+                            os::util::open_with_default_program(&path.to_string_lossy())
+                        });
+                        if let Err(e) = opened {
+                            log::error!("Could not open the config file. Reason: {}", e);
+                        }
+                        return;
+                    }
+                    BSEvent::Quit => {
+                        *control_flow = ControlFlow::Exit;
+                        return;
+                    }
+                    BSEvent::CountdownTick(seconds_left) => {
+                        if let (false, Some(item)) =
+                            (countdown_cancelled.load(Ordering::SeqCst), &countdown_item)
+                        {
+                            let text = i18n::t_with(
+                                "opening_in",
+                                &[("browser", &item.title), ("seconds", &seconds_left)],
+                            );
+                            if let Err(e) = ui.set_action_text(&text) {
+                                log::error!("Could not show the countdown. Reason: {}", e);
+                            }
+                        }
+                        return;
+                    }
+                    BSEvent::CountdownExpired => {
+                        match (countdown_cancelled.load(Ordering::SeqCst), &countdown_item) {
+                            (false, Some(item)) => (item.uuid.clone(), Action::Launch),
+                            _ => return,
+                        }
+                    }
+                    BSEvent::NewUrl(url) => {
+                        pending_urls.push_back(match config.strip_tracking {
+                            true => url_utils::clean_url(&url),
+                            false => url,
+                        });
+                        let text = i18n::t_with("links_waiting", &[("count", &pending_urls.len())]);
+                        if let Err(e) = ui.set_action_text(&text) {
+                            log::error!("Could not show the waiting links. Reason: {}", e);
+                        }
+                        return;
+                    }
+                    BSEvent::UrlDropped(url) => {
+                        let url = match config.strip_tracking {
+                            true => url_utils::clean_url(&url),
+                            false => url,
+                        };
+                        show_pending_url(&ui, &url, pending_urls.len(), &config, &loop_event_proxy);
+                        return;
+                    }
+                    BSEvent::UrlResolved(short_url, resolved_url) => {
+                        // the user may have edited the URL or moved on to another one meanwhile
+                        if ui.get_url() == short_url {
+                            let resolved_url = match config.strip_tracking {
+                                true => url_utils::clean_url(&resolved_url),
+                                false => resolved_url,
+                            };
+                            if let Err(e) = show_url(&ui, &resolved_url, &config) {
+                                log::error!("Could not show where the URL leads. Reason: {}", e);
+                            }
+                        }
+                        return;
+                    }
+                    BSEvent::CopyUrl => {
+                        let text = match os::util::copy_to_clipboard(&ui.get_url()) {
+                            Ok(()) => i18n::t("copied"),
+                            Err(e) => {
+                                log::error!("Could not copy the URL. Reason: {}", e);
+                                i18n::t("copy_failed")
+                            }
+                        };
+                        if let Err(e) = ui.set_action_text(text) {
+                            log::error!("Could not show that the URL was copied. Reason: {}", e);
+                        }
+                        return;
+                    }
+                    BSEvent::BrowserDiscovered(browser) => {
+                        let item = selector::list_item_from_browser(
+                            &browser,
+                            &config,
+                            window.scale_factor(),
+                        );
+                        list.items.push(item.clone());
+                        if config.group_by == sorting::GroupBy::Vendor {
+                            // kept under their header until `BrowsersLoaded` orders them
+                            list.items.sort_by_key(|item| {
+                                let group = item.group.clone().unwrap_or_default().to_lowercase();
+                                (group.is_empty(), group)
+                            });
+                        }
+                        if list.filter.is_empty() && config.group_by == sorting::GroupBy::None {
+                            if let Err(e) = ui.append_list_item(&item) {
+                                log::error!("Could not show {}. Reason: {}", item.title, e);
+                            }
+                            list.visible_items.push(item);
+                        } else {
+                            // a better match than the ones shown or the header of the
+                            // vendor of the browser takes its place among them
+                            if let Err(e) = list.refilter(&mut ui) {
+                                log::error!("Could not filter the browsers. Reason: {}", e);
+                            }
+                        }
+                        if let Err(e) = ui.fit_to_content(&window) {
+                            log::error!(
+                                "Could not fit the window to the browser list. Reason: {}",
+                                e
+                            );
+                        }
+                        return;
+                    }
+                    BSEvent::BrowsersLoaded => {
+                        // the browsers were discovered in the order of their registry names
+                        let mut loaded_browsers: Vec<os_browsers::Browser> = list
+                            .items
+                            .iter()
+                            .map(|item| item.state.as_ref().clone())
+                            .collect();
+                        sorting::order_list(
+                            &mut loaded_browsers,
+                            &config,
+                            &prefs.manual_order,
+                            &launch_counts,
+                        );
+                        let ordered_ids: Vec<String> =
+                            loaded_browsers.iter().map(|browser| browser.id()).collect();
+                        list.items.sort_by_key(|item| {
+                            ordered_ids
+                                .iter()
+                                .position(|id| *id == item.uuid)
+                                .unwrap_or(usize::MAX)
+                        });
+
+                        let selected_id = match list_navigated {
+                            true => ui
+                                .get_selected_list_item()
+                                .unwrap_or(None)
+                                .map(|item| item.uuid),
+                            false => preselected_id.clone(),
+                        };
+                        if let Err(e) = list.refilter(&mut ui) {
+                            log::error!("Could not sort the browsers. Reason: {}", e);
+                        }
+                        if let Some(index) = selected_id.and_then(|browser_id| {
+                            list.visible_items
+                                .iter()
+                                .position(|item| item.uuid == browser_id)
+                        }) {
+                            if let Err(e) = ui.select_list_item_by_index(index as u32) {
+                                log::error!("Could not pre-select the browser. Reason: {}", e);
+                            }
+                        }
+
+                        if list.items.is_empty() {
+                            // ie. on locked down machines where the browsers are installed
+                            // in unusual ways, or no mail program for a mailto: link
+                            let empty_text = match &handler_scheme {
+                                Some(scheme) => {
+                                    i18n::t_with("no_scheme_handler", &[("scheme", scheme)])
+                                }
+                                None => String::from(i18n::t("no_browsers_found")),
+                            };
+                            if let Err(e) = ui.show_empty_state(
+                                &empty_text,
+                                i18n::t("open_default_apps"),
+                                || {
+                                    if let Err(e) = os_browsers::open_default_apps_settings() {
+                                        log::error!(
+                                            "Could not open the default apps settings. Reason: {}",
+                                            e
+                                        );
+                                    }
+                                },
+                            ) {
+                                log::error!(
+                                    "Could not show that no browser was found. Reason: {}",
+                                    e
+                                );
+                            }
+                        }
+
+                        countdown_item =
+                            match (config.countdown.seconds, &config.countdown.browser) {
+                                (0, _) => None,
+                                (_, Some(reference)) => list
+                                    .items
+                                    .iter()
+                                    .find(|item| item.state.is_referenced_by(reference)),
+                                // set_list selects the first item when nothing is pre-selected
+                                (_, None) => preselected_id
+                                    .as_ref()
+                                    .and_then(|browser_id| {
+                                        list.items.iter().find(|item| item.uuid == *browser_id)
+                                    })
+                                    .or(list.items.first()),
+                            }
+                            .cloned();
+                        // the user may already have interacted with the window
+                        match (&countdown_item, countdown_cancelled.load(Ordering::SeqCst)) {
+                            (Some(_), false) => start_countdown(
+                                config.countdown.seconds,
+                                countdown_cancelled.clone(),
+                                loop_event_proxy.clone(),
+                            ),
+                            _ => countdown_cancelled.store(true, Ordering::SeqCst),
+                        }
+
+                        if let Err(e) = ui.fit_to_content(&window) {
+                            log::error!(
+                                "Could not fit the window to the browser list. Reason: {}",
+                                e
+                            );
+                        }
+                        selector::center_on_cursor_monitor(&window);
+                        return;
+                    }
+                    BSEvent::CountdownCancelled => {
+                        if let Err(e) = ui.set_action_text("") {
+                            log::error!("Could not hide the countdown. Reason: {}", e);
+                        }
+                        return;
+                    }
+                    BSEvent::ListReordered(uuids) => {
+                        // the number keys follow the order on screen, the items
+                        // hidden by the filter keep their order at the end
+                        list.items.sort_by_key(|item| {
+                            uuids
+                                .iter()
+                                .position(|uuid| *uuid == item.uuid)
+                                .unwrap_or(usize::MAX)
+                        });
+                        list.visible_items = selector::filter_list_items(&list.items, &list.filter);
+                        if config.shortcut_numbers {
+                            if let Err(e) = ui.set_list(&list.visible_items) {
+                                log::error!("Could not renumber the browsers. Reason: {}", e);
+                            }
+                        }
+
+                        if handler_scheme.is_some() {
+                            return;
+                        }
+                        prefs.manual_order =
+                            list.items.iter().map(|item| item.uuid.clone()).collect();
+                        if let Err(e) = prefs.save(&mut store) {
+                            log::error!("Error saving preferences. Reason: {}", e);
+                        }
+                        return;
+                    }
+                };
+
+                if let Some(item) = list.items.iter().find(|item| item.uuid == uuid) {
+                    let open_url = match edited_url(&ui) {
+                        Some(url) => url,
+                        None => return,
+                    };
+                    // a dry run leaves the choices and the launch counts as they were
+                    if !cli_args.dry_run {
+                        if let (true, Some(host)) =
+                            (config.remember_per_host, url_utils::host_of(&open_url))
+                        {
+                            prefs.set_host_choice(&host, &uuid);
+                        }
+                        match (ui.is_always_use_checked(), url_utils::host_of(&open_url)) {
+                            (Ok(true), Some(host)) => prefs.set_always_use(&host, &uuid),
+                            (Ok(_), _) => (),
+                            (Err(e), _) => {
+                                log::error!("Could not read the Always use box. Reason: {}", e)
+                            }
+                        }
+                        // a mail program would otherwise be offered for the web links
+                        if handler_scheme.is_none() {
+                            if action == Action::LaunchAndSetDefault {
+                                prefs.default_browser = Some(uuid.clone());
+                            }
+                            prefs.last_choice = Some(uuid.clone());
+                        }
+                        if let Err(e) = prefs.save(&mut store) {
+                            log::error!("Error saving preferences. Reason: {}", e);
+                        }
+                        // counted whatever the sort so that the counts are there when it changes
+                        if let Err(e) = stats::record_launch(&config_dir, &uuid) {
+                            log::error!("Error counting the launch. Reason: {}", e);
+                        }
+                    }
+
+                    let open_urls = [vec![open_url], extra_urls.clone()].concat();
+                    if let Err(e) = launch_browser(
+                        item.state.as_ref(),
+                        &open_urls,
+                        &cli_args,
+                        &temp_profiles_dir,
+                    ) {
+                        *loop_launch_error = Some(e);
+                        *control_flow = ControlFlow::Exit;
+                        return;
+                    }
+
+                    // the window stays open for the links handed over by later launches
+                    if action != Action::LaunchKeepOpen {
+                        if let Some(next_url) = pending_urls.pop_front() {
+                            show_pending_url(
+                                &ui,
+                                &next_url,
+                                pending_urls.len(),
+                                &config,
+                                &loop_event_proxy,
+                            );
+                            return;
+                        }
+                    }
+
+                    match action {
+                        Action::Launch => *control_flow = dismiss_window(&window, cli_args.daemon),
+                        Action::LaunchAndSetDefault => {
+                            let notice = i18n::t_with("now_default", &[("browser", &item.title)]);
+                            if let Err(e) = ui.set_action_text(&notice) {
+                                log::error!("Could not show the notice. Reason: {}", e);
+                            }
+
+                            let deadline = Instant::now() + DEFAULT_CHANGED_NOTICE_DURATION;
+                            exit_at = Some(deadline);
+                            *control_flow = ControlFlow::WaitUntil(deadline);
+                        }
+                        Action::LaunchKeepOpen => (),
+                    }
+                }
+            }
             Event::WindowEvent {
-                event: WindowEvent::Resized(_size),
+                event: WindowEvent::ThemeChanged(_),
                 ..
             } => {
-                ui.update_layout_size(&window, &_size).unwrap();
-                // this causes a memory violation
-                // when the program is closed but does work correclty
-                // while the program is running
+                // sent when the app mode changes in the Windows settings
+                if let Err(e) = ui.set_theme(&window, &config.theme) {
+                    log::error!("Could not apply the theme. Reason: {}", e);
+                }
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Focused(false),
+                ..
+            // the focus moving into the XAML island is still within the window
+            } if config.close_on_blur
+                && shown_at.elapsed() > CLOSE_ON_BLUR_GRACE_PERIOD
+                && !os::util::is_foreground_window(&window) =>
+            {
+                if let Err(e) = loop_event_proxy.send_event(BSEvent::Close) {
+                    log::error!("Could not close the window. Reason: {}", e);
+                }
             }
             Event::WindowEvent {
                 event: WindowEvent::KeyboardInput { input, .. },
                 ..
-            } if input.state == winit::event::ElementState::Pressed => {}
+            } if input.state == winit::event::ElementState::Pressed
+                && input.virtual_keycode == Some(winit::event::VirtualKeyCode::C)
+                && os::util::is_ctrl_key_down() =>
+            {
+                cancel_countdown(&countdown_cancelled, &ui);
+                if let Err(e) = loop_event_proxy.send_event(BSEvent::CopyUrl) {
+                    log::error!("Could not copy the URL. Reason: {}", e);
+                }
+            }
+            Event::WindowEvent { event, .. } => {
+                let typing = match &event {
+                    WindowEvent::KeyboardInput { input, .. } => {
+                        input.state == winit::event::ElementState::Pressed
+                    }
+                    WindowEvent::ReceivedCharacter(_) => true,
+                    _ => false,
+                };
+                if typing {
+                    cancel_countdown(&countdown_cancelled, &ui);
+                }
+
+                let event = match list.handle_window_event(&mut ui, &window, &event) {
+                    Some(selector::ListEvent::Chosen(uuid)) => {
+                        BSEvent::BrowserSelected(uuid, Action::from_modifier_keys())
+                    }
+                    Some(selector::ListEvent::Dismissed) => BSEvent::Close,
+                    Some(selector::ListEvent::Navigated) => {
+                        list_navigated = true;
+                        return;
+                    }
+                    None => return,
+                };
+                if let Err(e) = loop_event_proxy.send_event(event) {
+                    log::error!("Could not handle the key. Reason: {}", e);
+                }
+            }
+            Event::LoopDestroyed => {
+                tray_icon.take();
+
+                selector::release_ui(&mut ui);
+            }
             _ => (),
         }
     });
+
+    match launch_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Shows the `url` in the header, only its scheme and host when `mask_url` is on
+fn show_url<T: Clone>(
+    ui: &BrowserSelectorUI<T>,
+    url: &str,
+    config: &config::Config,
+) -> BSResult<()> {
+    match config.mask_url {
+        true => ui.set_masked_url(url, url_utils::mask_url(url).as_str())?,
+        false => ui.set_url(url)?,
+    }
+
+    // ticking "Always use" would not make a difference when the step is not consulted first
+    let always_use =
+        routing::opens_directly(&config.routing_order, routing::RoutingStep::AlwaysUse);
+    let always_use_host = url_utils::host_of(url).filter(|_| always_use);
+    ui.set_always_use_host(always_use_host.as_deref())
 }
 
-fn ui_list_item_from_browser(browser: &os_browsers::Browser) -> ui::ListItem<os_browsers::Browser> {
-    let image = BrowserSelectorUI::<os_browsers::Browser>::load_image(browser.exe_path.as_str())
-        .unwrap_or_default();
+/// The URL in the header with the changes the user made to it, see `url_utils::normalize_url`,
+/// `None` after asking the user to fix it when it no longer looks like a URL
+fn edited_url<T: Clone>(ui: &BrowserSelectorUI<T>) -> Option<String> {
+    match url_utils::normalize_url(&ui.get_url()) {
+        Ok(url) => Some(url),
+        Err(_) => {
+            if let Err(e) = ui.set_action_text(i18n::t("not_a_url")) {
+                log::error!("Could not show that the URL is not valid. Reason: {}", e);
+            }
+            None
+        }
+    }
+}
 
-    let uuid = {
-        let mut hasher = DefaultHasher::new();
-        browser.exe_path.hash(&mut hasher);
-        hasher.finish().to_string()
+/// Exits once the choice is made, in the `--daemon` mode the window is hidden
+/// instead and waits for the hot key
+fn dismiss_window(window: &Window, daemon: bool) -> ControlFlow {
+    match daemon {
+        true => {
+            window.set_visible(false);
+            ControlFlow::Wait
+        }
+        false => ControlFlow::Exit,
+    }
+}
+
+/// Brings the window hidden by the `--daemon` mode back on the monitor of the mouse cursor
+fn show_window(window: &Window) {
+    selector::center_on_cursor_monitor(window);
+    window.set_visible(true);
+    os::util::bring_to_foreground(window);
+}
+
+/// Moves on to the next `url` handed over by a later launch, `waiting` more are left after it
+fn show_pending_url<T: Clone>(
+    ui: &BrowserSelectorUI<T>,
+    url: &str,
+    waiting: usize,
+    config: &config::Config,
+    event_proxy: &EventLoopProxy<BSEvent>,
+) {
+    if let Err(e) = show_url(ui, url, config) {
+        log::error!("Could not show the next link. Reason: {}", e);
+    }
+    if config.resolve_short_urls {
+        resolve_short_url(url, event_proxy.clone());
+    }
+
+    let text = match waiting {
+        0 => String::new(),
+        waiting => i18n::t_with("links_waiting", &[("count", &waiting)]),
     };
+    if let Err(e) = ui.set_action_text(&text) {
+        log::error!("Could not show the waiting links. Reason: {}", e);
+    }
+}
+
+/// Posts `UrlResolved` with where the `url` leads when it is a short one, from another thread
+/// as the request can take seconds. Meanwhile the `url` as it is can be opened.
+fn resolve_short_url(url: &str, event_proxy: EventLoopProxy<BSEvent>) {
+    if !url_utils::is_short_url(url) {
+        return;
+    }
 
-    ui::ListItem {
-        title: browser.version.product_name.clone(),
-        subtitle: vec![
-            browser.version.product_version.clone(),
-            browser.version.binary_type.to_string(),
-            browser.version.company_name.clone(),
-            browser.version.file_description.clone(),
-        ]
-        .into_iter()
-        .filter(|itm| itm.len() > 0)
-        .collect::<Vec<String>>()
-        .join(" | "),
-        image,
-        uuid,
-        state: std::rc::Rc::new(browser.clone()),
+    let url = String::from(url);
+    std::thread::spawn(move || {
+        if let Some(resolved_url) = url_utils::resolve_short_url(&url, SHORT_URL_TIMEOUT) {
+            // the event loop is gone when this fails, ie. the URL was already opened
+            let _ = event_proxy.send_event(BSEvent::UrlResolved(url, resolved_url));
+        }
+    });
+}
+
+/// Opens the `urls` in the given `browser` applying the launch options from the command line
+fn launch_browser<B: BrowserEntry>(
+    browser: &B,
+    urls: &[String],
+    cli_args: &cli::CliArgs,
+    temp_profiles_dir: &std::path::Path,
+) -> BSResult<()> {
+    let mut extra_args = match cli_args.temp_profile {
+        true => temp_profile::create_profile_args(&browser.family(), temp_profiles_dir)
+            .unwrap_or_else(|e| {
+                log::error!("Could not create a temporary profile. Reason: {}", e);
+                Vec::new()
+            }),
+        false => Vec::new(),
+    };
+    if cli_args.private {
+        match browser.private_mode_flag() {
+            Some(flag) => extra_args.push(String::from(flag)),
+            None => log::warn!(
+                "{} has no known private mode, the URL is opened normally.",
+                browser.display_name()
+            ),
+        }
+    }
+    // each one stays a single argument of the browser, spaces included
+    extra_args.extend_from_slice(&cli_args.browser_args);
+
+    if cli_args.dry_run {
+        println!("Browser: {}", browser.display_name());
+        println!("Executable: {}", browser.exe_path());
+        for command_line in browser.launch_command_lines(urls, &extra_args) {
+            println!("Command line: {}", command_line);
+        }
+        return Ok(());
+    }
+
+    log::info!("Opening {} in {}", urls.join(" "), browser.id());
+    if let Err(e) = browser.launch_all(urls, &extra_args) {
+        log::error!("Could not open {}. Reason: {}", browser.display_name(), e);
+        return Err(e);
+    }
+    if cli_args.print_choice {
+        println!(
+            "{} {} {}",
+            ipc::PRINT_CHOICE_PREFIX,
+            browser.id(),
+            browser.display_name()
+        );
+    }
+
+    Ok(())
+}
+
+/// Dry run of the routing of the `url` for `--test-url`, nothing is opened
+fn print_routing_decision<B: BrowserEntry>(
+    browsers: &[B],
+    config: &config::Config,
+    prefs: &prefs::Prefs,
+    url: &str,
+) {
+    match routing::decide(browsers, config, prefs, url) {
+        routing::Decision::Rule(rule, browser) => println!(
+            "The rule \"{}\" matches, {} would open the URL.",
+            rule.pattern,
+            browser.display_name()
+        ),
+        routing::Decision::SchemeDefault(scheme, browser) => println!(
+            "{} would open the URL being the default for {}.",
+            browser.display_name(),
+            scheme
+        ),
+        routing::Decision::AlwaysUse(host, browser) => println!(
+            "{} would open the URL being always used for {}.",
+            browser.display_name(),
+            host
+        ),
+        routing::Decision::HostChoice(host, browser) => println!(
+            "{} would open the URL being the last choice for {}.",
+            browser.display_name(),
+            host
+        ),
+        routing::Decision::DefaultBrowser(browser) => println!(
+            "{} would open the URL being the default browser.",
+            browser.display_name()
+        ),
+        routing::Decision::LastChoice(browser) => println!(
+            "{} would open the URL being the last choice.",
+            browser.display_name()
+        ),
+        routing::Decision::SingleBrowser(browser) => println!(
+            "No rule matches, {} would open the URL being the only browser.",
+            browser.display_name()
+        ),
+        routing::Decision::Chooser(Some(browser)) => println!(
+            "No rule matches, would show the chooser with {} pre-selected.",
+            browser.display_name()
+        ),
+        routing::Decision::Chooser(None) => println!("No rule matches, would show the chooser."),
+    }
+}
+
+/// Posts a `CountdownTick` every second from `seconds` down to 1 followed by
+/// `CountdownExpired`, from another thread as the event loop has to keep running.
+/// Stops as soon as `cancelled` is set.
+fn start_countdown(seconds: u64, cancelled: Arc<AtomicBool>, event_proxy: EventLoopProxy<BSEvent>) {
+    std::thread::spawn(move || {
+        for seconds_left in (1..=seconds).rev() {
+            if cancelled.load(Ordering::SeqCst)
+                || event_proxy
+                    .send_event(BSEvent::CountdownTick(seconds_left))
+                    .is_err()
+            {
+                return;
+            }
+            std::thread::sleep(Duration::from_secs(1));
+        }
+
+        if !cancelled.load(Ordering::SeqCst) {
+            // the event loop is gone when this fails, there is nothing left to open
+            let _ = event_proxy.send_event(BSEvent::CountdownExpired);
+        }
+    });
+}
+
+/// Posts `LaunchAfterAnimation` for the `uuid` once `SELECTION_ANIMATION_DURATION` passed,
+/// from another thread as the event loop has to keep drawing the highlight.
+fn start_launch_timer(uuid: String, event_proxy: EventLoopProxy<BSEvent>) {
+    std::thread::spawn(move || {
+        std::thread::sleep(SELECTION_ANIMATION_DURATION);
+        // the event loop is gone when this fails, the window was closed meanwhile
+        let _ = event_proxy.send_event(BSEvent::LaunchAfterAnimation(uuid));
+    });
+}
+
+/// Reads the version and the icon of the `browsers` on another thread, each browser is
+/// posted as `BrowserDiscovered` once read and `BrowsersLoaded` follows the last one.
+fn start_reading_details(
+    browsers: Vec<os_browsers::Browser>,
+    event_proxy: EventLoopProxy<BSEvent>,
+) {
+    std::thread::spawn(move || {
+        for mut browser in browsers {
+            os_browsers::read_details(&mut browser);
+            // the event loop is gone when this fails, ie. the window was already closed
+            if event_proxy
+                .send_event(BSEvent::BrowserDiscovered(browser))
+                .is_err()
+            {
+                return;
+            }
+        }
+
+        let _ = event_proxy.send_event(BSEvent::BrowsersLoaded);
+    });
+}
+
+/// Stops the countdown, the text showing it is cleared the first time only
+fn cancel_countdown<T: Clone>(cancelled: &AtomicBool, ui: &BrowserSelectorUI<T>) {
+    if !cancelled.swap(true, Ordering::SeqCst) {
+        if let Err(e) = ui.set_action_text("") {
+            log::error!("Could not hide the countdown. Reason: {}", e);
+        }
     }
 }
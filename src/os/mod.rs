@@ -0,0 +1,58 @@
+/*
+  Platform specific code lives in one module per OS and only the module
+  matching the compilation target is built. The rest of the program should
+  only use the `os_browsers`, `single_instance`, `drop_target`, `hotkey`, `tray`
+  and `util` re-exports below. The other targets get the stand-ins from
+  `unsupported` so that the library and its tests build there too.
+*/
+pub mod shared;
+
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(not(target_os = "windows"))]
+mod unsupported;
+
+pub mod os_browsers {
+    #[cfg(not(target_os = "windows"))]
+    pub use super::unsupported::sys_browsers::*;
+    #[cfg(target_os = "windows")]
+    pub use super::windows::icons::*;
+    #[cfg(target_os = "windows")]
+    pub use super::windows::sys_browsers::*;
+}
+
+pub mod single_instance {
+    #[cfg(not(target_os = "windows"))]
+    pub use super::unsupported::single_instance::*;
+    #[cfg(target_os = "windows")]
+    pub use super::windows::single_instance::*;
+}
+
+pub mod util {
+    #[cfg(not(target_os = "windows"))]
+    pub use super::unsupported::util::*;
+    #[cfg(target_os = "windows")]
+    pub use super::windows::util::*;
+}
+
+pub mod drop_target {
+    #[cfg(not(target_os = "windows"))]
+    pub use super::unsupported::drop_target::*;
+    #[cfg(target_os = "windows")]
+    pub use super::windows::drop_target::*;
+}
+
+pub mod hotkey {
+    #[cfg(not(target_os = "windows"))]
+    pub use super::unsupported::hotkey::*;
+    #[cfg(target_os = "windows")]
+    pub use super::windows::hotkey::*;
+}
+
+pub mod tray {
+    #[cfg(not(target_os = "windows"))]
+    pub use super::unsupported::tray::*;
+    #[cfg(target_os = "windows")]
+    pub use super::windows::tray::*;
+}
@@ -0,0 +1,253 @@
+use crate::error::BSResult;
+use serde::Deserialize;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Platform independent view of a browser program. Each platform has its own
+/// `Browser` structure with whatever the OS exposes, the UI and the launching
+/// code should only rely on what is available through this trait.
+pub trait BrowserEntry {
+    /// Identifier that is stable between runs of the program for the same browser
+    fn id(&self) -> String;
+
+    /// User friendly name, used as the title of the list item
+    fn display_name(&self) -> String;
+
+    /// Details such as version or publisher, the UI skips the empty ones
+    fn subtitle_parts(&self) -> Vec<String>;
+
+    /// Labeled details shown in the details panel, ie. `("Version", "88.0")`,
+    /// the UI skips the empty ones
+    fn details(&self) -> Vec<(String, String)>;
+
+    /// Path to the file from which the browser icon can be loaded
+    fn icon_source(&self) -> String;
+
+    /// The name of the publisher, ie. `Mozilla`, empty when unknown
+    fn vendor(&self) -> String;
+
+    /// Path to the browser executable
+    fn exe_path(&self) -> String;
+
+    /// The program and the arguments run to open the URLs, as typed on a command line
+    fn command_line(&self) -> String;
+
+    /// The command lines `launch_all` runs to open the `urls` with the `extra_args`,
+    /// one per start of the browser program
+    fn launch_command_lines(&self, urls: &[String], extra_args: &[String]) -> Vec<String>;
+
+    /// The browser engine family, used for engine specific command line flags
+    fn family(&self) -> BrowserFamily;
+
+    /// The command line flag opening the URL in a private window, `None` when unknown
+    fn private_mode_flag(&self) -> Option<&'static str>;
+
+    /// Whether the OS is set to open links with this browser
+    fn is_default(&self) -> bool;
+
+    /// Whether the given config `reference` (executable file name or full path) points to this browser
+    fn is_referenced_by(&self, reference: &str) -> bool;
+
+    /// Starts the browser program opening the given `url`,
+    /// `extra_args` are passed after the browser's own arguments and before the URL
+    fn launch(&self, url: &str, extra_args: &[String]) -> BSResult<()>;
+
+    /// Starts the browser program opening all the `urls`, by default once per URL.
+    /// Browsers taking several URLs on their command line start once for all of them.
+    fn launch_all(&self, urls: &[String], extra_args: &[String]) -> BSResult<()> {
+        for url in urls {
+            self.launch(url, extra_args)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Discovers the browsers installed on the OS, each platform has one implementation
+/// named `os_browsers::SystemBrowserProvider`. Adding a platform is a matter of
+/// one more implementation with its own `BrowserEntry`.
+pub trait BrowserProvider {
+    type Browser: BrowserEntry + Clone;
+
+    /// The browsers registered on the system
+    fn read_browsers(&self) -> BSResult<Vec<Self::Browser>>;
+
+    /// Starts the given `browser` opening the `url`
+    fn open(&self, url: &str, browser: &Self::Browser) -> BSResult<()> {
+        browser.launch(url, &[])
+    }
+}
+
+/// How a browser program is started, set per custom browser with `launch` in the config file
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchMethod {
+    /// Runs the executable directly
+    #[default]
+    Spawn,
+
+    /// Goes through the shell "open" verb like Explorer does, which
+    /// some programs need for their single instance handling or for elevation
+    ShellOpen,
+}
+
+/// Groups browsers accepting the same command line flags
+#[derive(Debug, Clone, PartialEq)]
+pub enum BrowserFamily {
+    Chromium,
+    Firefox,
+    Unknown,
+}
+
+impl BrowserFamily {
+    /// Deducts the family out of the browser executable file name
+    pub fn from_exe_path(path: &str) -> BrowserFamily {
+        let file_name = std::path::Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match file_name.as_str() {
+            "chrome" | "chromium" | "msedge" | "brave" | "vivaldi" | "opera" => {
+                BrowserFamily::Chromium
+            }
+            "firefox" | "waterfox" | "librewolf" | "palemoon" => BrowserFamily::Firefox,
+            _ => BrowserFamily::Unknown,
+        }
+    }
+}
+
+// Flags opening a private window by executable file name, the Chromium based
+// browsers do not agree on one and Firefox takes the URL as the flag value
+const PRIVATE_MODE_FLAGS: [(&str, &str); 10] = [
+    ("chrome", "--incognito"),
+    ("chromium", "--incognito"),
+    ("brave", "--incognito"),
+    ("vivaldi", "--incognito"),
+    ("msedge", "--inprivate"),
+    ("opera", "--private"),
+    ("firefox", "-private-window"),
+    ("waterfox", "-private-window"),
+    ("librewolf", "-private-window"),
+    ("palemoon", "-private-window"),
+];
+
+/// The flag opening a private window for the browser at `exe_path`, `None` when unknown
+pub fn private_mode_flag(exe_path: &str) -> Option<&'static str> {
+    let file_name = std::path::Path::new(exe_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())?;
+
+    PRIVATE_MODE_FLAGS
+        .iter()
+        .find(|(name, _)| *name == file_name)
+        .map(|(_, flag)| *flag)
+}
+
+/// Case insensitive comparison of a browser reference from the config file
+/// which can either be the full path to the executable or just its file name.
+pub fn exe_path_matches(exe_path: &str, reference: &str) -> bool {
+    let reference = reference.to_lowercase();
+    let file_name = std::path::Path::new(exe_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    exe_path.to_lowercase() == reference || file_name == reference
+}
+
+/// Computes an identifier out of the given `path` to the browser program
+pub fn id_from_path(path: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    hasher.finish().to_string()
+}
+
+/// A browser made up by the tests of the code working with any `BrowserEntry`,
+/// identified by its executable like the detected browsers
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub struct TestBrowser {
+    pub name: String,
+    pub exe_path: String,
+    pub vendor: String,
+    pub is_default: bool,
+}
+
+#[cfg(test)]
+impl TestBrowser {
+    /// A browser called `name` whose executable is `exe_name` in a made up directory,
+    /// the forward slash separates the file name on every platform
+    pub fn new(name: &str, exe_name: &str) -> TestBrowser {
+        TestBrowser {
+            name: String::from(name),
+            exe_path: format!("C:/Browsers/{}", exe_name),
+            ..TestBrowser::default()
+        }
+    }
+}
+
+#[cfg(test)]
+impl BrowserEntry for TestBrowser {
+    fn id(&self) -> String {
+        id_from_path(&self.exe_path)
+    }
+
+    fn display_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn subtitle_parts(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn details(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    fn icon_source(&self) -> String {
+        self.exe_path.clone()
+    }
+
+    fn vendor(&self) -> String {
+        self.vendor.clone()
+    }
+
+    fn exe_path(&self) -> String {
+        self.exe_path.clone()
+    }
+
+    fn command_line(&self) -> String {
+        self.exe_path.clone()
+    }
+
+    fn launch_command_lines(&self, urls: &[String], extra_args: &[String]) -> Vec<String> {
+        vec![[
+            vec![self.exe_path.clone()],
+            extra_args.to_vec(),
+            urls.to_vec(),
+        ]
+        .concat()
+        .join(" ")]
+    }
+
+    fn family(&self) -> BrowserFamily {
+        BrowserFamily::from_exe_path(&self.exe_path)
+    }
+
+    fn private_mode_flag(&self) -> Option<&'static str> {
+        private_mode_flag(&self.exe_path)
+    }
+
+    fn is_default(&self) -> bool {
+        self.is_default
+    }
+
+    fn is_referenced_by(&self, reference: &str) -> bool {
+        exe_path_matches(&self.exe_path, reference)
+    }
+
+    fn launch(&self, _url: &str, _extra_args: &[String]) -> BSResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,295 @@
+/*
+  Stand-ins for the platform specific modules on the platforms without a backend
+  yet. They let the program and the library build everywhere, ie. for running the
+  tests: no browser is detected, the custom ones cannot be launched and the
+  window cannot be shown. A new backend replaces these module by module.
+*/
+use crate::error::*;
+
+fn unsupported<T>() -> BSResult<T> {
+    Err(BSError::new(
+        "browser-selector currently only supports Windows.",
+    ))
+}
+
+pub mod sys_browsers {
+    use super::unsupported;
+    use crate::config::CustomBrowser;
+    use crate::error::*;
+    use crate::os::shared::{
+        exe_path_matches, id_from_path, private_mode_flag, BrowserEntry, BrowserFamily,
+        BrowserProvider,
+    };
+
+    /// A program added under `[[custom_browsers]]`, the only browsers known here
+    #[derive(Debug, Clone, Default)]
+    pub struct Browser {
+        pub exe_path: String,
+        pub arguments: Vec<String>,
+        pub name: String,
+        pub icon: String,
+    }
+
+    impl From<&CustomBrowser> for Browser {
+        fn from(custom: &CustomBrowser) -> Browser {
+            Browser {
+                exe_path: custom.exe_path.clone(),
+                arguments: custom.arguments.clone(),
+                name: custom.name.clone(),
+                icon: custom.icon_path.clone().unwrap_or_default(),
+            }
+        }
+    }
+
+    impl BrowserEntry for Browser {
+        fn id(&self) -> String {
+            match self.arguments.len() {
+                0 => id_from_path(&self.exe_path),
+                _ => id_from_path(&format!("{} {}", self.exe_path, self.arguments.join(" "))),
+            }
+        }
+
+        fn display_name(&self) -> String {
+            self.name.clone()
+        }
+
+        fn subtitle_parts(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn details(&self) -> Vec<(String, String)> {
+            Vec::new()
+        }
+
+        fn icon_source(&self) -> String {
+            match self.icon.is_empty() {
+                true => self.exe_path.clone(),
+                false => self.icon.clone(),
+            }
+        }
+
+        fn vendor(&self) -> String {
+            String::new()
+        }
+
+        fn exe_path(&self) -> String {
+            self.exe_path.clone()
+        }
+
+        fn command_line(&self) -> String {
+            std::iter::once(&self.exe_path)
+                .chain(self.arguments.iter())
+                .cloned()
+                .collect::<Vec<String>>()
+                .join(" ")
+        }
+
+        fn launch_command_lines(&self, urls: &[String], extra_args: &[String]) -> Vec<String> {
+            vec![[
+                vec![self.command_line()],
+                extra_args.to_vec(),
+                urls.to_vec(),
+            ]
+            .concat()
+            .join(" ")]
+        }
+
+        fn family(&self) -> BrowserFamily {
+            BrowserFamily::from_exe_path(&self.exe_path)
+        }
+
+        fn private_mode_flag(&self) -> Option<&'static str> {
+            private_mode_flag(&self.exe_path)
+        }
+
+        fn is_default(&self) -> bool {
+            false
+        }
+
+        fn is_referenced_by(&self, reference: &str) -> bool {
+            exe_path_matches(&self.exe_path, reference)
+        }
+
+        fn launch(&self, _url: &str, _extra_args: &[String]) -> BSResult<()> {
+            unsupported()
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub struct SystemBrowserProvider;
+
+    impl BrowserProvider for SystemBrowserProvider {
+        type Browser = Browser;
+
+        fn read_browsers(&self) -> BSResult<Vec<Browser>> {
+            Ok(Vec::new())
+        }
+    }
+
+    pub fn read_browsers_without_details() -> BSResult<Vec<Browser>> {
+        Ok(Vec::new())
+    }
+
+    pub fn read_scheme_handlers(_scheme: &str) -> BSResult<Vec<Browser>> {
+        Ok(Vec::new())
+    }
+
+    pub fn read_details(_browser: &mut Browser) {}
+
+    pub fn expand_profiles(browsers: Vec<Browser>) -> Vec<Browser> {
+        browsers
+    }
+
+    pub fn register_as_default() -> BSResult<()> {
+        unsupported()
+    }
+
+    pub fn unregister() -> BSResult<()> {
+        unsupported()
+    }
+
+    pub fn open_default_apps_settings() -> BSResult<()> {
+        unsupported()
+    }
+}
+
+pub mod util {
+    use super::unsupported;
+    use crate::error::*;
+
+    pub fn get_create_config_directory(_app_name: &str, _env_name: &str) -> BSResult<String> {
+        unsupported()
+    }
+
+    pub fn get_machine_config_directory() -> BSResult<String> {
+        unsupported()
+    }
+
+    pub fn get_local_data_directory() -> BSResult<String> {
+        unsupported()
+    }
+
+    /// The seconds since the Unix epoch with the milliseconds, there is no time zone at hand
+    pub fn get_local_time_text() -> String {
+        let since_epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+        format!(
+            "{}.{:03}",
+            since_epoch.as_secs(),
+            since_epoch.subsec_millis()
+        )
+    }
+
+    pub fn get_ui_language_name() -> Option<String> {
+        None
+    }
+
+    pub fn get_foreground_process_exe_path() -> BSResult<String> {
+        unsupported()
+    }
+
+    pub fn get_cursor_position() -> Option<winit::dpi::PhysicalPosition<i32>> {
+        None
+    }
+
+    pub fn is_foreground_window(_window: &winit::window::Window) -> bool {
+        true
+    }
+
+    pub fn bring_to_foreground(_window: &winit::window::Window) {}
+
+    pub fn is_shift_key_down() -> bool {
+        false
+    }
+
+    pub fn is_alt_key_down() -> bool {
+        false
+    }
+
+    pub fn is_ctrl_key_down() -> bool {
+        false
+    }
+
+    pub fn copy_to_clipboard(_text: &str) -> BSResult<()> {
+        unsupported()
+    }
+
+    pub fn read_clipboard_text() -> BSResult<String> {
+        unsupported()
+    }
+
+    pub fn open_with_default_program(_path: &str) -> BSResult<()> {
+        unsupported()
+    }
+
+    pub fn follow_redirects(_url: &str, _timeout: std::time::Duration) -> BSResult<String> {
+        unsupported()
+    }
+
+    pub fn output_error_text(text: String) {
+        eprintln!("{}", text);
+    }
+
+    pub fn output_panic_text(text: String) {
+        eprintln!("{}", text);
+    }
+}
+
+pub mod single_instance {
+    use super::unsupported;
+    use crate::error::*;
+
+    pub fn send_to_running_instance(_url: &str) -> bool {
+        false
+    }
+
+    pub fn listen(_on_url: impl Fn(String) + Send + 'static) -> BSResult<()> {
+        unsupported()
+    }
+}
+
+pub mod drop_target {
+    use super::unsupported;
+    use crate::error::*;
+
+    pub fn register_url_drop(
+        _window: &winit::window::Window,
+        _on_url: impl Fn(String) + 'static,
+    ) -> BSResult<()> {
+        unsupported()
+    }
+}
+
+pub mod hotkey {
+    use super::unsupported;
+    use crate::error::*;
+
+    pub const HOTKEY_NAME: &str = "Ctrl+Alt+B";
+
+    pub fn listen(_on_pressed: impl Fn() + Send + 'static) -> BSResult<()> {
+        unsupported()
+    }
+}
+
+pub mod tray {
+    use super::unsupported;
+    use crate::error::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum TrayCommand {
+        ShowWindow,
+        OpenClipboardUrl,
+        OpenSettings,
+        Quit,
+    }
+
+    pub struct TrayIcon;
+
+    pub fn show(
+        _tooltip: &str,
+        _on_command: impl Fn(TrayCommand) + Send + 'static,
+    ) -> BSResult<TrayIcon> {
+        unsupported()
+    }
+}
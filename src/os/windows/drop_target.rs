@@ -0,0 +1,247 @@
+/*
+  Links dragged from a browser or another program can be dropped on the window
+  to choose a browser for them instead. The drag and drop of winit only accepts
+  files thus it is turned off and the window gets this OLE drop target which
+  also accepts the links and the text, besides the `.url` shortcut files.
+*/
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::error::*;
+use crate::os::windows::util::{get_hwnd, str_to_wide, wide_to_str};
+use crate::url_utils;
+
+mod winapi {
+    pub use winapi::ctypes::c_void;
+    pub use winapi::shared::guiddef::{IsEqualGUID, REFIID};
+    pub use winapi::shared::minwindef::{DWORD, HGLOBAL, ULONG};
+    pub use winapi::shared::ntdef::HRESULT;
+    pub use winapi::shared::windef::POINTL;
+    pub use winapi::shared::winerror::{E_NOINTERFACE, SUCCEEDED, S_OK};
+    pub use winapi::shared::wtypes::DVASPECT_CONTENT;
+    pub use winapi::um::objidl::{IDataObject, FORMATETC, STGMEDIUM, TYMED_HGLOBAL};
+    pub use winapi::um::ole2::{OleInitialize, RegisterDragDrop, ReleaseStgMedium};
+    pub use winapi::um::oleidl::{IDropTarget, IDropTargetVtbl, DROPEFFECT_COPY, DROPEFFECT_NONE};
+    pub use winapi::um::shellapi::{DragQueryFileW, HDROP};
+    pub use winapi::um::unknwnbase::{IUnknown, IUnknownVtbl};
+    pub use winapi::um::winbase::{GlobalLock, GlobalSize, GlobalUnlock};
+    pub use winapi::um::winuser::{RegisterClipboardFormatW, CF_HDROP, CF_UNICODETEXT};
+    pub use winapi::Interface;
+}
+
+use self::winapi::*;
+
+// the format browsers use for the links, before the plain text of the link
+const URL_CLIPBOARD_FORMAT_NAME: &str = "UniformResourceLocatorW";
+
+// the line of the `.url` shortcut files holding the link
+const URL_SHORTCUT_PREFIX: &str = "URL=";
+
+/// The COM object given to OLE, the interface has to be the first field
+/// so that a pointer to the object is a pointer to the interface.
+#[repr(C)]
+struct DropTarget {
+    interface: IDropTarget,
+    ref_count: AtomicU32,
+
+    // whether the data being dragged over the window holds something to open
+    accepts_drag: Cell<bool>,
+    on_url: Box<dyn Fn(String)>,
+}
+
+static DROP_TARGET_VTBL: IDropTargetVtbl = IDropTargetVtbl {
+    parent: IUnknownVtbl {
+        QueryInterface: query_interface,
+        AddRef: add_ref,
+        Release: release,
+    },
+    DragEnter: drag_enter,
+    DragOver: drag_over,
+    DragLeave: drag_leave,
+    Drop: drop_data,
+};
+
+/// Lets the links and the `.url` files be dropped on the `window`, `on_url` is called
+/// with each dropped URL. The window has to be built with the drag and drop of winit off.
+pub fn register_url_drop(
+    window: &winit::window::Window,
+    on_url: impl Fn(String) + 'static,
+) -> BSResult<()> {
+    // the drop target lives as long as the window, thus for the rest of the program
+    let drop_target = Box::into_raw(Box::new(DropTarget {
+        interface: IDropTarget {
+            lpVtbl: &DROP_TARGET_VTBL,
+        },
+        ref_count: AtomicU32::new(1),
+        accepts_drag: Cell::new(false),
+        on_url: Box::new(on_url),
+    }));
+
+    let result = unsafe {
+        OleInitialize(std::ptr::null_mut());
+        RegisterDragDrop(get_hwnd(window), drop_target as *mut IDropTarget)
+    };
+    if !SUCCEEDED(result) {
+        bail!(
+            "Cannot register the window for dropping links. Error code: {:#x}",
+            result
+        );
+    }
+
+    Ok(())
+}
+
+unsafe extern "system" fn query_interface(
+    this: *mut IUnknown,
+    riid: REFIID,
+    object: *mut *mut c_void,
+) -> HRESULT {
+    if IsEqualGUID(&*riid, &IUnknown::uuidof()) || IsEqualGUID(&*riid, &IDropTarget::uuidof()) {
+        add_ref(this);
+        *object = this as *mut c_void;
+        return S_OK;
+    }
+
+    *object = std::ptr::null_mut();
+    E_NOINTERFACE
+}
+
+unsafe extern "system" fn add_ref(this: *mut IUnknown) -> ULONG {
+    let drop_target = &*(this as *const DropTarget);
+    drop_target.ref_count.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+unsafe extern "system" fn release(this: *mut IUnknown) -> ULONG {
+    // never freed, see `register_url_drop`
+    let drop_target = &*(this as *const DropTarget);
+    drop_target.ref_count.fetch_sub(1, Ordering::SeqCst) - 1
+}
+
+unsafe extern "system" fn drag_enter(
+    this: *mut IDropTarget,
+    data_object: *const IDataObject,
+    _key_state: DWORD,
+    _point: *const POINTL,
+    effect: *mut DWORD,
+) -> HRESULT {
+    let drop_target = &*(this as *const DropTarget);
+    drop_target
+        .accepts_drag
+        .set(read_dropped_url(data_object).is_some());
+    *effect = drop_effect(drop_target);
+    S_OK
+}
+
+unsafe extern "system" fn drag_over(
+    this: *mut IDropTarget,
+    _key_state: DWORD,
+    _point: *const POINTL,
+    effect: *mut DWORD,
+) -> HRESULT {
+    *effect = drop_effect(&*(this as *const DropTarget));
+    S_OK
+}
+
+unsafe extern "system" fn drag_leave(this: *mut IDropTarget) -> HRESULT {
+    (*(this as *const DropTarget)).accepts_drag.set(false);
+    S_OK
+}
+
+unsafe extern "system" fn drop_data(
+    this: *mut IDropTarget,
+    data_object: *const IDataObject,
+    _key_state: DWORD,
+    _point: *const POINTL,
+    effect: *mut DWORD,
+) -> HRESULT {
+    let drop_target = &*(this as *const DropTarget);
+    drop_target.accepts_drag.set(false);
+    *effect = match read_dropped_url(data_object) {
+        Some(url) => {
+            (drop_target.on_url)(url);
+            DROPEFFECT_COPY
+        }
+        None => DROPEFFECT_NONE,
+    };
+    S_OK
+}
+
+fn drop_effect(drop_target: &DropTarget) -> DWORD {
+    match drop_target.accepts_drag.get() {
+        true => DROPEFFECT_COPY,
+        false => DROPEFFECT_NONE,
+    }
+}
+
+/// The URL held by the dragged data, a link, a text which is a URL or a `.url` file
+unsafe fn read_dropped_url(data_object: *const IDataObject) -> Option<String> {
+    let url_format = RegisterClipboardFormatW(str_to_wide(URL_CLIPBOARD_FORMAT_NAME).as_ptr());
+    let url = read_text(data_object, url_format)
+        .or_else(|| read_text(data_object, CF_UNICODETEXT))
+        .or_else(|| read_shortcut_file(data_object))?;
+
+    let url = url.trim().to_owned();
+    match url_utils::is_plausible_url(&url) {
+        true => Some(url),
+        false => None,
+    }
+}
+
+unsafe fn read_text(data_object: *const IDataObject, format: u32) -> Option<String> {
+    read_global_memory(data_object, format, |_, memory, size| {
+        let text = std::slice::from_raw_parts(memory as *const u16, size / 2);
+        Some(wide_to_str(text))
+    })
+}
+
+unsafe fn read_shortcut_file(data_object: *const IDataObject) -> Option<String> {
+    let path = read_global_memory(data_object, CF_HDROP, |global, _, _| {
+        let drop = global as HDROP;
+        let len = DragQueryFileW(drop, 0, std::ptr::null_mut(), 0);
+        let mut path: Vec<u16> = vec![0; len as usize + 1];
+        match DragQueryFileW(drop, 0, path.as_mut_ptr(), len + 1) {
+            0 => None,
+            _ => Some(wide_to_str(&path)),
+        }
+    })?;
+    if !path.to_lowercase().ends_with(".url") {
+        return None;
+    }
+
+    std::fs::read_to_string(&path)
+        .ok()?
+        .lines()
+        .find(|line| line.starts_with(URL_SHORTCUT_PREFIX))
+        .map(|line| line[URL_SHORTCUT_PREFIX.len()..].to_owned())
+}
+
+/// Calls `read` with the global memory handle holding the dragged data in the `format`,
+/// the memory locked for reading and its size in bytes
+unsafe fn read_global_memory<T>(
+    data_object: *const IDataObject,
+    format: u32,
+    read: impl FnOnce(HGLOBAL, *mut c_void, usize) -> Option<T>,
+) -> Option<T> {
+    let format = FORMATETC {
+        cfFormat: format as u16,
+        ptd: std::ptr::null_mut(),
+        dwAspect: DVASPECT_CONTENT,
+        lindex: -1,
+        tymed: TYMED_HGLOBAL,
+    };
+    let mut medium: STGMEDIUM = std::mem::zeroed();
+    if !SUCCEEDED((*data_object).GetData(&format, &mut medium)) {
+        return None;
+    }
+
+    let global = *(*medium.u).hGlobal();
+    let memory = GlobalLock(global);
+    let result = match memory.is_null() {
+        true => None,
+        false => read(global, memory, GlobalSize(global)),
+    };
+    GlobalUnlock(global);
+    ReleaseStgMedium(&mut medium);
+
+    result
+}
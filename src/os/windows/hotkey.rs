@@ -0,0 +1,54 @@
+/*
+  The `--daemon` mode shows the window when a key combination is pressed
+  anywhere in Windows. The hot key is registered by a thread of its own which
+  waits for its messages, the event loop of winit does not hand them over.
+*/
+use std::sync::mpsc;
+
+use crate::error::*;
+
+mod winapi {
+    pub use winapi::um::winuser::{
+        GetMessageW, RegisterHotKey, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MSG, WM_HOTKEY,
+    };
+}
+
+// Ctrl+Alt+B, for browser
+const HOTKEY_MODIFIERS: u32 = (winapi::MOD_CONTROL | winapi::MOD_ALT | winapi::MOD_NOREPEAT) as u32;
+const HOTKEY_KEY: u32 = b'B' as u32;
+const HOTKEY_ID: i32 = 1;
+
+/// The key combination registered by `listen`, as shown to the user
+pub const HOTKEY_NAME: &str = "Ctrl+Alt+B";
+
+/// Calls `on_pressed` from another thread each time the hot key is pressed.
+/// Fails when another program already registered the same key combination.
+pub fn listen(on_pressed: impl Fn() + Send + 'static) -> BSResult<()> {
+    let (registered_sender, registered) = mpsc::channel::<bool>();
+
+    // the messages of the hot key go to the thread that registered it
+    std::thread::spawn(move || unsafe {
+        let is_registered = winapi::RegisterHotKey(
+            std::ptr::null_mut(),
+            HOTKEY_ID,
+            HOTKEY_MODIFIERS,
+            HOTKEY_KEY,
+        ) != 0;
+        let _ = registered_sender.send(is_registered);
+        if !is_registered {
+            return;
+        }
+
+        let mut message: winapi::MSG = std::mem::zeroed();
+        while winapi::GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) > 0 {
+            if message.message == winapi::WM_HOTKEY {
+                on_pressed();
+            }
+        }
+    });
+
+    match registered.recv() {
+        Ok(true) => Ok(()),
+        _ => bail!("Cannot register {}, another program uses it.", HOTKEY_NAME),
+    }
+}
@@ -0,0 +1,586 @@
+/*
+  Turns the icon of a program into an Image control WinUI can show: the icon
+  associated with the executable is read as a HICON, its pixels are converted
+  to BGRA, kept in the icon cache, and put in a SoftwareBitmap which becomes the
+  source of the Image. Each stage is a function of its own.
+
+  The icon is read from the resources of the executable at the size of the
+  physical pixels it is shown at, the icon chosen by the shell is only a fallback
+  as its size follows the DPI of the primary monitor.
+*/
+use std::convert::TryInto;
+use std::mem::MaybeUninit;
+
+mod wrt {
+    pub use bindings::windows::graphics::imaging::{
+        BitmapAlphaMode, BitmapPixelFormat, ISoftwareBitmapFactory, SoftwareBitmap,
+    };
+    pub use bindings::windows::storage::streams::{DataWriter, IBuffer, IDataWriterFactory};
+    pub use bindings::windows::ui::xaml::controls::Image;
+    pub use bindings::windows::ui::xaml::media::imaging::SoftwareBitmapSource;
+    pub use bindings::windows::ui::xaml::media::ImageSource;
+}
+
+mod winapi {
+    pub use winapi::shared::basetsd::LONG_PTR;
+    pub use winapi::shared::minwindef::{BOOL, HMODULE, TRUE};
+    pub use winapi::shared::windef::{HBITMAP, HGDIOBJ, HICON};
+    pub use winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+    pub use winapi::um::combaseapi::CoCreateInstance;
+    pub use winapi::um::libloaderapi::{
+        EnumResourceNamesW, FreeLibrary, LoadLibraryExW, LOAD_LIBRARY_AS_DATAFILE,
+        LOAD_LIBRARY_AS_IMAGE_RESOURCE,
+    };
+    pub use winapi::um::unknwnbase::IUnknown;
+    pub use winapi::um::wincodec::{
+        CLSID_WICImagingFactory, GUID_WICPixelFormat32bppPBGRA, IWICBitmap, IWICBitmapSource,
+        IWICFormatConverter, IWICImagingFactory, WICBitmapDitherTypeNone,
+        WICBitmapPaletteTypeCustom,
+    };
+    pub use winapi::um::wingdi::{
+        CreateCompatibleDC, DeleteDC, DeleteObject, GetDIBits, GetObjectW, BITMAP, BITMAPINFO,
+        BITMAPINFOHEADER, BI_RGB, DIBSECTION, DIB_RGB_COLORS,
+    };
+    pub use winapi::um::winnt::{LPCWSTR, LPWSTR};
+    pub use winapi::um::winuser::{
+        DestroyIcon, GetIconInfo, LoadImageW, ICONINFO, IMAGE_ICON, LR_DEFAULTCOLOR,
+        LR_LOADFROMFILE, RT_GROUP_ICON,
+    };
+    pub use winapi::Interface;
+}
+
+use crate::error::*;
+use crate::icon_cache::{self, split_icon_index, IconPixels};
+use crate::os::windows::util::{get_exe_file_icon, str_to_wide};
+
+/// The icon of the program at `exe_path` as an Image control of `size` by `size`
+/// physical pixels, read from the icon cache when it has it and added to it otherwise
+pub fn load_image_for_exe(exe_path: &str, size: u32) -> BSResult<wrt::Image> {
+    let icon = match icon_cache::load(exe_path, size) {
+        Some(icon) => icon,
+        None => {
+            let icon = read_icon_pixels(exe_path, size)?;
+            if let Err(e) = icon_cache::store(exe_path, size, &icon) {
+                log::error!("Could not cache the icon of {}. Reason: {}", exe_path, e);
+            }
+            icon
+        }
+    };
+    let bmp = icon_pixels_to_software_bitmap(&icon)?;
+
+    match software_bitmap_to_xaml_image(bmp) {
+        Ok(image) => Ok(image),
+        Err(winrt_error) => Err(BSError::from(winrt_error)),
+    }
+}
+
+/// The pixels of the icon at `icon_path` of `size` by `size` pixels, the one of the
+/// shell at its own size when the file has no icon resources, ie. a shortcut
+fn read_icon_pixels(icon_path: &str, size: u32) -> BSResult<IconPixels> {
+    match load_sized_icon(icon_path, size) {
+        Ok(hicon) => {
+            let icon = hicon_to_icon_pixels(hicon);
+            unsafe { winapi::DestroyIcon(hicon) };
+            icon
+        }
+        Err(e) => {
+            log::debug!(
+                "Reading the icon of {} at its default size. Reason: {}",
+                icon_path,
+                e
+            );
+            hicon_to_icon_pixels(get_exe_file_icon(icon_path)?)
+        }
+    }
+}
+
+/// Loads the icon at `icon_path` scaled to `size` by `size` pixels from the best fitting
+/// image of the icon, either an `.ico` file or a resource of an executable. The registry
+/// icon paths pick the icon by its position, ie. `app.exe,1`, or by its id, ie. `app.exe,-101`.
+/// The icon has to be destroyed with `DestroyIcon`.
+pub fn load_sized_icon(icon_path: &str, size: u32) -> BSResult<winapi::HICON> {
+    let (file_path, icon_index) = split_icon_index(icon_path);
+    let wide_path = str_to_wide(file_path);
+    if file_path.to_lowercase().ends_with(".ico") {
+        let hicon = unsafe {
+            winapi::LoadImageW(
+                std::ptr::null_mut(),
+                wide_path.as_ptr(),
+                winapi::IMAGE_ICON,
+                size as i32,
+                size as i32,
+                winapi::LR_LOADFROMFILE,
+            )
+        };
+        if hicon.is_null() {
+            bail!("Cannot load the icon file {}", file_path);
+        }
+        return Ok(hicon as winapi::HICON);
+    }
+
+    let module = unsafe {
+        winapi::LoadLibraryExW(
+            wide_path.as_ptr(),
+            std::ptr::null_mut(),
+            winapi::LOAD_LIBRARY_AS_DATAFILE | winapi::LOAD_LIBRARY_AS_IMAGE_RESOURCE,
+        )
+    };
+    if module.is_null() {
+        bail!("Cannot read the resources of {}", file_path);
+    }
+
+    // the icon is a copy which stays valid once the module is freed
+    let hicon = icon_group_name(module, icon_index.unwrap_or(0)).map(|name| unsafe {
+        winapi::LoadImageW(
+            module,
+            name.as_ptr(),
+            winapi::IMAGE_ICON,
+            size as i32,
+            size as i32,
+            winapi::LR_DEFAULTCOLOR,
+        ) as winapi::HICON
+    });
+    unsafe { winapi::FreeLibrary(module) };
+
+    match hicon {
+        Some(hicon) if !hicon.is_null() => Ok(hicon),
+        _ => bail!("{} has no icon {}", file_path, icon_index.unwrap_or(0)),
+    }
+}
+
+/// The resource name of the icon of the `module` at `icon_index`, a negative
+/// index being the id of the icon, written `#id` as the integer names are
+fn icon_group_name(module: winapi::HMODULE, icon_index: i32) -> Option<Vec<u16>> {
+    if icon_index < 0 {
+        return Some(str_to_wide(&format!("#{}", -icon_index)));
+    }
+
+    let mut names: Vec<Vec<u16>> = Vec::new();
+    unsafe {
+        winapi::EnumResourceNamesW(
+            module,
+            winapi::RT_GROUP_ICON,
+            Some(collect_resource_name),
+            &mut names as *mut _ as winapi::LONG_PTR,
+        );
+    }
+
+    names.into_iter().nth(icon_index as usize)
+}
+
+/// Adds the resource `name` to the `Vec<Vec<u16>>` given as `names`, copied
+/// as the string names only live during the enumeration
+unsafe extern "system" fn collect_resource_name(
+    _: winapi::HMODULE,
+    _: winapi::LPCWSTR,
+    name: winapi::LPWSTR,
+    names: winapi::LONG_PTR,
+) -> winapi::BOOL {
+    let names = &mut *(names as *mut Vec<Vec<u16>>);
+    names.push(match name as usize >> 16 {
+        0 => str_to_wide(&format!("#{}", name as usize)),
+        _ => {
+            let length = (0..).take_while(|&i| *name.offset(i) != 0).count();
+            let mut wide_name = std::slice::from_raw_parts(name, length).to_vec();
+            wide_name.push(0);
+            wide_name
+        }
+    });
+
+    winapi::TRUE
+}
+
+/// From the given WinRT SoftwareBitmap it returns
+/// the corresponding WinUI Image XAML control that can be inserted
+/// as a node in any UIElement derived object
+pub fn software_bitmap_to_xaml_image(bmp: wrt::SoftwareBitmap) -> winrt::Result<wrt::Image> {
+    // ImageSource.SetBitmapAsync throws an exception unless the bitmap
+    // is Pixel Format: BGRA8, BitmapAlphaMode: Premultiplied
+    // thus a conversion (and a copy of the pixels) is only made when needed
+    let is_displayable = bmp.bitmap_pixel_format()? == wrt::BitmapPixelFormat::Bgra8
+        && bmp.bitmap_alpha_mode()? == wrt::BitmapAlphaMode::Premultiplied;
+    let bgra8_bmp = match is_displayable {
+        true => bmp,
+        false => wrt::SoftwareBitmap::convert_with_alpha(
+            bmp,
+            wrt::BitmapPixelFormat::Bgra8,
+            wrt::BitmapAlphaMode::Premultiplied,
+        )?,
+    };
+
+    let image_control = wrt::Image::new()?;
+    let img_src: wrt::SoftwareBitmapSource = wrt::SoftwareBitmapSource::new()?;
+    img_src.set_bitmap_async(bgra8_bmp)?;
+    image_control.set_source(wrt::ImageSource::from(img_src))?;
+
+    return Ok(image_control);
+}
+
+/// Converts a HICON to a SoftwareBitmap that can be used with WinUI controls
+pub fn hicon_to_software_bitmap(hicon: winapi::HICON) -> BSResult<wrt::SoftwareBitmap> {
+    icon_pixels_to_software_bitmap(&hicon_to_icon_pixels(hicon)?)
+}
+
+/// Reads the pixels of a HICON in BGRA8.
+///
+/// The GDI conversion is the fast path, WIC is used for the icons it cannot handle
+/// such as the ones without 32 bits color bitmaps.
+pub fn hicon_to_icon_pixels(hicon: winapi::HICON) -> BSResult<IconPixels> {
+    hicon_to_icon_pixels_gdi(hicon).or_else(|e| {
+        log::debug!("Converting the icon with WIC instead of GDI. Reason: {}", e);
+        hicon_to_icon_pixels_wic(hicon)
+    })
+}
+
+pub fn icon_pixels_to_software_bitmap(icon: &IconPixels) -> BSResult<wrt::SoftwareBitmap> {
+    let data_writer = wrt::DataWriter::new()?;
+    data_writer.write_bytes(&icon.pixels)?;
+
+    let alpha_mode = match icon.premultiplied {
+        true => wrt::BitmapAlphaMode::Premultiplied,
+        false => wrt::BitmapAlphaMode::Straight,
+    };
+    Ok(wrt::SoftwareBitmap::create_copy_with_alpha_from_buffer(
+        data_writer.detach_buffer()?,
+        wrt::BitmapPixelFormat::Bgra8,
+        icon.width as i32,
+        icon.height as i32,
+        alpha_mode,
+    )?)
+}
+
+/// Converts a HICON with the Windows Imaging Component which knows about every icon format
+fn hicon_to_icon_pixels_wic(hicon: winapi::HICON) -> BSResult<IconPixels> {
+    use self::winapi::Interface;
+
+    let mut factory: *mut winapi::IWICImagingFactory = std::ptr::null_mut();
+    let mut bitmap: *mut winapi::IWICBitmap = std::ptr::null_mut();
+    let mut converter: *mut winapi::IWICFormatConverter = std::ptr::null_mut();
+
+    let pixels_result: BSResult<(Vec<u8>, u32, u32)> = unsafe {
+        (|| {
+            if winapi::CoCreateInstance(
+                &winapi::CLSID_WICImagingFactory,
+                std::ptr::null_mut(),
+                winapi::CLSCTX_INPROC_SERVER,
+                &winapi::IWICImagingFactory::uuidof(),
+                &mut factory as *mut _ as *mut *mut std::ffi::c_void,
+            ) < 0
+            {
+                bail!("Could not create the WIC imaging factory.");
+            }
+            if (*factory).CreateBitmapFromHICON(hicon, &mut bitmap) < 0 {
+                bail!("WIC could not read the icon {:?}", hicon);
+            }
+            if (*factory).CreateFormatConverter(&mut converter) < 0
+                || (*converter).Initialize(
+                    bitmap as *mut winapi::IWICBitmapSource,
+                    &winapi::GUID_WICPixelFormat32bppPBGRA,
+                    winapi::WICBitmapDitherTypeNone,
+                    std::ptr::null_mut(),
+                    0.,
+                    winapi::WICBitmapPaletteTypeCustom,
+                ) < 0
+            {
+                bail!("WIC could not convert the icon {:?} to BGRA", hicon);
+            }
+
+            let (mut width, mut height) = (0u32, 0u32);
+            (*converter).GetSize(&mut width, &mut height);
+            let stride = width * 4;
+            let mut pixels = vec![0u8; (stride * height) as usize];
+            if (*converter).CopyPixels(
+                std::ptr::null(),
+                stride,
+                pixels.len() as u32,
+                pixels.as_mut_ptr(),
+            ) < 0
+            {
+                bail!("WIC could not copy the pixels of the icon {:?}", hicon);
+            }
+
+            Ok((pixels, width, height))
+        })()
+    };
+
+    unsafe {
+        for com_object in [
+            converter as *mut winapi::IUnknown,
+            bitmap as *mut winapi::IUnknown,
+            factory as *mut winapi::IUnknown,
+        ]
+        .iter()
+        {
+            if !com_object.is_null() {
+                (**com_object).Release();
+            }
+        }
+    }
+
+    let (pixels, width, height) = pixels_result?;
+
+    // already premultiplied thus software_bitmap_to_xaml_image won't convert it again
+    Ok(IconPixels {
+        width,
+        height,
+        premultiplied: true,
+        pixels,
+    })
+}
+
+/// Converts a HICON through its GDI color bitmap
+///
+/// Notes:
+/// - There probably is a simpler way to achieve this
+/// - The function does not implement all possiblities described in the Windows API doc
+/// thus it is possible that it might not work for certain icon formats
+fn hicon_to_icon_pixels_gdi(hicon: winapi::HICON) -> BSResult<IconPixels> {
+    let mut icon_info: winapi::ICONINFO = unsafe { MaybeUninit::uninit().assume_init() };
+    let icon_result = unsafe { winapi::GetIconInfo(hicon, &mut icon_info) };
+    if icon_result == 0 {
+        bail!("Couldn't get icon info for HICON {:?}", hicon);
+    }
+    if icon_info.hbmColor.is_null() {
+        // monochrome icons keep both of their masks in hbmMask
+        unsafe { winapi::DeleteObject(icon_info.hbmMask as winapi::HGDIOBJ) };
+        bail!(
+            "The HICON {:?} is monochrome, it has no ICONINFO.hbmColor bitmap.",
+            hicon
+        );
+    }
+
+    let dib_struct_size = std::mem::size_of::<winapi::DIBSECTION>()
+        .try_into()
+        .unwrap_or(0);
+    let bitmap_struct_size = std::mem::size_of::<winapi::BITMAP>()
+        .try_into()
+        .unwrap_or(0);
+
+    let mut dib: winapi::DIBSECTION = unsafe { MaybeUninit::uninit().assume_init() };
+    let bytes_read = unsafe {
+        winapi::GetObjectW(
+            icon_info.hbmColor as *mut _ as *mut std::ffi::c_void,
+            dib_struct_size,
+            &mut dib as *mut _ as *mut std::ffi::c_void,
+        )
+    };
+
+    if bytes_read == 0 {
+        unsafe {
+            winapi::DeleteObject(icon_info.hbmColor as winapi::HGDIOBJ);
+            winapi::DeleteObject(icon_info.hbmMask as winapi::HGDIOBJ);
+        }
+
+        bail!("Error: winapi::GetObject returned 0 on ICONINFO.hbmColor bitmap.");
+    }
+
+    // the pixels of every depth GetDIBits knows about are read as BGRA below
+    let bits_per_pixel = dib.dsBm.bmBitsPixel;
+    if ![1, 4, 8, 16, 24, 32].contains(&bits_per_pixel) {
+        unsafe {
+            winapi::DeleteObject(icon_info.hbmColor as winapi::HGDIOBJ);
+            winapi::DeleteObject(icon_info.hbmMask as winapi::HGDIOBJ);
+        }
+
+        bail!(
+            "Unsupported {} bits per pixel in the ICONINFO.hbmColor bitmap.",
+            bits_per_pixel
+        );
+    }
+
+    // BITMAP size is 32 bytes
+    // DIBSECTION is 104 bytes
+    let bmp_size_in_bytes =
+        (dib.dsBm.bmHeight * dib.dsBm.bmWidth) * (dib.dsBm.bmBitsPixel as i32 / 8);
+
+    let pixel_bytes_result = match bytes_read {
+        bytes_read if bytes_read == bitmap_struct_size || bits_per_pixel != 32 => {
+            // when GetObject returns the size of the BITMAP structure then dib.dsBm
+            // is a device dependent bitmap, which has to be converted the same as the
+            // DIBs with less than 32 bits per pixel, ie. 24 bits or palettized ones
+            read_bitmap_as_bgra(icon_info.hbmColor, dib.dsBm.bmWidth, dib.dsBm.bmHeight)
+        }
+        bytes_read if bytes_read == dib_struct_size => {
+            if dib.dsBm.bmBits as usize != 0 {
+                let mut img_bytes = unsafe {
+                    std::slice::from_raw_parts::<u8>(
+                        dib.dsBm.bmBits as *const u8,
+                        bmp_size_in_bytes as usize,
+                    )
+                    .to_vec()
+                };
+
+                // a positive biHeight means the DIB rows are stored bottom-up,
+                // unlike the GetDIBits output above and the SoftwareBitmap
+                if dib.dsBmih.biHeight > 0 {
+                    flip_rows(&mut img_bytes, dib.dsBm.bmHeight as usize);
+                }
+
+                Ok(img_bytes)
+            } else {
+                Err("Unexpected NULL pointer for image bits from DIBSECTION.dsBm.bmBits")
+            }
+        }
+        0 => Err("winapi::GetObject returned 0 on ICONINFO.hbmColor bitmap."),
+        _ => Err(
+            "Unexpected response from winapi::GetObject, was expecting read bytes \
+            to match either the BITMAP struct size or the DIBSECTION struct size.",
+        ),
+    };
+
+    // the formats below 32 bits have no alpha channel, neither have the 32 bits
+    // icons made before Windows XP whose alpha is left at 0, their transparency
+    // is given by the AND mask where the set bits are the transparent pixels
+    let pixel_bytes = pixel_bytes_result.and_then(|mut bytes| {
+        if bits_per_pixel < 32 || bytes.chunks_exact(4).all(|pixel| pixel[3] == 0) {
+            let mask = read_bitmap_as_bgra(icon_info.hbmMask, dib.dsBm.bmWidth, dib.dsBm.bmHeight)?;
+            alpha_from_and_mask(&mut bytes, &mask);
+        }
+
+        Ok(bytes)
+    });
+    let pixel_bytes = match pixel_bytes {
+        Ok(bytes) => bytes,
+        Err(error) => unsafe {
+            winapi::DeleteObject(icon_info.hbmColor as winapi::HGDIOBJ);
+            winapi::DeleteObject(icon_info.hbmMask as winapi::HGDIOBJ);
+            bail!(error);
+        },
+    };
+
+    unsafe {
+        winapi::DeleteObject(icon_info.hbmColor as winapi::HGDIOBJ);
+        winapi::DeleteObject(icon_info.hbmMask as winapi::HGDIOBJ);
+    }
+
+    Ok(IconPixels {
+        width: dib.dsBm.bmWidth as u32,
+        height: dib.dsBm.bmHeight as u32,
+        premultiplied: false,
+        pixels: pixel_bytes,
+    })
+}
+
+/// Reads the pixels of a `bitmap` of any depth as a top-down 32 bits BGRA DIB,
+/// the alpha channel is left at 0 for the bitmaps without one
+fn read_bitmap_as_bgra(
+    bitmap: winapi::HBITMAP,
+    width: i32,
+    height: i32,
+) -> std::result::Result<Vec<u8>, &'static str> {
+    let mut bitmap_info = top_down_bgra_bitmap_info(width, height);
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+    let device_context = unsafe { winapi::CreateCompatibleDC(std::ptr::null_mut()) };
+    if device_context.is_null() {
+        return Err("winapi::CreateCompatibleDC failed to create a memory device context");
+    }
+
+    let lines_read = unsafe {
+        winapi::GetDIBits(
+            device_context,
+            bitmap,
+            0,
+            height as u32,
+            pixels.as_mut_ptr() as *mut std::ffi::c_void,
+            &mut bitmap_info,
+            winapi::DIB_RGB_COLORS,
+        )
+    };
+    unsafe { winapi::DeleteDC(device_context) };
+
+    match lines_read {
+        0 => Err("winapi::GetDIBits read 0 lines from the ICONINFO.hbmColor"),
+        _ => Ok(pixels),
+    }
+}
+
+/// The format of the pixels `read_bitmap_as_bgra` asks `GetDIBits` for
+fn top_down_bgra_bitmap_info(width: i32, height: i32) -> winapi::BITMAPINFO {
+    let mut bitmap_info: winapi::BITMAPINFO = unsafe { std::mem::zeroed() };
+    bitmap_info.bmiHeader.biSize = std::mem::size_of::<winapi::BITMAPINFOHEADER>() as u32;
+    bitmap_info.bmiHeader.biWidth = width;
+    bitmap_info.bmiHeader.biHeight = -height; // negative for the rows to be top-down
+    bitmap_info.bmiHeader.biPlanes = 1;
+    bitmap_info.bmiHeader.biBitCount = 32;
+    bitmap_info.bmiHeader.biCompression = winapi::BI_RGB;
+
+    bitmap_info
+}
+
+/// Sets the alpha of the BGRA `pixels` from the AND `mask` read as BGRA as well,
+/// the pixels are opaque where the mask is black and transparent where it is white
+fn alpha_from_and_mask(pixels: &mut [u8], mask: &[u8]) {
+    for (pixel, mask_pixel) in pixels.chunks_exact_mut(4).zip(mask.chunks_exact(4)) {
+        pixel[3] = match mask_pixel[0] {
+            0 => 255,
+            _ => 0,
+        };
+    }
+}
+
+/// Reverses the order of the `row_count` rows of equal size in the `pixels` buffer
+fn flip_rows(pixels: &mut [u8], row_count: usize) {
+    if row_count == 0 {
+        return;
+    }
+
+    let row_size = pixels.len() / row_count;
+    for top_row in 0..row_count / 2 {
+        let bottom_row = row_count - 1 - top_row;
+        let (top, bottom) = pixels.split_at_mut(bottom_row * row_size);
+        top[top_row * row_size..(top_row + 1) * row_size].swap_with_slice(&mut bottom[..row_size]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flip_rows_reverses_the_rows_and_keeps_the_pixels_of_each_row() {
+        // 2 by 3 pixels of 4 bytes, each byte is its row number
+        let mut pixels = [vec![0u8; 8], vec![1u8; 8], vec![2u8; 8]].concat();
+        flip_rows(&mut pixels, 3);
+        assert_eq!(pixels, [vec![2u8; 8], vec![1u8; 8], vec![0u8; 8]].concat());
+
+        let mut pixels = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        flip_rows(&mut pixels, 2);
+        assert_eq!(pixels, vec![5, 6, 7, 8, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn flip_rows_leaves_a_single_row_or_no_rows() {
+        let mut pixels = vec![1, 2, 3, 4];
+        flip_rows(&mut pixels, 1);
+        assert_eq!(pixels, vec![1, 2, 3, 4]);
+
+        let mut pixels: Vec<u8> = Vec::new();
+        flip_rows(&mut pixels, 0);
+        assert!(pixels.is_empty());
+    }
+
+    #[test]
+    fn top_down_bgra_bitmap_info_asks_for_top_down_32_bits_rgb() {
+        let header = top_down_bgra_bitmap_info(32, 48).bmiHeader;
+        assert_eq!(
+            header.biSize as usize,
+            std::mem::size_of::<winapi::BITMAPINFOHEADER>()
+        );
+        assert_eq!(header.biWidth, 32);
+        assert_eq!(header.biHeight, -48);
+        assert_eq!(header.biPlanes, 1);
+        assert_eq!(header.biBitCount, 32);
+        assert_eq!(header.biCompression, winapi::BI_RGB);
+    }
+
+    #[test]
+    fn alpha_from_and_mask_makes_the_masked_pixels_transparent() {
+        let mut pixels = vec![10, 20, 30, 0, 40, 50, 60, 0, 70, 80, 90, 0];
+        let mask = vec![0, 0, 0, 0, 255, 255, 255, 0, 0, 0, 0, 0];
+        alpha_from_and_mask(&mut pixels, &mask);
+        assert_eq!(
+            pixels,
+            vec![10, 20, 30, 255, 40, 50, 60, 0, 70, 80, 90, 255]
+        );
+    }
+}
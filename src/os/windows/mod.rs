@@ -0,0 +1,8 @@
+pub mod drop_target;
+pub mod hotkey;
+pub mod icons;
+pub mod packaged_browsers;
+pub mod single_instance;
+pub mod sys_browsers;
+pub mod tray;
+pub mod util;
@@ -0,0 +1,241 @@
+/*
+  Browsers installed from the Microsoft Store are packaged apps which are not
+  listed under StartMenuInternet. They are found through the packages of the
+  user declaring the http protocol in their manifest and are opened through
+  protocol activation since their executable cannot be started directly.
+  The apps opening the other schemes, ie. mailto, are found the same way.
+*/
+use std::path::Path;
+
+use bindings::windows::management::deployment::PackageManager;
+
+use crate::error::*;
+use crate::os::windows::sys_browsers::{Browser, VersionInfo};
+use crate::os::windows::util::{get_exe_file_icon, str_to_wide};
+
+mod winapi {
+    pub use winapi::ctypes::c_void;
+    pub use winapi::shared::guiddef::REFIID;
+    pub use winapi::shared::minwindef::DWORD;
+    pub use winapi::shared::ntdef::{HRESULT, LPCWSTR};
+    pub use winapi::shared::winerror::SUCCEEDED;
+    pub use winapi::shared::wtypesbase::CLSCTX_LOCAL_SERVER;
+    pub use winapi::um::combaseapi::{CoCreateInstance, CoInitializeEx};
+    pub use winapi::um::objbase::COINIT_APARTMENTTHREADED;
+    pub use winapi::um::shobjidl_core::{
+        ApplicationActivationManager, IShellItem, IShellItemArray, SHCreateItemFromParsingName,
+    };
+    pub use winapi::um::unknwnbase::{IUnknown, IUnknownVtbl};
+    pub use winapi::{Class, Interface};
+}
+
+use self::winapi::*;
+use ::winapi::RIDL;
+
+RIDL! {#[uuid(0x2e941141, 0x7f97, 0x4756, 0xba, 0x1d, 0x9d, 0xec, 0xde, 0x89, 0x4a, 0x3d)]
+interface IApplicationActivationManager(IApplicationActivationManagerVtbl):
+    IUnknown(IUnknownVtbl) {
+    fn ActivateApplication(
+        appUserModelId: LPCWSTR,
+        arguments: LPCWSTR,
+        options: DWORD,
+        processId: *mut DWORD,
+    ) -> HRESULT,
+    fn ActivateForFile(
+        appUserModelId: LPCWSTR,
+        itemArray: *mut IShellItemArray,
+        verb: LPCWSTR,
+        processId: *mut DWORD,
+    ) -> HRESULT,
+    fn ActivateForProtocol(
+        appUserModelId: LPCWSTR,
+        itemArray: *mut IShellItemArray,
+        processId: *mut DWORD,
+    ) -> HRESULT,
+}}
+
+#[link(name = "shell32")]
+extern "system" {
+    // missing from winapi
+    fn SHCreateShellItemArrayFromShellItem(
+        psi: *mut IShellItem,
+        riid: REFIID,
+        ppv: *mut *mut c_void,
+    ) -> HRESULT;
+}
+
+const MANIFEST_FILE_NAME: &str = "AppxManifest.xml";
+
+/// The packaged apps of the current user able to open http links
+pub fn read_packaged_browsers() -> BSResult<Vec<Browser>> {
+    read_packaged_handlers("http")
+}
+
+/// The packaged apps of the current user able to open the links of the `scheme`
+pub fn read_packaged_handlers(scheme: &str) -> BSResult<Vec<Browser>> {
+    let mut browsers = Vec::new();
+
+    // an empty security id stands for the current user
+    let packages = PackageManager::new()?.find_packages_by_user_security_id("")?;
+    let iterator = packages.first()?;
+    while iterator.has_current()? {
+        let package = iterator.current()?;
+        iterator.move_next()?;
+        if package.is_framework()? {
+            continue;
+        }
+
+        let installed_dir = package.installed_location()?.path()?.to_string();
+        let manifest =
+            match std::fs::read_to_string(Path::new(&installed_dir).join(MANIFEST_FILE_NAME)) {
+                Ok(manifest) => manifest,
+                Err(_) => continue,
+            };
+
+        let package_id = package.id()?;
+        let package_version = package_id.version()?;
+        for (app_id, executable) in protocol_applications(&manifest, scheme) {
+            let exe_path = Path::new(&installed_dir)
+                .join(&executable)
+                .to_string_lossy()
+                .to_string();
+            let display_name = package.display_name()?.to_string();
+            browsers.push(Browser {
+                name: display_name.clone(),
+                icon: exe_path.clone(),
+                handle_icon: get_exe_file_icon(&exe_path).unwrap_or(std::ptr::null_mut()),
+                exe_exists: Path::new(&exe_path).exists(),
+                version: VersionInfo {
+                    company_name: package.publisher_display_name()?.to_string(),
+                    product_name: display_name,
+                    product_version: format!(
+                        "{}.{}.{}.{}",
+                        package_version.major,
+                        package_version.minor,
+                        package_version.build,
+                        package_version.revision
+                    ),
+                    ..VersionInfo::default()
+                },
+                app_user_model_id: Some(format!("{}!{}", package_id.family_name()?, app_id)),
+                exe_path,
+                details_loaded: true,
+                ..Browser::default()
+            });
+        }
+    }
+
+    Ok(browsers)
+}
+
+/// The id and the executable of the applications in the package `manifest` declaring
+/// the protocol of the `scheme`. The manifest is simple enough to not need an XML parser.
+fn protocol_applications(manifest: &str, scheme: &str) -> Vec<(String, String)> {
+    let protocol_declaration = format!("Name=\"{}\"", scheme);
+    manifest
+        .split("<Application ")
+        .skip(1)
+        .filter_map(|application| {
+            let application = match application.find("</Application>") {
+                Some(end) => &application[..end],
+                None => application,
+            };
+            if !application.contains(&protocol_declaration) {
+                return None;
+            }
+
+            // the split removed the element name along with the space before the first attribute
+            let start_tag = format!(" {}", &application[..application.find('>')?]);
+            Some((
+                attribute_value(&start_tag, "Id")?,
+                attribute_value(&start_tag, "Executable").unwrap_or_default(),
+            ))
+        })
+        .collect()
+}
+
+fn attribute_value(start_tag: &str, name: &str) -> Option<String> {
+    let prefix = format!(" {}=\"", name);
+    let start = start_tag.find(&prefix)? + prefix.len();
+    let length = start_tag[start..].find('"')?;
+
+    Some(start_tag[start..start + length].to_owned())
+}
+
+/// Opens the `url` in the packaged app with the given `app_user_model_id`
+/// the same way Windows does when the app is the default browser.
+pub fn activate_for_protocol(app_user_model_id: &str, url: &str) -> BSResult<()> {
+    log::info!("Activating {} for {}", app_user_model_id, url);
+    let wide_url = str_to_wide(url);
+    let wide_app_user_model_id = str_to_wide(app_user_model_id);
+
+    unsafe {
+        // already done when the UI is shown, otherwise COM is not initialized yet
+        CoInitializeEx(std::ptr::null_mut(), COINIT_APARTMENTTHREADED);
+
+        let mut item: *mut IShellItem = std::ptr::null_mut();
+        let result = SHCreateItemFromParsingName(
+            wide_url.as_ptr(),
+            std::ptr::null_mut(),
+            &IShellItem::uuidof(),
+            &mut item as *mut *mut IShellItem as *mut *mut c_void,
+        );
+        if !SUCCEEDED(result) {
+            bail!(
+                "Cannot create a shell item for {}. Error code: {:#x}",
+                url,
+                result
+            );
+        }
+
+        let mut items: *mut IShellItemArray = std::ptr::null_mut();
+        let result = SHCreateShellItemArrayFromShellItem(
+            item,
+            &IShellItemArray::uuidof(),
+            &mut items as *mut *mut IShellItemArray as *mut *mut c_void,
+        );
+        (*item).Release();
+        if !SUCCEEDED(result) {
+            bail!(
+                "Cannot create a shell item array for {}. Error code: {:#x}",
+                url,
+                result
+            );
+        }
+
+        let mut activation_manager: *mut IApplicationActivationManager = std::ptr::null_mut();
+        let result = CoCreateInstance(
+            &ApplicationActivationManager::uuidof(),
+            std::ptr::null_mut(),
+            CLSCTX_LOCAL_SERVER,
+            &IApplicationActivationManager::uuidof(),
+            &mut activation_manager as *mut *mut IApplicationActivationManager as *mut *mut c_void,
+        );
+        if !SUCCEEDED(result) {
+            (*items).Release();
+            bail!(
+                "Cannot create the activation manager. Error code: {:#x}",
+                result
+            );
+        }
+
+        let mut process_id: DWORD = 0;
+        let result = (*activation_manager).ActivateForProtocol(
+            wide_app_user_model_id.as_ptr(),
+            items,
+            &mut process_id,
+        );
+        (*activation_manager).Release();
+        (*items).Release();
+        if !SUCCEEDED(result) {
+            bail!(
+                "Cannot activate {} for {}. Error code: {:#x}",
+                app_user_model_id,
+                url,
+                result
+            );
+        }
+    }
+
+    Ok(())
+}
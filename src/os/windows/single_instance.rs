@@ -0,0 +1,125 @@
+/*
+  Clicking several links in a row would stack up one window per link, instead
+  the first instance listens on a named pipe and the later launches hand their
+  URL over to it and exit. The pipe is gone as soon as the listening process
+  exits since Windows closes its handles, there is nothing to clean up.
+*/
+use std::io::{Read, Write};
+use std::os::windows::io::{FromRawHandle, RawHandle};
+use std::time::Duration;
+
+use crate::error::*;
+use crate::os::windows::util::str_to_wide;
+
+mod winapi {
+    pub use winapi::shared::minwindef::DWORD;
+    pub use winapi::shared::winerror::ERROR_PIPE_CONNECTED;
+    pub use winapi::um::errhandlingapi::GetLastError;
+    pub use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+    pub use winapi::um::namedpipeapi::{ConnectNamedPipe, CreateNamedPipeW};
+    pub use winapi::um::winbase::{
+        FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_INBOUND, PIPE_READMODE_BYTE,
+        PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+    pub use winapi::um::winnt::HANDLE;
+}
+
+const PIPE_BUFFER_SIZE: winapi::DWORD = 4096;
+
+// the running instance reads one URL at a time, the others wait for their turn
+const CONNECT_ATTEMPTS: u32 = 20;
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Named pipes are visible to every session of the machine
+/// thus each user gets a pipe of their own.
+fn pipe_name() -> String {
+    format!(
+        "\\\\.\\pipe\\{}-{}",
+        crate::APP_NAME,
+        std::env::var("USERNAME").unwrap_or_default()
+    )
+}
+
+/// Hands the `url` over to the instance already showing its window.
+/// Returns false when there is none, the caller then shows its own window.
+pub fn send_to_running_instance(url: &str) -> bool {
+    for _ in 0..CONNECT_ATTEMPTS {
+        match std::fs::OpenOptions::new().write(true).open(pipe_name()) {
+            Ok(mut pipe) => return pipe.write_all(url.as_bytes()).is_ok(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return false,
+            // every pipe instance is busy with the URL of another launch
+            Err(_) => std::thread::sleep(CONNECT_RETRY_DELAY),
+        }
+    }
+
+    false
+}
+
+/// Makes this process the running instance, `on_url` is called from another thread
+/// with each URL handed over by the later launches. Fails when another process
+/// became the running instance in the meantime.
+pub fn listen(on_url: impl Fn(String) + Send + 'static) -> BSResult<()> {
+    // handles are not `Send` as far as Rust knows, they are plain numbers to Windows
+    let mut pipe = create_pipe_instance(true)? as usize;
+
+    std::thread::spawn(move || loop {
+        let connected = unsafe {
+            winapi::ConnectNamedPipe(pipe as winapi::HANDLE, std::ptr::null_mut()) != 0
+                || winapi::GetLastError() == winapi::ERROR_PIPE_CONNECTED
+        };
+
+        // created before the current one is closed so that the pipe never disappears,
+        // otherwise a launch happening in between would show a window of its own
+        let next_pipe = match create_pipe_instance(false) {
+            Ok(next_pipe) => next_pipe as usize,
+            Err(e) => {
+                log::error!("Stopped receiving URLs from other launches. Reason: {}", e);
+                return;
+            }
+        };
+
+        // closes the handle when dropped
+        let mut current_pipe = unsafe { std::fs::File::from_raw_handle(pipe as RawHandle) };
+        let mut url = String::new();
+        if connected && current_pipe.read_to_string(&mut url).is_ok() && !url.trim().is_empty() {
+            on_url(url.trim().to_owned());
+        }
+
+        pipe = next_pipe;
+    });
+
+    Ok(())
+}
+
+fn create_pipe_instance(first: bool) -> BSResult<winapi::HANDLE> {
+    let name = str_to_wide(&pipe_name());
+    let open_mode = match first {
+        true => winapi::PIPE_ACCESS_INBOUND | winapi::FILE_FLAG_FIRST_PIPE_INSTANCE,
+        false => winapi::PIPE_ACCESS_INBOUND,
+    };
+
+    let pipe = unsafe {
+        winapi::CreateNamedPipeW(
+            name.as_ptr(),
+            open_mode,
+            winapi::PIPE_TYPE_BYTE
+                | winapi::PIPE_READMODE_BYTE
+                | winapi::PIPE_WAIT
+                | winapi::PIPE_REJECT_REMOTE_CLIENTS,
+            winapi::PIPE_UNLIMITED_INSTANCES,
+            0,
+            PIPE_BUFFER_SIZE,
+            0,
+            std::ptr::null_mut(),
+        )
+    };
+    if pipe == winapi::INVALID_HANDLE_VALUE {
+        bail!(
+            "Could not create the pipe {}. Error code: {}",
+            pipe_name(),
+            unsafe { winapi::GetLastError() }
+        );
+    }
+
+    Ok(pipe)
+}
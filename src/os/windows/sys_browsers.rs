@@ -0,0 +1,1735 @@
+use simple_error::SimpleResult as Result;
+
+use crate::browser_profiles::{read_profiles, BrowserProfile};
+use crate::config::CustomBrowser;
+use crate::error::*;
+use crate::os::shared::{
+    exe_path_matches, id_from_path, private_mode_flag, BrowserEntry, BrowserFamily,
+    BrowserProvider, LaunchMethod,
+};
+use crate::os::windows::packaged_browsers::{
+    activate_for_protocol, read_packaged_browsers, read_packaged_handlers,
+};
+use crate::{i18n, url_utils};
+mod winapi {
+    pub use winapi::shared::minwindef::DWORD;
+    pub use winapi::shared::windef::HICON;
+    pub use winapi::um::errhandlingapi::GetLastError;
+    pub use winapi::um::shellapi::ShellExecuteW;
+    pub use winapi::um::winnls::GetUserDefaultUILanguage;
+    pub use winapi::um::winnt::{
+        IMAGE_DOS_SIGNATURE, IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64,
+        IMAGE_FILE_MACHINE_I386, IMAGE_NT_SIGNATURE,
+    };
+    pub use winapi::um::winuser::SW_SHOWNORMAL;
+    pub use winapi::um::winver::{GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW};
+}
+
+/// The `Browser` data structure is an entry mapped to the
+/// a browser program installed on the user's OS. What determines
+/// the list of present browser is platform specific.
+#[derive(Debug, Clone)]
+pub struct Browser {
+    // The path to the executable binary or script that is the entry point
+    // of the browser program. This path is absolute and free of arguments.
+    pub exe_path: String,
+
+    // The arguments that should be passed when executing the browser binary
+    pub arguments: Vec<String>,
+
+    // User friendly browser program name, deducted from the executable metadata
+    // as defined by the program publisher
+    pub name: String,
+
+    // Path to the browser program icon/logo
+    pub icon: String,
+
+    pub handle_icon: winapi::HICON,
+    pub exe_exists: bool,
+    pub icon_exists: bool,
+    pub version: VersionInfo,
+
+    // Whether this is the browser set as default in the OS settings
+    pub is_default: bool,
+
+    pub launch_method: LaunchMethod,
+
+    // The profile of a Chromium based browser this entry opens, see `expand_profiles`
+    pub profile: Option<BrowserProfile>,
+
+    // The id of the packaged app opening the URLs, for the browsers from the Microsoft Store
+    pub app_user_model_id: Option<String>,
+
+    // Whether the version and the icon were read, see `read_details`
+    pub details_loaded: bool,
+}
+
+// The browsers are read on another thread than the UI one, see `read_details`.
+// The icon handle is the only field stopping this and it is valid in the whole process.
+unsafe impl Send for Browser {}
+
+impl Default for Browser {
+    fn default() -> Browser {
+        Browser {
+            exe_path: String::default(),
+            arguments: Vec::default(),
+            name: String::default(),
+            version: VersionInfo::default(),
+            icon: String::default(),
+            exe_exists: false,
+            icon_exists: false,
+            handle_icon: std::ptr::null_mut(),
+            is_default: false,
+            launch_method: LaunchMethod::default(),
+            profile: None,
+            app_user_model_id: None,
+            details_loaded: false,
+        }
+    }
+}
+
+impl From<&CustomBrowser> for Browser {
+    fn from(custom: &CustomBrowser) -> Browser {
+        let icon = custom.icon_path.clone().unwrap_or_default();
+
+        Browser {
+            exe_exists: std::path::Path::new(&custom.exe_path).exists(),
+            icon_exists: icon.len() > 0 && std::path::Path::new(&icon).exists(),
+            exe_path: custom.exe_path.clone(),
+            arguments: custom.arguments.clone(),
+            name: custom.name.clone(),
+            icon,
+            launch_method: custom.launch.clone(),
+            // the name and the icon are the ones from the config
+            details_loaded: true,
+            ..Browser::default()
+        }
+    }
+}
+
+impl BrowserEntry for Browser {
+    fn id(&self) -> String {
+        // custom entries can run the same program with different arguments
+        match self.arguments.len() {
+            0 => id_from_path(&self.exe_path),
+            _ => id_from_path(&format!("{} {}", self.exe_path, self.arguments.join(" "))),
+        }
+    }
+
+    fn display_name(&self) -> String {
+        let name = match self.version.product_name.len() {
+            0 => self.name.clone(),
+            _ => self.version.product_name.clone(),
+        };
+
+        match &self.profile {
+            Some(profile) => format!("{} ({})", name, profile.name),
+            None => name,
+        }
+    }
+
+    fn subtitle_parts(&self) -> Vec<String> {
+        vec![
+            match self.is_default {
+                true => String::from(i18n::t("default_browser")),
+                false => String::default(),
+            },
+            match self.exe_exists {
+                true => String::default(),
+                false => String::from(i18n::t("program_not_found")),
+            },
+            self.version.product_version.clone(),
+            self.version.binary_type.to_string(),
+            self.version.company_name.clone(),
+            self.version.file_description.clone(),
+        ]
+    }
+
+    fn details(&self) -> Vec<(String, String)> {
+        vec![
+            (String::from(i18n::t("detail_name")), self.display_name()),
+            (
+                String::from(i18n::t("detail_version")),
+                self.version.product_version.clone(),
+            ),
+            (
+                String::from(i18n::t("detail_vendor")),
+                self.version.company_name.clone(),
+            ),
+            (
+                String::from(i18n::t("detail_binary_type")),
+                self.version.binary_type.to_string(),
+            ),
+            (
+                String::from(i18n::t("detail_executable")),
+                self.exe_path.clone(),
+            ),
+            (
+                String::from(i18n::t("detail_profile")),
+                self.profile
+                    .as_ref()
+                    .map(|profile| profile.directory.clone())
+                    .unwrap_or_default(),
+            ),
+            (
+                String::from(i18n::t("detail_app_id")),
+                self.app_user_model_id.clone().unwrap_or_default(),
+            ),
+        ]
+    }
+
+    fn exe_path(&self) -> String {
+        self.exe_path.clone()
+    }
+
+    fn icon_source(&self) -> String {
+        match self.icon_exists {
+            true => self.icon.clone(),
+            false => self.exe_path.clone(),
+        }
+    }
+
+    fn vendor(&self) -> String {
+        vendor_name(&self.version.company_name)
+    }
+
+    fn command_line(&self) -> String {
+        // packaged apps are activated by their id instead of running their executable
+        if let Some(app_user_model_id) = &self.app_user_model_id {
+            return app_user_model_id.clone();
+        }
+
+        format_command_line(&self.exe_path, &self.arguments)
+    }
+
+    fn launch_command_lines(&self, urls: &[String], extra_args: &[String]) -> Vec<String> {
+        // packaged apps are activated once per URL, see `open_urls`
+        if let Some(app_user_model_id) = &self.app_user_model_id {
+            return urls
+                .iter()
+                .map(|url| format!("{} {}", app_user_model_id, quote_argument(url)))
+                .collect();
+        }
+
+        launch_arguments(urls, self, extra_args)
+            .iter()
+            .map(|arguments| match self.launch_method {
+                LaunchMethod::ShellOpen => shell_open_command_line(&self.exe_path, arguments),
+                LaunchMethod::Spawn => format_command_line(&self.exe_path, arguments),
+            })
+            .collect()
+    }
+
+    fn family(&self) -> BrowserFamily {
+        BrowserFamily::from_exe_path(&self.exe_path)
+    }
+
+    fn private_mode_flag(&self) -> Option<&'static str> {
+        private_mode_flag(&self.exe_path)
+    }
+
+    fn is_default(&self) -> bool {
+        self.is_default
+    }
+
+    fn is_referenced_by(&self, reference: &str) -> bool {
+        exe_path_matches(&self.exe_path, reference)
+    }
+
+    fn launch(&self, url: &str, extra_args: &[String]) -> BSResult<()> {
+        open_url(url, self, extra_args)
+    }
+
+    fn launch_all(&self, urls: &[String], extra_args: &[String]) -> BSResult<()> {
+        open_urls(urls, self, extra_args)
+    }
+}
+
+// The legal forms ending the company names, left out of the vendor names
+const COMPANY_SUFFIXES: &[&str] = &["LLC", "Corporation", "Corp", "Inc", "Ltd", "GmbH", "AS"];
+
+/// The `company_name` of the version info without its legal form, ie. `Google` for `Google LLC`
+fn vendor_name(company_name: &str) -> String {
+    let mut words: Vec<&str> = company_name.split_whitespace().collect();
+    while words.len() > 1 {
+        let last_word = words[words.len() - 1].trim_end_matches(&['.', ','][..]);
+        if !COMPANY_SUFFIXES
+            .iter()
+            .any(|suffix| suffix.eq_ignore_ascii_case(last_word))
+        {
+            break;
+        }
+        words.pop();
+    }
+
+    words.join(" ").trim_end_matches(',').to_owned()
+}
+
+const URL_PLACEHOLDER: &str = "{url}";
+
+/// How long `run_browser` watches a launched browser for exiting with an error right away,
+/// ie. a broken install or a bad argument. The process is polled every `LAUNCH_CHECK_INTERVAL`
+/// and left running afterwards. Exiting successfully within that time is common, ie. when the
+/// URL is handed to an already running instance, thus it is not an error.
+const LAUNCH_CHECK_DURATION: std::time::Duration = std::time::Duration::from_millis(200);
+const LAUNCH_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_millis(20);
+
+/// Runs the `browser` executable with its own arguments followed
+/// by the `extra_args` and lastly the `url` to be opened.
+/// If the arguments contain the `{url}` placeholder the URL is passed in its place instead.
+///
+/// Fails when the program cannot be started or when it exits with an error
+/// within `LAUNCH_CHECK_DURATION`, without waiting for the browser to close.
+pub fn open_url(url: &str, browser: &Browser, extra_args: &[String]) -> BSResult<()> {
+    open_urls(&[String::from(url)], browser, extra_args)
+}
+
+/// Same as `open_url` for several `urls`, they are all passed to a single run of the browser
+/// so that they open in the same window. Packaged apps and the browsers with the `{url}`
+/// placeholder in their arguments take one URL at a time thus they run once per URL.
+///
+/// The `urls` are checked with `url_utils::normalize_url` first, nothing is started when
+/// one of them is not a URL. The empty ones are passed as they are.
+pub fn open_urls(urls: &[String], browser: &Browser, extra_args: &[String]) -> BSResult<()> {
+    let urls = urls
+        .iter()
+        .map(|url| match url.is_empty() {
+            true => Ok(String::new()),
+            false => url_utils::normalize_url(url),
+        })
+        .collect::<BSResult<Vec<String>>>()?;
+    let urls = urls.as_slice();
+    if let Some(app_user_model_id) = &browser.app_user_model_id {
+        if !extra_args.is_empty() {
+            log::warn!(
+                "{} is a packaged app which takes no arguments, ignoring {}",
+                browser.name,
+                extra_args.join(" ")
+            );
+        }
+        for url in urls {
+            activate_for_protocol(app_user_model_id, url)?;
+        }
+        return Ok(());
+    }
+
+    for command_arguments in launch_arguments(urls, browser, extra_args) {
+        run_browser(browser, command_arguments)?;
+    }
+
+    Ok(())
+}
+
+/// The arguments of each run of the `browser` executable opening the `urls`, see `open_urls`
+fn launch_arguments(urls: &[String], browser: &Browser, extra_args: &[String]) -> Vec<Vec<String>> {
+    let mut command_arguments = browser.arguments.clone();
+    command_arguments.extend_from_slice(extra_args);
+
+    if command_arguments
+        .iter()
+        .any(|argument| argument.contains(URL_PLACEHOLDER))
+    {
+        return urls
+            .iter()
+            .map(|url| {
+                command_arguments
+                    .iter()
+                    .map(|argument| argument.replace(URL_PLACEHOLDER, url))
+                    .collect()
+            })
+            .collect();
+    }
+
+    command_arguments.extend_from_slice(urls);
+    vec![command_arguments]
+}
+
+/// Starts the `browser` executable with the given `command_arguments`
+fn run_browser(browser: &Browser, command_arguments: Vec<String>) -> BSResult<()> {
+    if browser.launch_method == LaunchMethod::ShellOpen {
+        return shell_open(&browser.exe_path, &command_arguments);
+    }
+
+    log::info!(
+        "Running {}",
+        format_command_line(&browser.exe_path, &command_arguments)
+    );
+
+    let mut child = match std::process::Command::new(&browser.exe_path)
+        .args(command_arguments)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            return Err(BSError::from(
+                format!(
+                    "Couldn't run browser program at {}. Reason: {}",
+                    browser.exe_path, e
+                )
+                .as_str(),
+            ))
+        }
+    };
+
+    check_launch(&browser.exe_path, LAUNCH_CHECK_DURATION, || {
+        child.try_wait()
+    })
+}
+
+/// Polls the status of a launched program with `try_wait` for the given `duration`,
+/// fails when the program at `exe_path` exited with an error or when its status cannot be read
+fn check_launch(
+    exe_path: &str,
+    duration: std::time::Duration,
+    mut try_wait: impl FnMut() -> std::io::Result<Option<std::process::ExitStatus>>,
+) -> BSResult<()> {
+    let deadline = std::time::Instant::now() + duration;
+    while std::time::Instant::now() < deadline {
+        match try_wait()? {
+            Some(status) if !status.success() => {
+                return Err(BSError::from(
+                    format!(
+                        "The browser program at {} failed to start, it exited with {}.",
+                        exe_path, status
+                    )
+                    .as_str(),
+                ))
+            }
+            Some(_) => break,
+            None => std::thread::sleep(LAUNCH_CHECK_INTERVAL),
+        }
+    }
+
+    Ok(())
+}
+
+/// Starts the program at `exe_path` through the shell "open" verb
+fn shell_open(exe_path: &str, arguments: &[String]) -> BSResult<()> {
+    let parameters = arguments
+        .iter()
+        .map(|argument| quote_argument(argument))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let operation = crate::os::util::str_to_wide("open");
+    let file = crate::os::util::str_to_wide(exe_path);
+    log::info!("Running {}", shell_open_command_line(exe_path, arguments));
+    let parameters = crate::os::util::str_to_wide(&parameters);
+
+    let result = unsafe {
+        winapi::ShellExecuteW(
+            std::ptr::null_mut(),
+            operation.as_ptr(),
+            file.as_ptr(),
+            parameters.as_ptr(),
+            std::ptr::null(),
+            winapi::SW_SHOWNORMAL,
+        )
+    };
+
+    // values up to 32 are error codes
+    if result as usize <= 32 {
+        return Err(BSError::from(
+            format!(
+                "Couldn't open browser program at {} through the shell. Error code: {}",
+                exe_path, result as usize
+            )
+            .as_str(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// What `shell_open` runs for the `exe_path` and the `arguments`, in the form of a command line
+fn shell_open_command_line(exe_path: &str, arguments: &[String]) -> String {
+    format!(
+        "ShellExecute open {}",
+        format_command_line(exe_path, arguments)
+    )
+}
+
+/// The `exe_path` followed by the `arguments`, each quoted with `quote_argument`
+fn format_command_line(exe_path: &str, arguments: &[String]) -> String {
+    std::iter::once(exe_path)
+        .chain(arguments.iter().map(String::as_str))
+        .map(quote_argument)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Quotes the `argument` for a command line string the way
+/// the MS C runtime splits it back into the program arguments
+fn quote_argument(argument: &str) -> String {
+    if !argument.is_empty() && !argument.contains(&[' ', '\t', '"'][..]) {
+        return String::from(argument);
+    }
+
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for character in argument.chars() {
+        match character {
+            '\\' => backslashes += 1,
+            '"' => {
+                // backslashes followed by a quote are escaped along with the quote
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                backslashes = 0;
+            }
+        }
+        if character != '\\' {
+            quoted.push(character);
+        }
+    }
+    // backslashes before the closing quote would escape it
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+
+    quoted
+}
+
+#[derive(Debug, Default)]
+struct WinExePath {
+    pub path_to_exe: String,
+    pub arguments: Vec<String>,
+}
+
+/// Windows paths can sometimes be formatted with arguments
+/// in the form of "C:\Path\To\Exe" --arg1 --arg2
+/// this method converts it into path string and arguments array
+impl From<&str> for WinExePath {
+    fn from(string_path: &str) -> Self {
+        // TODO: Support dobule quote escaped arguments "someArg"
+        if let [_, exe_path, args_part, ..] =
+            *string_path.split('"').collect::<Vec<&str>>().as_slice()
+        {
+            let arguments = match args_part.len() {
+                len if len > 0 => args_part.trim().split(' ').collect::<Vec<&str>>(),
+                _ => Vec::default(),
+            };
+            let arguments_len = arguments.len();
+
+            return WinExePath {
+                path_to_exe: String::from(exe_path),
+                arguments: arguments.into_iter().fold(
+                    Vec::with_capacity(arguments_len),
+                    |mut arg_list, arg| {
+                        arg_list.push(String::from(arg));
+                        arg_list
+                    },
+                ),
+            };
+        }
+
+        WinExePath {
+            path_to_exe: String::from(string_path),
+            arguments: Vec::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BinaryType {
+    X86,
+    X64,
+    Arm64,
+    None,
+}
+
+impl Default for BinaryType {
+    fn default() -> BinaryType {
+        BinaryType::None
+    }
+}
+
+impl std::fmt::Display for BinaryType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                BinaryType::X86 => "x86",
+                BinaryType::X64 => "x64",
+                BinaryType::Arm64 => "ARM64",
+                _ => "",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VersionInfo {
+    pub company_name: String,
+    pub file_description: String,
+    pub product_version: String,
+    pub product_name: String,
+    pub binary_type: BinaryType,
+}
+
+/// Finds the browsers through the registry keys Windows uses for the default apps settings
+#[derive(Debug, Default)]
+pub struct SystemBrowserProvider;
+
+impl BrowserProvider for SystemBrowserProvider {
+    type Browser = Browser;
+
+    fn read_browsers(&self) -> BSResult<Vec<Browser>> {
+        Ok(with_packaged_browsers(read_system_browsers_sync()?))
+    }
+
+    fn open(&self, url: &str, browser: &Browser) -> BSResult<()> {
+        open_url(url, browser, &[])
+    }
+}
+
+/// The same browsers as `SystemBrowserProvider` without their version and icon, reading them
+/// can take seconds ie. with network drives. They are read afterwards with `read_details`.
+pub fn read_browsers_without_details() -> BSResult<Vec<Browser>> {
+    Ok(with_packaged_browsers(dedup_browsers(
+        read_registered_browsers()?,
+    )))
+}
+
+/// Adds the packaged browsers to the `browsers` found in the registry
+fn with_packaged_browsers(browsers: Vec<Browser>) -> Vec<Browser> {
+    let packaged_browsers = read_packaged_browsers().unwrap_or_else(|e| {
+        log::error!("Could not read the packaged browsers. Reason: {}", e);
+        Vec::new()
+    });
+
+    with_packaged_apps(browsers, packaged_browsers)
+}
+
+/// Adds the `packaged_browsers` not already among the `browsers` found in the registry
+fn with_packaged_apps(mut browsers: Vec<Browser>, packaged_browsers: Vec<Browser>) -> Vec<Browser> {
+    // some Store browsers are also registered the classic way, ie. Firefox
+    for packaged_browser in packaged_browsers {
+        if !browsers
+            .iter()
+            .any(|browser| exe_path_matches(&browser.exe_path, &packaged_browser.exe_path))
+        {
+            browsers.push(packaged_browser);
+        }
+    }
+
+    browsers
+}
+
+pub fn read_system_browsers_sync() -> Result<Vec<Browser>> {
+    let mut list = read_registered_browsers()?;
+    for browser in list.iter_mut() {
+        read_details(browser);
+    }
+
+    Ok(dedup_browsers(list))
+}
+
+/// Reads the version and the icon of the `browser` unless they are already known
+pub fn read_details(browser: &mut Browser) {
+    if browser.details_loaded {
+        return;
+    }
+
+    browser.version = read_browser_exe_info(&browser.exe_path);
+    match crate::os::util::get_exe_file_icon(&browser.exe_path) {
+        Ok(icon) => browser.handle_icon = icon,
+        Err(e) => log::error!(
+            "Error loading icon from file {}, Reason: {}",
+            browser.exe_path,
+            e
+        ),
+    }
+    browser.details_loaded = true;
+}
+
+/// The browsers listed under StartMenuInternet, possibly several times each
+fn read_registered_browsers() -> Result<Vec<Browser>> {
+    // windows registry
+    let path32 = "SOFTWARE\\Clients\\StartMenuInternet";
+    let path64 = "SOFTWARE\\WOW6432Node\\Clients\\StartMenuInternet";
+    let local_machine = winreg::enums::HKEY_LOCAL_MACHINE;
+    let current_user = winreg::enums::HKEY_CURRENT_USER;
+    let mut list = [
+        read_browsers_from_reg_path_sync(local_machine, path32)?,
+        read_browsers_from_reg_path_sync(local_machine, path64)?,
+        // browsers installed for the current user only, ie. Chrome without admin rights
+        read_browsers_from_reg_path_sync(current_user, path32)?,
+    ]
+    .concat();
+
+    let default_browser_path = read_default_browser_exe_path().unwrap_or_else(|e| {
+        log::error!("Could not read the default browser. Reason: {}", e);
+        String::default()
+    });
+
+    for browser in list.iter_mut() {
+        let path_and_args = WinExePath::from(browser.exe_path.as_str());
+        browser.exe_path = path_and_args.path_to_exe;
+        browser.arguments = path_and_args.arguments;
+        browser.exe_exists = std::path::Path::new(&browser.exe_path).exists();
+        browser.is_default = default_browser_path.len() > 0
+            && exe_path_matches(&browser.exe_path, &default_browser_path);
+    }
+    Ok(list)
+}
+
+/// Keeps one entry per executable as a browser can be registered more than once,
+/// ie. for all users and for the current one. The paths are compared ignoring the case
+/// and the 8.3 short names, the most complete entry is kept, see `registration_rank`.
+fn dedup_browsers(browsers: Vec<Browser>) -> Vec<Browser> {
+    let mut unique_browsers: Vec<(String, Browser)> = Vec::with_capacity(browsers.len());
+    for browser in browsers {
+        let exe_path = crate::os::util::get_long_path(&browser.exe_path).to_lowercase();
+        match unique_browsers
+            .iter_mut()
+            .find(|(unique_exe_path, _)| *unique_exe_path == exe_path)
+        {
+            Some((_, kept_browser)) => {
+                let is_default = kept_browser.is_default || browser.is_default;
+                if registration_rank(&browser) > registration_rank(kept_browser) {
+                    *kept_browser = browser;
+                }
+                kept_browser.is_default = is_default;
+            }
+            None => unique_browsers.push((exe_path, browser)),
+        }
+    }
+
+    unique_browsers
+        .into_iter()
+        .map(|(_, browser)| browser)
+        .collect()
+}
+
+/// How complete the entry of the `browser` is, compared in the order of the fields. The version
+/// is only read by `read_details` thus the registry values decide when dedup runs before it.
+fn registration_rank(browser: &Browser) -> (bool, bool, bool, usize) {
+    (
+        browser.exe_exists,
+        !browser.icon.is_empty(),
+        !browser.name.is_empty(),
+        version_details_count(&browser.version),
+    )
+}
+
+fn version_details_count(version: &VersionInfo) -> usize {
+    [
+        &version.company_name,
+        &version.file_description,
+        &version.product_version,
+        &version.product_name,
+    ]
+    .iter()
+    .filter(|detail| !detail.is_empty())
+    .count()
+}
+
+// ProgId of the links opened through this program
+const URL_PROG_ID: &str = "BrowserSelectorURL";
+const CAPABILITIES_PATH: &str = "Software\\browser-selector\\Capabilities";
+const REGISTERED_APPLICATIONS_PATH: &str = "Software\\RegisteredApplications";
+const REGISTERED_APPLICATION_NAME: &str = "Browser Selector";
+const DEFAULT_APPS_SETTINGS_URI: &str = "ms-settings:defaultapps";
+
+/// Registers the running executable as a browser able to open http and https links
+/// for the current user, then opens the Windows "Default apps" settings.
+///
+/// Since Windows 10 programs cannot make themselves the default browser, the user has
+/// to pick it in the settings. `IApplicationAssociationRegistrationUI` only opens the
+/// same settings page on Windows 10 thus the page is opened directly.
+pub fn register_as_default() -> BSResult<()> {
+    let exe_path = std::env::current_exe()?.to_string_lossy().to_string();
+    let prog_id_path = format!("Software\\Classes\\{}", URL_PROG_ID);
+
+    write_registry_values(
+        &prog_id_path,
+        &[("", "Browser Selector URL"), ("URL Protocol", "")],
+    )?;
+    write_registry_values(
+        &format!("{}\\DefaultIcon", prog_id_path),
+        &[("", format!("\"{}\",0", exe_path).as_str())],
+    )?;
+    write_registry_values(
+        &format!("{}\\shell\\open\\command", prog_id_path),
+        &[("", format!("\"{}\" \"%1\"", exe_path).as_str())],
+    )?;
+    write_registry_values(
+        CAPABILITIES_PATH,
+        &[
+            ("ApplicationName", REGISTERED_APPLICATION_NAME),
+            (
+                "ApplicationDescription",
+                "Choose the browser opening each link",
+            ),
+        ],
+    )?;
+    write_registry_values(
+        &format!("{}\\URLAssociations", CAPABILITIES_PATH),
+        &[("http", URL_PROG_ID), ("https", URL_PROG_ID)],
+    )?;
+    write_registry_values(
+        REGISTERED_APPLICATIONS_PATH,
+        &[(REGISTERED_APPLICATION_NAME, CAPABILITIES_PATH)],
+    )?;
+
+    open_default_apps_settings()
+}
+
+/// Opens the Windows "Default apps" settings where the default browser is chosen
+pub fn open_default_apps_settings() -> BSResult<()> {
+    shell_open(DEFAULT_APPS_SETTINGS_URI, &[]).map_err(|e| {
+        BSError::from(format!("Could not open the Default apps settings. Reason: {}", e).as_str())
+    })
+}
+
+/// Removes the registry keys written by `register_as_default`,
+/// the ones already missing are skipped.
+pub fn unregister() -> BSResult<()> {
+    let current_user = winreg::RegKey::predef(winreg::enums::HKEY_CURRENT_USER);
+    let ignore_missing = |result: std::io::Result<()>, path: &str| match result {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(BSError::from(
+            format!(
+                "Could not remove the registry key HKCU\\{}. Reason: {}",
+                path, e
+            )
+            .as_str(),
+        )),
+        _ => Ok(()),
+    };
+
+    let registered_applications = current_user
+        .open_subkey_with_flags(REGISTERED_APPLICATIONS_PATH, winreg::enums::KEY_SET_VALUE);
+    ignore_missing(
+        registered_applications.and_then(|key| key.delete_value(REGISTERED_APPLICATION_NAME)),
+        REGISTERED_APPLICATIONS_PATH,
+    )?;
+
+    // the Capabilities key is the only one under the program key
+    let program_path = CAPABILITIES_PATH.trim_end_matches("\\Capabilities");
+    ignore_missing(current_user.delete_subkey_all(program_path), program_path)?;
+
+    let prog_id_path = format!("Software\\Classes\\{}", URL_PROG_ID);
+    ignore_missing(current_user.delete_subkey_all(&prog_id_path), &prog_id_path)
+}
+
+/// Creates the key at `path` under HKEY_CURRENT_USER if missing and sets the given
+/// name and value pairs on it, the empty name being the default value of the key.
+fn write_registry_values(path: &str, values: &[(&str, &str)]) -> BSResult<()> {
+    winreg::RegKey::predef(winreg::enums::HKEY_CURRENT_USER)
+        .create_subkey(path)
+        .and_then(|(key, _)| {
+            values
+                .iter()
+                .try_for_each(|(name, value)| key.set_value(name, value))
+        })
+        .map_err(|e| {
+            BSError::from(
+                format!(
+                    "Could not write the registry key HKCU\\{}. Reason: {}",
+                    path, e
+                )
+                .as_str(),
+            )
+        })
+}
+
+/// Replaces each Chromium based browser having several profiles with one entry per profile,
+/// the browsers already given a profile in their arguments are kept as they are.
+pub fn expand_profiles(browsers: Vec<Browser>) -> Vec<Browser> {
+    let mut expanded = Vec::with_capacity(browsers.len());
+    for browser in browsers {
+        let has_profile_argument = browser
+            .arguments
+            .iter()
+            .any(|argument| argument.starts_with("--profile-directory"));
+        let profiles = match has_profile_argument {
+            true => Vec::new(),
+            false => read_profiles(&browser.exe_path).unwrap_or_else(|e| {
+                log::error!(
+                    "Could not read the profiles of {}. Reason: {}",
+                    browser.exe_path,
+                    e
+                );
+                Vec::new()
+            }),
+        };
+
+        // with a single profile the browser opens it anyway
+        if profiles.len() < 2 {
+            expanded.push(browser);
+            continue;
+        }
+
+        for profile in profiles {
+            let mut profile_browser = browser.clone();
+            profile_browser.arguments.push(profile.argument());
+            profile_browser.profile = Some(profile);
+            expanded.push(profile_browser);
+        }
+    }
+
+    expanded
+}
+
+/// Returns the path to the executable of the browser the user has chosen
+/// as default for opening http links in the Windows settings.
+pub fn read_default_browser_exe_path() -> Result<String> {
+    read_default_handler_exe_path("http")
+}
+
+/// Returns the path to the executable of the program the user has chosen
+/// as default for opening the links of the `scheme` in the Windows settings.
+fn read_default_handler_exe_path(scheme: &str) -> Result<String> {
+    let user_choice_path = format!(
+        "Software\\Microsoft\\Windows\\Shell\\Associations\\UrlAssociations\\{}\\UserChoice",
+        scheme
+    );
+
+    let prog_id: String = match winreg::RegKey::predef(winreg::enums::HKEY_CURRENT_USER)
+        .open_subkey(user_choice_path)
+        .and_then(|key| key.get_value("ProgId"))
+    {
+        Ok(prog_id) => prog_id,
+        Err(e) => bail!(
+            "Cannot read the {} UserChoice ProgId. Reason: {}",
+            scheme,
+            e
+        ),
+    };
+
+    let command: String = match winreg::RegKey::predef(winreg::enums::HKEY_CLASSES_ROOT)
+        .open_subkey(format!("{}\\shell\\open\\command", prog_id))
+        .and_then(|key| key.get_value(""))
+    {
+        Ok(command) => command,
+        Err(e) => bail!("Cannot read the open command of {}. Reason: {}", prog_id, e),
+    };
+
+    Ok(WinExePath::from(command.as_str()).path_to_exe)
+}
+
+/// The programs registered in the Windows settings for opening the links of the `scheme`,
+/// ie. the mail programs for `mailto`, followed by the packaged apps declaring it.
+/// The version and the icon of the programs are read afterwards, see `read_details`.
+pub fn read_scheme_handlers(scheme: &str) -> BSResult<Vec<Browser>> {
+    let default_handler_path = read_default_handler_exe_path(scheme).unwrap_or_else(|e| {
+        log::error!(
+            "Could not read the default {} program. Reason: {}",
+            scheme,
+            e
+        );
+        String::default()
+    });
+
+    let mut handlers = Vec::new();
+    for root_key in &[
+        winreg::enums::HKEY_LOCAL_MACHINE,
+        winreg::enums::HKEY_CURRENT_USER,
+    ] {
+        let registered_applications =
+            match winreg::RegKey::predef(*root_key).open_subkey(REGISTERED_APPLICATIONS_PATH) {
+                Ok(key) => key,
+                Err(e) => {
+                    log::warn!(
+                        "Skipping registry path {}. Reason: {}",
+                        REGISTERED_APPLICATIONS_PATH,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+        for (application_name, _) in registered_applications.enum_values().filter_map(|x| x.ok()) {
+            // this program would only offer the same list again
+            if application_name == REGISTERED_APPLICATION_NAME {
+                continue;
+            }
+
+            let handler = registered_applications
+                .get_value::<String, _>(&application_name)
+                .and_then(|capabilities_path| {
+                    read_scheme_handler(*root_key, &capabilities_path, scheme, &application_name)
+                });
+            match handler {
+                Ok(Some(mut handler)) => {
+                    handler.is_default = default_handler_path.len() > 0
+                        && exe_path_matches(&handler.exe_path, &default_handler_path);
+                    handlers.push(handler);
+                }
+                Ok(None) => (),
+                Err(e) => log::error!(
+                    "Error reading the {} program {}. Reason: {}",
+                    scheme,
+                    application_name,
+                    e
+                ),
+            }
+        }
+    }
+
+    let packaged_handlers = read_packaged_handlers(scheme).unwrap_or_else(|e| {
+        log::error!(
+            "Could not read the packaged {} programs. Reason: {}",
+            scheme,
+            e
+        );
+        Vec::new()
+    });
+    Ok(with_packaged_apps(
+        dedup_browsers(handlers),
+        packaged_handlers,
+    ))
+}
+
+/// The program whose capabilities are at `capabilities_path`, `None` when it is not
+/// registered for the `scheme`. The `application_name` is used when its own is missing.
+fn read_scheme_handler(
+    root_key: winreg::HKEY,
+    capabilities_path: &str,
+    scheme: &str,
+    application_name: &str,
+) -> std::io::Result<Option<Browser>> {
+    let capabilities = winreg::RegKey::predef(root_key).open_subkey(capabilities_path)?;
+    let prog_id: String = match capabilities
+        .open_subkey("URLAssociations")
+        .and_then(|key| key.get_value(scheme))
+    {
+        Ok(prog_id) => prog_id,
+        Err(_) => return Ok(None),
+    };
+
+    let classes_root = winreg::RegKey::predef(winreg::enums::HKEY_CLASSES_ROOT);
+    let command: String = classes_root
+        .open_subkey(format!("{}\\shell\\open\\command", prog_id))?
+        .get_value("")?;
+    let command = WinExePath::from(command.as_str());
+    // the quoted "%1" is left out by `WinExePath`, the link is then passed last
+    let arguments = command
+        .arguments
+        .iter()
+        .filter(|argument| !argument.is_empty())
+        .map(|argument| argument.replace("%1", URL_PLACEHOLDER))
+        .collect();
+    let icon = classes_root
+        .open_subkey(format!("{}\\DefaultIcon", prog_id))
+        .and_then(|key| key.get_value(""))
+        .unwrap_or_else(|_| command.path_to_exe.clone());
+    // the names starting with @ point to a string resource of a dll
+    let name = match capabilities.get_value::<String, _>("ApplicationName") {
+        Ok(name) if !name.starts_with('@') => name,
+        _ => String::from(application_name),
+    };
+
+    Ok(Some(Browser {
+        name,
+        icon,
+        exe_exists: std::path::Path::new(&command.path_to_exe).exists(),
+        exe_path: command.path_to_exe,
+        arguments,
+        ..Browser::default()
+    }))
+}
+
+fn read_browsers_from_reg_path_sync(
+    root_key: winreg::HKEY,
+    win_reg_path: &str,
+) -> Result<Vec<Browser>> {
+    let root = match winreg::RegKey::predef(root_key).open_subkey(win_reg_path) {
+        Ok(root) => root,
+        Err(e) => {
+            // a missing key (ie. no WOW6432Node on 32 bits Windows) or a key we
+            // are not allowed to read should not stop reading the other locations
+            log::warn!("Skipping registry path {}. Reason: {}", win_reg_path, e);
+            return Ok(Vec::new());
+        }
+    };
+
+    let mut entries = Vec::new();
+    for key_result in root.enum_keys() {
+        match key_result {
+            Ok(key_name) => entries.push(read_browser_reg_values(&root, key_name)),
+            Err(e) => log::warn!("Skipping sub key of {}. Reason: {}", win_reg_path, e),
+        }
+    }
+
+    Ok(browsers_from_reg_values(win_reg_path, entries))
+}
+
+/// The values of a browser key under StartMenuInternet, each one is the error
+/// reading it when it is missing or cannot be read, see `browser_from_reg_values`
+struct BrowserRegValues {
+    // the name of the key, ie. `FIREFOX.EXE`
+    key_name: String,
+    name: std::io::Result<String>,
+    command: std::io::Result<String>,
+    icon: std::io::Result<String>,
+}
+
+fn read_browser_reg_values(root: &winreg::RegKey, key_name: String) -> BrowserRegValues {
+    // empty gives us (Default)
+    let read_value =
+        |path: &str| -> std::io::Result<String> { root.open_subkey(path)?.get_value("") };
+
+    BrowserRegValues {
+        name: read_value(&key_name),
+        command: read_value(&format!("{}\\shell\\open\\command", key_name)),
+        icon: read_value(&format!("{}\\DefaultIcon", key_name)),
+        key_name,
+    }
+}
+
+/// The browsers of the `entries` read under `win_reg_path`, the entries which cannot be
+/// used are logged and skipped so that a broken one does not hide the other browsers
+fn browsers_from_reg_values(win_reg_path: &str, entries: Vec<BrowserRegValues>) -> Vec<Browser> {
+    entries
+        .into_iter()
+        .filter_map(|values| {
+            let key_name = values.key_name.clone();
+            browser_from_reg_values(values)
+                .map_err(|e| {
+                    log::warn!(
+                        "Skipping the browser {}\\{}. Reason: {}",
+                        win_reg_path,
+                        key_name,
+                        e
+                    )
+                })
+                .ok()
+        })
+        .collect()
+}
+
+/// The browser registered with the `values`, which needs the command running it. The name
+/// and the icon are optional, the key name and the icon of the executable replace them.
+fn browser_from_reg_values(values: BrowserRegValues) -> Result<Browser> {
+    let BrowserRegValues {
+        key_name,
+        name,
+        command,
+        icon,
+    } = values;
+    // the arguments are split from the path in `read_registered_browsers`
+    let exe_path = match command {
+        Ok(command) => command,
+        Err(e) => bail!("The open command cannot be read. Reason: {}", e),
+    };
+    if WinExePath::from(exe_path.as_str())
+        .path_to_exe
+        .trim()
+        .is_empty()
+    {
+        bail!("The open command \"{}\" has no executable.", exe_path);
+    }
+    let name = name.unwrap_or_else(|e| {
+        log::warn!(
+            "Naming the browser {} after its key, the name cannot be read. Reason: {}",
+            key_name,
+            e
+        );
+        key_name.clone()
+    });
+    let icon = icon.unwrap_or_else(|e| {
+        log::warn!(
+            "Showing the icon of the executable of {}, the DefaultIcon cannot be read. Reason: {}",
+            name,
+            e
+        );
+        String::new()
+    });
+
+    Ok(Browser {
+        name,
+        exe_path,
+        icon,
+        ..Browser::default()
+    })
+}
+
+/// The version resource and the architecture of the executable at `path`,
+/// the details that cannot be read are left empty.
+fn read_browser_exe_info(path: &str) -> VersionInfo {
+    let mut ver_info = read_exe_version_info(path).unwrap_or_else(|e| {
+        log::error!(
+            "Error with reading browser info for {}. Reason: {}",
+            path,
+            e
+        );
+        VersionInfo::default()
+    });
+    ver_info.binary_type = read_exe_arch(path);
+
+    ver_info
+}
+
+// Only the start of the executable is read, the headers needed are found there
+const EXE_HEADER_READ_SIZE: u64 = 1024;
+
+// Position of the offset of the PE header within the MS-DOS header
+const PE_HEADER_OFFSET_POSITION: usize = 0x3c;
+
+/// For the given `path` it returns the architecture of the executable read from
+/// the machine field of its PE header, `BinaryType::None` when it cannot be read.
+fn read_exe_arch(path: &str) -> BinaryType {
+    use std::io::Read;
+
+    let mut header = Vec::with_capacity(EXE_HEADER_READ_SIZE as usize);
+    match std::fs::File::open(path)
+        .and_then(|file| file.take(EXE_HEADER_READ_SIZE).read_to_end(&mut header))
+    {
+        Ok(_) => binary_type_from_exe_header(&header),
+        Err(e) => {
+            log::warn!("Cannot read the header of {}. Reason: {}", path, e);
+            BinaryType::None
+        }
+    }
+}
+
+fn binary_type_from_exe_header(header: &[u8]) -> BinaryType {
+    let read_u16 = |offset: usize| {
+        header
+            .get(offset..offset + 2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+    };
+    let read_u32 = |offset: usize| {
+        header
+            .get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+
+    if read_u16(0) != Some(winapi::IMAGE_DOS_SIGNATURE) {
+        return BinaryType::None;
+    }
+    let pe_header_offset = match read_u32(PE_HEADER_OFFSET_POSITION) {
+        Some(offset) => offset as usize,
+        None => return BinaryType::None,
+    };
+    if read_u32(pe_header_offset) != Some(winapi::IMAGE_NT_SIGNATURE) {
+        return BinaryType::None;
+    }
+
+    // the machine is the first field of the COFF header following the signature
+    match read_u16(pe_header_offset + 4) {
+        Some(winapi::IMAGE_FILE_MACHINE_I386) => BinaryType::X86,
+        Some(winapi::IMAGE_FILE_MACHINE_AMD64) => BinaryType::X64,
+        Some(winapi::IMAGE_FILE_MACHINE_ARM64) => BinaryType::Arm64,
+        _ => BinaryType::None,
+    }
+}
+
+/// Reads file attributes specific to Windows executables as per the fields
+/// in `VersionInfo` struct based on the given `path`.
+///
+/// The fields read are:
+/// - ProductName
+/// - CompanyName
+/// - ProductVersion
+/// - FileDescription
+///
+/// Fields missing from the version resource are left empty.
+///
+/// ### Implementation details
+/// The implementation is overly complicated due to the goal of having a correct
+/// implementation as per Microsoft Docs, thus what we do is:
+///  - ask the OS what size is needed to hold the whole blob containing the file version fields
+///  - allocate a buffer with that size and ask the OS to copy the whole blob in our buffer
+///  - the blob contains some Windows specific hierarchy structures where the data we're interested in is beneath a certain language code
+///  - we ask the OS to tell us what are the metadata languages in the .exe file
+///  - we ask the OS what is the OS setting for the user's language and we pick .exe language that matches the UI default or the language neutral entry which Windows defines it as a lang code of 0, or the first element found
+///  - we ask the for specific values of the properties `ProductName`, `CompanyName`, `ProductVersion`, `FileDescription` and if they're not `UTF-16` we convert them based on the indicated `Code Page`.
+fn read_exe_version_info(path: &str) -> Result<VersionInfo> {
+    const UTF16_WINDOWS_CODE_PAGE: u16 = 1200;
+    let file_path_wide = crate::os::util::str_to_wide(path);
+    let file_version_size: u32 =
+        unsafe { winapi::GetFileVersionInfoSizeW(file_path_wide.as_ptr(), &mut 0) };
+    if file_version_size == 0 {
+        bail!(
+            "Cannot read file version size with GetFileVersionInfoSizeExW for {}",
+            path
+        );
+    }
+
+    unsafe {
+        let mut version_info_blob: Vec<u8> = vec![0; file_version_size as usize];
+        if winapi::GetFileVersionInfoW(
+            file_path_wide.as_ptr(),
+            0,
+            file_version_size,
+            version_info_blob.as_mut_ptr() as *mut std::ffi::c_void,
+        ) == 0
+        {
+            bail!(
+                "Cannot get file version info data with GetFileVersionInfoW for {}",
+                path
+            );
+        }
+
+        #[repr(C)]
+        #[derive(Debug)]
+        #[allow(non_snake_case)]
+        struct LANGANDCODEPAGE {
+            wLanguage: u16,
+            wCodePage: u16,
+        };
+        type PCLANGANDCODEPAGE = *const LANGANDCODEPAGE;
+
+        // pointer within `buffer` var above based on the sub block given to VerQueryValueW
+        let mut out_pointer = std::ptr::null_mut();
+
+        // the number of bytes VerQueryValueW has written for the the requested sub block from within the `version_info_blob`
+        let mut out_size: u32 = 0;
+
+        let translations_sub_block = crate::os::util::str_to_wide("\\VarFileInfo\\Translation");
+
+        let result = winapi::VerQueryValueW(
+            version_info_blob.as_ptr() as *mut std::ffi::c_void,
+            translations_sub_block.as_ptr(),
+            &mut out_pointer,
+            &mut out_size,
+        );
+
+        if result == 0
+            || (out_size as usize) < std::mem::size_of::<LANGANDCODEPAGE>()
+            || out_pointer == std::ptr::null_mut()
+        {
+            bail!(
+                "Failed to read version info for {}. GetLastError: {:#x}",
+                path,
+                winapi::GetLastError()
+            );
+        }
+
+        let translations_len = out_size as usize / std::mem::size_of::<LANGANDCODEPAGE>();
+
+        // TODO: do we need to forget this because it's technically part of the version_info_blob?
+        let translations: &[LANGANDCODEPAGE] =
+            std::slice::from_raw_parts(out_pointer as PCLANGANDCODEPAGE, translations_len);
+
+        let user_lang_id = winapi::GetUserDefaultUILanguage();
+        let default_lang_id = 0; // 0 means language neutral
+
+        // look at the translations list and find the one matching
+        // the OS language (user_lang_id) or find the language neutral one (default_lang_id)
+        // or just return the first element (&translations[0])
+        let translation: &LANGANDCODEPAGE = translations
+            .iter()
+            .find(|item| item.wLanguage == user_lang_id)
+            .unwrap_or_else(|| {
+                translations
+                    .iter()
+                    .find(|item| item.wLanguage == default_lang_id)
+                    .unwrap_or_else(|| &translations[0])
+            });
+
+        let base_block = format!(
+            "\\StringFileInfo\\{:04x}{:04x}",
+            translation.wLanguage, translation.wCodePage
+        );
+        let product_name_block = base_block.clone() + "\\ProductName";
+        let company_name_block = base_block.clone() + "\\CompanyName";
+        let product_version_block = base_block.clone() + "\\ProductVersion";
+        let file_description_block = base_block.clone() + "\\FileDescription";
+
+        let mut results = Vec::<String>::with_capacity(4);
+
+        for &block in [
+            &product_name_block,
+            &company_name_block,
+            &product_version_block,
+            &file_description_block,
+        ]
+        .iter()
+        {
+            // pointer within `buffer` var above based on the sub block given to VerQueryValueW
+            let mut out_pointer = std::ptr::null_mut();
+
+            // the number of bytes VerQueryValueW has written for the the requested sub block from within the `version_info_blob`
+            let mut out_size: u32 = 0;
+            let result = winapi::VerQueryValueW(
+                version_info_blob.as_ptr() as *mut std::ffi::c_void,
+                crate::os::util::str_to_wide(block).as_ptr(),
+                &mut out_pointer,
+                &mut out_size,
+            );
+
+            if result == 0 || out_size == 0 || out_pointer == std::ptr::null_mut() {
+                results.push(String::from(""));
+                continue;
+            }
+
+            let raw_wide_string: Vec<u16>;
+            if translation.wCodePage != UTF16_WINDOWS_CODE_PAGE {
+                // TODO: do we need to std::mem::forget this because it's technically part of the version_info_blob?
+                let raw_string =
+                    std::slice::from_raw_parts(out_pointer as *const i8, out_size as usize)
+                        .to_vec();
+                raw_wide_string =
+                    crate::os::util::ansi_str_to_wide(&raw_string, translation.wCodePage)?;
+            } else {
+                raw_wide_string =
+                    std::slice::from_raw_parts(out_pointer as *const u16, out_size as usize)
+                        .to_vec();
+            }
+
+            let result_str = crate::os::util::wide_to_str(&raw_wide_string);
+            results.push(result_str);
+        }
+
+        if let [product_name, company_name, product_version, file_description] = results.as_slice()
+        {
+            return Ok(VersionInfo {
+                product_name: product_name.into(),
+                product_version: product_version.into(),
+                company_name: company_name.into(),
+                file_description: file_description.into(),
+                ..Default::default()
+            });
+        } else {
+            bail!("Not all required props were found.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn browser_with_arguments(arguments: &[&str]) -> Browser {
+        Browser {
+            exe_path: String::from("C:\\Program Files\\Firefox\\firefox.exe"),
+            arguments: arguments
+                .iter()
+                .map(|argument| argument.to_string())
+                .collect(),
+            ..Browser::default()
+        }
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn launch_arguments_pass_all_the_urls_to_one_run() {
+        let browser = browser_with_arguments(&["-P", "work"]);
+        let urls = strings(&["https://a.test", "https://b.test"]);
+        assert_eq!(
+            launch_arguments(&urls, &browser, &strings(&["-private-window"])),
+            vec![strings(&[
+                "-P",
+                "work",
+                "-private-window",
+                "https://a.test",
+                "https://b.test"
+            ])]
+        );
+    }
+
+    #[test]
+    fn launch_arguments_run_once_per_url_with_the_placeholder() {
+        let browser = browser_with_arguments(&["--app={url}"]);
+        let urls = strings(&["https://a.test", "https://b.test"]);
+        assert_eq!(
+            launch_arguments(&urls, &browser, &[]),
+            vec![
+                strings(&["--app=https://a.test"]),
+                strings(&["--app=https://b.test"])
+            ]
+        );
+    }
+
+    // the stub browser program, exiting with the code given after `/c exit`
+    fn cmd_exe() -> String {
+        std::env::var("ComSpec").unwrap_or_else(|_| String::from("cmd.exe"))
+    }
+
+    #[test]
+    fn run_browser_fails_when_the_exe_is_missing() {
+        let browser = Browser {
+            exe_path: String::from("C:\\browser-selector-test\\missing.exe"),
+            ..Browser::default()
+        };
+        assert!(run_browser(&browser, strings(&["https://a.test"])).is_err());
+    }
+
+    #[test]
+    fn run_browser_succeeds_when_the_program_exits_cleanly() {
+        let browser = Browser {
+            exe_path: cmd_exe(),
+            ..Browser::default()
+        };
+        assert!(run_browser(&browser, strings(&["/c", "exit", "0"])).is_ok());
+    }
+
+    #[test]
+    fn check_launch_fails_when_the_program_exits_with_an_error() {
+        let mut child = std::process::Command::new(cmd_exe())
+            .args(&["/c", "exit", "1"])
+            .spawn()
+            .unwrap();
+        // the check ends as soon as the stub exits, the long duration only avoids a slow start
+        let duration = std::time::Duration::from_secs(10);
+        assert!(check_launch(&cmd_exe(), duration, || child.try_wait()).is_err());
+    }
+
+    #[test]
+    fn check_launch_fails_when_the_status_cannot_be_read() {
+        let duration = std::time::Duration::from_secs(10);
+        let result = check_launch("browser.exe", duration, || {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "access denied",
+            ))
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn check_launch_succeeds_while_the_program_runs() {
+        let duration = std::time::Duration::from_millis(50);
+        assert!(check_launch("browser.exe", duration, || Ok(None)).is_ok());
+    }
+
+    #[test]
+    fn quote_argument_leaves_the_plain_arguments() {
+        assert_eq!(quote_argument("-private-window"), "-private-window");
+        assert_eq!(
+            quote_argument("C:\\Firefox\\firefox.exe"),
+            "C:\\Firefox\\firefox.exe"
+        );
+    }
+
+    #[test]
+    fn quote_argument_quotes_the_spaces_and_the_empty_argument() {
+        assert_eq!(quote_argument(""), "\"\"");
+        assert_eq!(
+            quote_argument("C:\\Program Files\\firefox.exe"),
+            "\"C:\\Program Files\\firefox.exe\""
+        );
+    }
+
+    #[test]
+    fn quote_argument_escapes_the_quotes_and_the_backslashes_before_them() {
+        assert_eq!(quote_argument("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(quote_argument("a\\\"b"), "\"a\\\\\\\"b\"");
+        assert_eq!(quote_argument("C:\\My Dir\\"), "\"C:\\My Dir\\\\\"");
+    }
+
+    #[test]
+    fn format_command_line_quotes_the_exe_and_each_argument() {
+        assert_eq!(
+            format_command_line(
+                "C:\\Program Files\\firefox.exe",
+                &strings(&["-P", "my work", "https://a.test"])
+            ),
+            "\"C:\\Program Files\\firefox.exe\" -P \"my work\" https://a.test"
+        );
+    }
+
+    #[test]
+    fn win_exe_path_splits_the_quoted_path_from_the_arguments() {
+        let path_and_args = WinExePath::from("\"C:\\Program Files\\Opera\\launcher.exe\" --a --b");
+        assert_eq!(
+            path_and_args.path_to_exe,
+            "C:\\Program Files\\Opera\\launcher.exe"
+        );
+        assert_eq!(path_and_args.arguments, strings(&["--a", "--b"]));
+    }
+
+    #[test]
+    fn win_exe_path_without_arguments() {
+        let quoted = WinExePath::from("\"C:\\Program Files\\Mozilla Firefox\\firefox.exe\"");
+        assert_eq!(
+            quoted.path_to_exe,
+            "C:\\Program Files\\Mozilla Firefox\\firefox.exe"
+        );
+        assert!(quoted.arguments.is_empty());
+
+        let unquoted = WinExePath::from("C:\\Browsers\\chrome.exe");
+        assert_eq!(unquoted.path_to_exe, "C:\\Browsers\\chrome.exe");
+        assert!(unquoted.arguments.is_empty());
+    }
+
+    fn exe_header(machine: u16) -> Vec<u8> {
+        let pe_header_offset = 0x80;
+        let mut header = vec![0u8; 0x100];
+        header[..2].copy_from_slice(b"MZ");
+        header[PE_HEADER_OFFSET_POSITION..PE_HEADER_OFFSET_POSITION + 4]
+            .copy_from_slice(&(pe_header_offset as u32).to_le_bytes());
+        header[pe_header_offset..pe_header_offset + 4].copy_from_slice(b"PE\0\0");
+        header[pe_header_offset + 4..pe_header_offset + 6].copy_from_slice(&machine.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn binary_type_from_exe_header_reads_the_machine() {
+        assert!(matches!(
+            binary_type_from_exe_header(&exe_header(winapi::IMAGE_FILE_MACHINE_I386)),
+            BinaryType::X86
+        ));
+        assert!(matches!(
+            binary_type_from_exe_header(&exe_header(winapi::IMAGE_FILE_MACHINE_AMD64)),
+            BinaryType::X64
+        ));
+        assert!(matches!(
+            binary_type_from_exe_header(&exe_header(winapi::IMAGE_FILE_MACHINE_ARM64)),
+            BinaryType::Arm64
+        ));
+        assert!(matches!(
+            binary_type_from_exe_header(&exe_header(0x0200)),
+            BinaryType::None
+        ));
+    }
+
+    #[test]
+    fn binary_type_from_exe_header_rejects_the_invalid_headers() {
+        assert!(matches!(binary_type_from_exe_header(&[]), BinaryType::None));
+        assert!(matches!(
+            binary_type_from_exe_header(b"#!/bin/sh\nexec firefox"),
+            BinaryType::None
+        ));
+
+        let mut no_pe_signature = exe_header(winapi::IMAGE_FILE_MACHINE_AMD64);
+        no_pe_signature[0x80] = b'X';
+        assert!(matches!(
+            binary_type_from_exe_header(&no_pe_signature),
+            BinaryType::None
+        ));
+
+        // the PE header offset points past the bytes read
+        let truncated = exe_header(winapi::IMAGE_FILE_MACHINE_AMD64)[..0x82].to_vec();
+        assert!(matches!(
+            binary_type_from_exe_header(&truncated),
+            BinaryType::None
+        ));
+    }
+
+    #[test]
+    fn launch_command_lines_show_how_the_browser_is_started() {
+        let urls = strings(&["https://a.test"]);
+        let mut browser = browser_with_arguments(&["-P", "work"]);
+        assert_eq!(
+            browser.launch_command_lines(&urls, &[]),
+            vec!["\"C:\\Program Files\\Firefox\\firefox.exe\" -P work https://a.test"]
+        );
+
+        browser.launch_method = LaunchMethod::ShellOpen;
+        assert_eq!(
+            browser.launch_command_lines(&urls, &[]),
+            vec!["ShellExecute open \"C:\\Program Files\\Firefox\\firefox.exe\" -P work https://a.test"]
+        );
+    }
+
+    #[test]
+    fn dedup_browsers_keeps_the_most_complete_entry_of_each_exe() {
+        let for_all_users = Browser {
+            exe_path: String::from("C:\\Program Files\\Browser\\browser.exe"),
+            is_default: true,
+            ..Browser::default()
+        };
+        let for_current_user = Browser {
+            exe_path: String::from("c:\\program files\\BROWSER\\Browser.exe"),
+            name: String::from("Browser"),
+            icon: String::from("c:\\program files\\BROWSER\\Browser.exe,0"),
+            ..Browser::default()
+        };
+        let other = Browser {
+            exe_path: String::from("C:\\Program Files\\Other\\other.exe"),
+            ..Browser::default()
+        };
+
+        let browsers = dedup_browsers(vec![for_all_users, other, for_current_user]);
+        assert_eq!(browsers.len(), 2);
+        assert_eq!(browsers[0].name, "Browser");
+        assert!(browsers[0].icon.ends_with(",0"));
+        // the default browser stays the default whichever entry is kept
+        assert!(browsers[0].is_default);
+        assert_eq!(browsers[1].exe_path, "C:\\Program Files\\Other\\other.exe");
+    }
+
+    #[test]
+    fn vendor_name_leaves_out_the_legal_form() {
+        assert_eq!(vendor_name("Google LLC"), "Google");
+        assert_eq!(vendor_name("Microsoft Corporation"), "Microsoft");
+        assert_eq!(vendor_name("Brave Software, Inc."), "Brave Software");
+        assert_eq!(vendor_name("Opera Norway AS"), "Opera Norway");
+        assert_eq!(vendor_name("Mozilla"), "Mozilla");
+        // a name made only of a legal form is kept
+        assert_eq!(vendor_name("Inc"), "Inc");
+        assert_eq!(vendor_name(""), "");
+    }
+
+    fn reg_values(key_name: &str, command: Option<&str>, icon: Option<&str>) -> BrowserRegValues {
+        let value = |value: Option<&str>| match value {
+            Some(value) => Ok(String::from(value)),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "The system cannot find the file specified.",
+            )),
+        };
+
+        BrowserRegValues {
+            key_name: String::from(key_name),
+            name: value(Some(key_name)).map(|name| name.to_lowercase()),
+            command: value(command),
+            icon: value(icon),
+        }
+    }
+
+    #[test]
+    fn browser_from_reg_values_reads_the_complete_entry() {
+        let browser = browser_from_reg_values(reg_values(
+            "Firefox",
+            Some("\"C:\\Firefox\\firefox.exe\" -osint"),
+            Some("C:\\Firefox\\firefox.exe,0"),
+        ))
+        .unwrap();
+        assert_eq!(browser.name, "firefox");
+        assert_eq!(browser.exe_path, "\"C:\\Firefox\\firefox.exe\" -osint");
+        assert_eq!(browser.icon, "C:\\Firefox\\firefox.exe,0");
+    }
+
+    #[test]
+    fn browser_from_reg_values_needs_the_command_with_an_exe() {
+        let missing_command = browser_from_reg_values(reg_values("Firefox", None, Some("")));
+        assert!(missing_command
+            .unwrap_err()
+            .to_string()
+            .contains("cannot find the file"));
+
+        let missing_exe = browser_from_reg_values(reg_values("Firefox", Some("\"\" -osint"), None));
+        assert!(missing_exe.is_err());
+        assert!(browser_from_reg_values(reg_values("Firefox", Some(" "), None)).is_err());
+    }
+
+    #[test]
+    fn browser_from_reg_values_does_without_the_name_and_the_icon() {
+        let mut values = reg_values("FIREFOX.EXE", Some("C:\\Firefox\\firefox.exe"), None);
+        values.name = Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "Access is denied.",
+        ));
+        let browser = browser_from_reg_values(values).unwrap();
+        assert_eq!(browser.name, "FIREFOX.EXE");
+        assert_eq!(browser.icon, "");
+        assert_eq!(browser.icon_source(), "C:\\Firefox\\firefox.exe");
+    }
+
+    #[test]
+    fn browsers_from_reg_values_skip_the_broken_entries() {
+        let browsers = browsers_from_reg_values(
+            "SOFTWARE\\Clients\\StartMenuInternet",
+            vec![
+                reg_values("Chrome", Some("C:\\Chrome\\chrome.exe"), Some("")),
+                reg_values("Broken", None, None),
+                reg_values("Firefox", Some("C:\\Firefox\\firefox.exe"), None),
+            ],
+        );
+        let names: Vec<&str> = browsers
+            .iter()
+            .map(|browser| browser.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["chrome", "firefox"]);
+    }
+}
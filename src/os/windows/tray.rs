@@ -0,0 +1,227 @@
+/*
+  The notification area icon of the `--daemon` mode. The shell reports the
+  clicks on the icon as messages to a window, a hidden window is created for
+  them by a thread of its own which waits for its messages, like the hot key one.
+*/
+use std::cell::RefCell;
+use std::sync::mpsc;
+
+use crate::error::*;
+use crate::i18n;
+use crate::os::windows::util::str_to_wide;
+
+mod winapi {
+    pub use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+    pub use winapi::shared::windef::{HICON, HWND, POINT};
+    pub use winapi::um::libloaderapi::GetModuleHandleW;
+    pub use winapi::um::shellapi::{
+        Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE, NOTIFYICONDATAW,
+    };
+    pub use winapi::um::winuser::{
+        AppendMenuW, CreatePopupMenu, CreateWindowExW, DefWindowProcW, DestroyMenu,
+        DispatchMessageW, GetCursorPos, GetMessageW, LoadIconW, PostMessageW, RegisterClassW,
+        SetForegroundWindow, TrackPopupMenu, TranslateMessage, IDI_APPLICATION, MAKEINTRESOURCEW,
+        MF_STRING, MSG, TPM_RETURNCMD, TPM_RIGHTBUTTON, WM_APP, WM_LBUTTONUP, WM_NULL,
+        WM_RBUTTONUP, WNDCLASSW,
+    };
+}
+
+use self::winapi::*;
+
+/// What the user asked for through the tray icon
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrayCommand {
+    /// The icon was clicked
+    ShowWindow,
+    OpenClipboardUrl,
+    OpenSettings,
+    Quit,
+}
+
+const TRAY_ICON_ID: UINT = 1;
+const TRAY_CALLBACK_MESSAGE: UINT = WM_APP + 1;
+const WINDOW_CLASS_NAME: &str = "browser-selector-tray";
+
+// IDI_APP_ICON in browser-selector-rt.rc
+const APP_ICON_RESOURCE_ID: u16 = 102;
+
+// the items of the context menu in their order, with their menu ids and `i18n` keys
+const MENU_ITEMS: [(usize, &str, TrayCommand); 3] = [
+    (1, "tray_open_clipboard_url", TrayCommand::OpenClipboardUrl),
+    (2, "tray_settings", TrayCommand::OpenSettings),
+    (3, "tray_quit", TrayCommand::Quit),
+];
+
+thread_local! {
+    // called by `window_proc` on the thread of the tray window
+    static ON_COMMAND: RefCell<Option<Box<dyn Fn(TrayCommand)>>> = RefCell::new(None);
+}
+
+/// The icon in the notification area, it is removed when dropped.
+/// The process exiting does not remove it, the icon would stay until hovered.
+pub struct TrayIcon {
+    // the window receiving the clicks, handles are plain numbers to Windows
+    hwnd: usize,
+}
+
+impl Drop for TrayIcon {
+    fn drop(&mut self) {
+        let mut icon_data = notify_icon_data(self.hwnd as HWND);
+        unsafe { Shell_NotifyIconW(NIM_DELETE, &mut icon_data) };
+    }
+}
+
+/// Adds the icon with the `tooltip` to the notification area, `on_command`
+/// is called from another thread with what the user picked
+pub fn show(
+    tooltip: &str,
+    on_command: impl Fn(TrayCommand) + Send + 'static,
+) -> BSResult<TrayIcon> {
+    let (created_sender, created) = mpsc::channel::<Option<usize>>();
+    let tooltip = String::from(tooltip);
+
+    std::thread::spawn(move || unsafe {
+        ON_COMMAND.with(|handler| *handler.borrow_mut() = Some(Box::new(on_command)));
+        let hwnd = create_window();
+        let is_added = !hwnd.is_null() && add_icon(hwnd, &tooltip);
+        let _ = created_sender.send(Some(hwnd as usize).filter(|_| is_added));
+        if !is_added {
+            return;
+        }
+
+        let mut message: MSG = std::mem::zeroed();
+        while GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) > 0 {
+            TranslateMessage(&message);
+            DispatchMessageW(&message);
+        }
+    });
+
+    match created.recv() {
+        Ok(Some(hwnd)) => Ok(TrayIcon { hwnd }),
+        _ => bail!("Cannot add the icon to the notification area."),
+    }
+}
+
+/// A window that is never shown, a message-only one cannot be the
+/// foreground window which the context menu needs
+unsafe fn create_window() -> HWND {
+    let class_name = str_to_wide(WINDOW_CLASS_NAME);
+    let instance = GetModuleHandleW(std::ptr::null());
+    let class = WNDCLASSW {
+        lpfnWndProc: Some(window_proc),
+        hInstance: instance,
+        lpszClassName: class_name.as_ptr(),
+        ..std::mem::zeroed()
+    };
+    RegisterClassW(&class);
+
+    CreateWindowExW(
+        0,
+        class_name.as_ptr(),
+        class_name.as_ptr(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+        instance,
+        std::ptr::null_mut(),
+    )
+}
+
+unsafe fn add_icon(hwnd: HWND, tooltip: &str) -> bool {
+    let mut icon_data = notify_icon_data(hwnd);
+    icon_data.uFlags = NIF_ICON | NIF_MESSAGE | NIF_TIP;
+    icon_data.uCallbackMessage = TRAY_CALLBACK_MESSAGE;
+    icon_data.hIcon = load_app_icon();
+
+    // the tooltip is cut to fit, leaving room for the ending NUL
+    let max_tooltip_len = icon_data.szTip.len() - 1;
+    for (destination, source) in icon_data
+        .szTip
+        .iter_mut()
+        .zip(tooltip.encode_utf16().take(max_tooltip_len))
+    {
+        *destination = source;
+    }
+
+    Shell_NotifyIconW(NIM_ADD, &mut icon_data) != 0
+}
+
+/// The fields identifying the icon of the `hwnd`
+fn notify_icon_data(hwnd: HWND) -> NOTIFYICONDATAW {
+    let mut icon_data: NOTIFYICONDATAW = unsafe { std::mem::zeroed() };
+    icon_data.cbSize = std::mem::size_of::<NOTIFYICONDATAW>() as u32;
+    icon_data.hWnd = hwnd;
+    icon_data.uID = TRAY_ICON_ID;
+    icon_data
+}
+
+/// The icon embedded in the executable, the generic application icon when missing
+unsafe fn load_app_icon() -> HICON {
+    let instance = GetModuleHandleW(std::ptr::null());
+    let icon = LoadIconW(instance, MAKEINTRESOURCEW(APP_ICON_RESOURCE_ID));
+    match icon.is_null() {
+        true => LoadIconW(std::ptr::null_mut(), IDI_APPLICATION),
+        false => icon,
+    }
+}
+
+unsafe extern "system" fn window_proc(
+    hwnd: HWND,
+    message: UINT,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    let command = match (message, lparam as UINT) {
+        (TRAY_CALLBACK_MESSAGE, WM_LBUTTONUP) => Some(TrayCommand::ShowWindow),
+        (TRAY_CALLBACK_MESSAGE, WM_RBUTTONUP) => show_menu(hwnd),
+        _ => return DefWindowProcW(hwnd, message, wparam, lparam),
+    };
+
+    if let Some(command) = command {
+        ON_COMMAND.with(|handler| {
+            if let Some(on_command) = handler.borrow().as_ref() {
+                on_command(command);
+            }
+        });
+    }
+    0
+}
+
+/// Shows the context menu at the mouse cursor, returns the command picked from it
+unsafe fn show_menu(hwnd: HWND) -> Option<TrayCommand> {
+    let menu = CreatePopupMenu();
+    for (id, text_key, _) in MENU_ITEMS.iter() {
+        AppendMenuW(
+            menu,
+            MF_STRING,
+            *id,
+            str_to_wide(i18n::t(*text_key)).as_ptr(),
+        );
+    }
+
+    let mut cursor: POINT = std::mem::zeroed();
+    GetCursorPos(&mut cursor);
+
+    // otherwise the menu stays open when clicking elsewhere
+    SetForegroundWindow(hwnd);
+    let chosen_id = TrackPopupMenu(
+        menu,
+        TPM_RETURNCMD | TPM_RIGHTBUTTON,
+        cursor.x,
+        cursor.y,
+        0,
+        hwnd,
+        std::ptr::null(),
+    );
+    PostMessageW(hwnd, WM_NULL, 0, 0);
+    DestroyMenu(menu);
+
+    MENU_ITEMS
+        .iter()
+        .find(|(id, _, _)| *id as i32 == chosen_id)
+        .map(|(_, _, command)| *command)
+}
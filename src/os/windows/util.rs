@@ -0,0 +1,599 @@
+use raw_window_handle::HasRawWindowHandle;
+use simple_error::SimpleResult as Result;
+
+use crate::error::*;
+use winapi::um::stringapiset::MultiByteToWideChar;
+use winapi::um::winuser::MessageBoxW;
+
+// TODO: Do we really need this function?
+// use winapi::winrt::roapi::RoInitialize;
+// pub unsafe fn initialize_runtime_com() -> winrt::Result<()> {
+//   let result = winrt::ErrorCode::from(Ok(RoInitialize(
+//     winapi::winrt::roapi::RO_INIT_MULTITHREADED, // TODO: Investigate if we need multithreaded due to winnit event loop
+//   )));
+
+//   if result.is_ok() {
+//     return winrt::Result::Ok(());
+//   }
+
+//   winapi::um::combaseapi::CoInitializeEx(std::ptr::null_mut(), 0x2);
+
+//   return Err(winrt::Error::from(result));
+// }
+
+pub fn get_hwnd(window: &winit::window::Window) -> winapi::shared::windef::HWND {
+    match window.raw_window_handle() {
+        raw_window_handle::RawWindowHandle::Windows(wnd_handle) => {
+            wnd_handle.hwnd as winapi::shared::windef::HWND
+        }
+        _ => panic!("No MS Windows specific window handle. Wrong platform?"),
+    }
+}
+
+pub fn str_to_wide(string: &str) -> Vec<u16> {
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+
+    OsStr::new(string).encode_wide().chain(once(0)).collect()
+}
+
+/// Converts a UTF-16 string read from the Windows API, ending at the first NUL if any.
+/// Invalid UTF-16, such as lone surrogates written by some installers, is replaced
+/// by U+FFFD instead of failing since these strings are only displayed.
+pub fn wide_to_str(buf: &[u16]) -> String {
+    let string_length = buf.iter().position(|c| *c == 0).unwrap_or(buf.len());
+    let wide_string = &buf[..string_length];
+
+    match String::from_utf16(wide_string) {
+        Ok(string) => string,
+        Err(_) => {
+            let string = String::from_utf16_lossy(wide_string);
+            log::debug!("Replaced invalid UTF-16 characters in \"{}\".", string);
+            string
+        }
+    }
+}
+
+/// From the given buffer `src_string` use the Windows API to convert the
+/// ANSI string with the given `code_page`.
+///
+/// [MSDN Info](https://docs.microsoft.com/en-us/windows/win32/intl/code-pages)
+pub fn ansi_str_to_wide(src_string: &Vec<i8>, code_page: u16) -> Result<Vec<u16>> {
+    let receiving_capacity = src_string.len() * 2;
+    // generally single byte strings use 1 byte per character
+    // we allocate twice of that hoping we won't get truncated
+
+    let mut dst_string = Vec::<u16>::with_capacity(receiving_capacity);
+    let result = unsafe {
+        MultiByteToWideChar(
+            code_page as u32,
+            0,
+            src_string.as_ptr() as *const i8,
+            src_string.len() as i32, // size in bytes is the same as the length
+            dst_string.as_mut_ptr(),
+            (receiving_capacity - 1) as i32,
+        )
+    };
+    // this call is safe as long as we trust WinAPI
+    // to respect the indicated capacities in cbMultiByte (param 4)
+    // and in cchWideChar (param 6)
+
+    if result == 0 {
+        bail!("Could not convert the given string. Call GetLastError from WinAPI to find out why.");
+    }
+
+    // the API wrote `result` characters which the Vec does not know about
+    unsafe { dst_string.set_len(result as usize) };
+    Ok(dst_string)
+}
+
+pub fn as_u8_slice(v: &[u32]) -> &[u8] {
+    let element_size = std::mem::size_of::<u32>();
+    unsafe { std::slice::from_raw_parts(v.as_ptr() as *const u8, v.len() * element_size) }
+}
+
+pub fn get_exe_file_icon(path: &str) -> Result<winapi::shared::windef::HICON> {
+    use winapi::um::shellapi::{SHGetFileInfoW, SHFILEINFOW, SHGFI_ICON, SHGFI_LARGEICON};
+
+    let wide_path = crate::os::util::str_to_wide(&path);
+    let mut file_info: SHFILEINFOW = unsafe { std::mem::MaybeUninit::zeroed().assume_init() };
+    let res = unsafe {
+        SHGetFileInfoW(
+            wide_path.as_ptr(),
+            0,
+            &mut file_info,
+            std::mem::size_of_val(&file_info) as u32,
+            SHGFI_ICON | SHGFI_LARGEICON,
+        )
+    };
+
+    if res == 0 {
+        bail!("Cannot get icon with SHGetFileInfoW for {}", path);
+    }
+
+    // Icons given by this function should be destroyed
+    // with DestroyIcon. At the same time, according to this
+    // https://docs.microsoft.com/en-gb/windows/win32/api/winuser/nf-winuser-loadimagea?redirectedfrom=MSDN#remarks
+    // it looks like resources are automatically released
+    // when the program ends which is what we need here
+    // ToDO: investigate if this causes any memory leaks
+    Ok(file_info.hIcon)
+}
+
+pub fn get_config_directory() -> BSResult<String> {
+    get_known_folder(&winapi::um::knownfolders::FOLDERID_RoamingAppData)
+}
+
+/// The directory shared by all the users of the machine (`%PROGRAMDATA%`)
+pub fn get_machine_config_directory() -> BSResult<String> {
+    get_known_folder(&winapi::um::knownfolders::FOLDERID_ProgramData)
+}
+
+/// The directory for the data of the current user which is kept on this machine only
+pub fn get_local_data_directory() -> BSResult<String> {
+    get_known_folder(&winapi::um::knownfolders::FOLDERID_LocalAppData)
+}
+
+fn get_known_folder(folder_id: &winapi::shared::guiddef::GUID) -> BSResult<String> {
+    use winapi::shared::winerror::S_OK;
+    use winapi::um::combaseapi::CoTaskMemFree;
+    use winapi::um::shlobj::SHGetKnownFolderPath;
+
+    let mut wide_system_path: *mut u16 = std::ptr::null_mut();
+    let result_path: BSResult<String> = unsafe {
+        match SHGetKnownFolderPath(folder_id, 0, std::ptr::null_mut(), &mut wide_system_path) {
+            S_OK => {
+                let mut string_length = 0usize;
+                while *wide_system_path.add(string_length) != 0 {
+                    string_length += 1;
+                }
+
+                // copy the string out, the memory itself is owned by the OS
+                // and is released below with CoTaskMemFree as recommended by the WinAPI
+                let buff = std::slice::from_raw_parts(wide_system_path, string_length).to_vec();
+                Ok(wide_to_str(&buff))
+            }
+            code => Err(BSError::from(
+                format!("Error getting OS config directory. Error code: {:?}", code).as_str(),
+            )),
+        }
+    };
+
+    unsafe { CoTaskMemFree(wide_system_path as *mut std::ffi::c_void) };
+    result_path
+}
+
+pub fn get_create_config_directory(app_name: &str, env_name: &str) -> BSResult<String> {
+    let app_data_dir = get_config_directory()?;
+    let os_path = std::path::Path::new(&app_data_dir);
+    let subpath = format!("{}/{}", app_name, env_name);
+    let app_env_path = std::path::Path::new(&subpath);
+    let full_path = os_path.join(app_env_path);
+    let full_path_str = full_path.to_string_lossy().to_string();
+
+    if std::fs::create_dir_all(&full_path).is_err() {
+        bail!("Error creating config path {}", full_path_str);
+    }
+
+    Ok(full_path_str)
+}
+
+/// Returns the path to the executable of the process owning
+/// the window the user is currently working with.
+pub fn get_foreground_process_exe_path() -> BSResult<String> {
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::OpenProcess;
+    use winapi::um::winbase::QueryFullProcessImageNameW;
+    use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+    use winapi::um::winuser::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.is_null() {
+        bail!("There is no foreground window.");
+    }
+
+    let mut process_id: u32 = 0;
+    unsafe { GetWindowThreadProcessId(hwnd, &mut process_id) };
+    if process_id == 0 {
+        bail!("Cannot get the process of the foreground window.");
+    }
+
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process_id) };
+    if process.is_null() {
+        bail!(
+            "Cannot open process {} to query its executable.",
+            process_id
+        );
+    }
+
+    const MAX_PATH_LEN: u32 = 32767; // max length of extended paths
+    let mut path_len = MAX_PATH_LEN;
+    let mut wide_path = vec![0u16; MAX_PATH_LEN as usize];
+    let result =
+        unsafe { QueryFullProcessImageNameW(process, 0, wide_path.as_mut_ptr(), &mut path_len) };
+    unsafe { CloseHandle(process) };
+
+    if result == 0 {
+        bail!("Cannot read the executable path of process {}.", process_id);
+    }
+
+    wide_path.truncate(path_len as usize);
+    Ok(wide_to_str(&wide_path))
+}
+
+/// The `path` with its 8.3 short names, ie. `PROGRA~1`, expanded to the long names.
+/// The `path` is returned as it is when it cannot be expanded, ie. when it does not exist.
+pub fn get_long_path(path: &str) -> String {
+    let wide_path = str_to_wide(path);
+    let required_len = unsafe {
+        winapi::um::fileapi::GetLongPathNameW(wide_path.as_ptr(), std::ptr::null_mut(), 0)
+    };
+    if required_len == 0 {
+        return String::from(path);
+    }
+
+    let mut long_path: Vec<u16> = vec![0; required_len as usize];
+    let len = unsafe {
+        winapi::um::fileapi::GetLongPathNameW(
+            wide_path.as_ptr(),
+            long_path.as_mut_ptr(),
+            required_len,
+        )
+    };
+    match len {
+        0 => String::from(path),
+        _ => wide_to_str(&long_path),
+    }
+}
+
+/// Whether the given `window` is the one the user is currently working with
+pub fn is_foreground_window(window: &winit::window::Window) -> bool {
+    unsafe { winapi::um::winuser::GetForegroundWindow() == get_hwnd(window) }
+}
+
+/// Activates the `window` above the others with the keyboard focus, Windows only lets
+/// the process that received the last input do it, ie. the one launched by a click
+/// on a link or whose hot key was pressed
+pub fn bring_to_foreground(window: &winit::window::Window) {
+    use winapi::um::winuser::{BringWindowToTop, SetFocus, SetForegroundWindow};
+
+    let hwnd = get_hwnd(window);
+    unsafe {
+        BringWindowToTop(hwnd);
+        SetForegroundWindow(hwnd);
+        // the key presses are read from the window, not from the XAML island inside it,
+        // thus the arrows and the typed filter work without clicking the window first
+        SetFocus(hwnd);
+    }
+}
+
+/// The local date and time as `2021-01-31 23:59:59.999`
+pub fn get_local_time_text() -> String {
+    let mut time: winapi::um::minwinbase::SYSTEMTIME = unsafe { std::mem::zeroed() };
+    unsafe { winapi::um::sysinfoapi::GetLocalTime(&mut time) };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+        time.wYear,
+        time.wMonth,
+        time.wDay,
+        time.wHour,
+        time.wMinute,
+        time.wSecond,
+        time.wMilliseconds
+    )
+}
+
+/// The position of the mouse cursor on the virtual screen in physical pixels,
+/// `None` when it cannot be read, ie. on the secure desktop.
+pub fn get_cursor_position() -> Option<winit::dpi::PhysicalPosition<i32>> {
+    let mut point = winapi::shared::windef::POINT { x: 0, y: 0 };
+    match unsafe { winapi::um::winuser::GetCursorPos(&mut point) } {
+        0 => None,
+        _ => Some(winit::dpi::PhysicalPosition::new(point.x, point.y)),
+    }
+}
+
+/// The name of the Windows display language, ie. `de-DE`, `None` when it cannot be read
+pub fn get_ui_language_name() -> Option<String> {
+    use winapi::um::winnls::{GetUserDefaultUILanguage, LCIDToLocaleName};
+    use winapi::um::winnt::LOCALE_NAME_MAX_LENGTH;
+
+    let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+    let length = unsafe {
+        // the language id is a locale id with the default sort order
+        let locale_id = GetUserDefaultUILanguage() as u32;
+        LCIDToLocaleName(locale_id, name.as_mut_ptr(), name.len() as i32, 0)
+    };
+    match length {
+        0 => None,
+        _ => Some(wide_to_str(&name)),
+    }
+}
+
+/// Whether the app mode is set to dark in the Windows settings, light is assumed when unknown
+pub fn is_dark_mode() -> bool {
+    winreg::RegKey::predef(winreg::enums::HKEY_CURRENT_USER)
+        .open_subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize")
+        .and_then(|key| key.get_value::<u32, _>("AppsUseLightTheme"))
+        .map(|apps_use_light_theme| apps_use_light_theme == 0)
+        .unwrap_or(false)
+}
+
+/// Puts the `text` on the clipboard replacing what was there
+pub fn copy_to_clipboard(text: &str) -> BSResult<()> {
+    use winapi::um::winbase::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+    use winapi::um::winuser::{
+        CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData, CF_UNICODETEXT,
+    };
+
+    let wide_text = str_to_wide(text);
+    let size = wide_text.len() * std::mem::size_of::<u16>();
+    unsafe {
+        // the clipboard takes ownership of the memory once the data is set
+        let memory = GlobalAlloc(GMEM_MOVEABLE, size);
+        if memory.is_null() {
+            bail!("Cannot allocate {} bytes for the clipboard.", size);
+        }
+        let destination = GlobalLock(memory) as *mut u16;
+        if destination.is_null() {
+            GlobalFree(memory);
+            bail!("Cannot lock the memory for the clipboard.");
+        }
+        std::ptr::copy_nonoverlapping(wide_text.as_ptr(), destination, wide_text.len());
+        GlobalUnlock(memory);
+
+        if OpenClipboard(std::ptr::null_mut()) == 0 {
+            GlobalFree(memory);
+            bail!("Cannot open the clipboard, another program is using it.");
+        }
+        EmptyClipboard();
+        let is_set = !SetClipboardData(CF_UNICODETEXT, memory).is_null();
+        CloseClipboard();
+        if !is_set {
+            GlobalFree(memory);
+            bail!("Cannot put the text on the clipboard.");
+        }
+    }
+
+    Ok(())
+}
+
+/// The text on the clipboard, empty when it holds something else such as an image
+pub fn read_clipboard_text() -> BSResult<String> {
+    use winapi::um::winbase::{GlobalLock, GlobalSize, GlobalUnlock};
+    use winapi::um::winuser::{CloseClipboard, GetClipboardData, OpenClipboard, CF_UNICODETEXT};
+
+    unsafe {
+        if OpenClipboard(std::ptr::null_mut()) == 0 {
+            bail!("Cannot open the clipboard, another program is using it.");
+        }
+        let memory = GetClipboardData(CF_UNICODETEXT);
+        let source = match memory.is_null() {
+            true => std::ptr::null_mut(),
+            false => GlobalLock(memory) as *const u16,
+        };
+        let text = match source.is_null() {
+            true => String::new(),
+            false => {
+                let length = GlobalSize(memory) / std::mem::size_of::<u16>();
+                let text = wide_to_str(std::slice::from_raw_parts(source, length));
+                GlobalUnlock(memory);
+                text
+            }
+        };
+        CloseClipboard();
+
+        Ok(text)
+    }
+}
+
+/// Opens the file at `path` in the program associated with its type, like Explorer does
+pub fn open_with_default_program(path: &str) -> BSResult<()> {
+    use winapi::um::shellapi::ShellExecuteW;
+    use winapi::um::winuser::SW_SHOWNORMAL;
+
+    let operation = str_to_wide("open");
+    let file = str_to_wide(path);
+    let result = unsafe {
+        ShellExecuteW(
+            std::ptr::null_mut(),
+            operation.as_ptr(),
+            file.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+
+    // values up to 32 are error codes
+    if result as usize <= 32 {
+        bail!("Cannot open {}. Error code: {}", path, result as usize);
+    }
+
+    Ok(())
+}
+
+/// Closes the WinHTTP handle when dropped
+struct InternetHandle(winapi::um::winhttp::HINTERNET);
+
+impl Drop for InternetHandle {
+    fn drop(&mut self) {
+        unsafe { winapi::um::winhttp::WinHttpCloseHandle(self.0) };
+    }
+}
+
+/// The URL reached from `url` by following its redirects with a HEAD request,
+/// each step of the request (resolving, connecting, sending, receiving) gets the `timeout`.
+pub fn follow_redirects(url: &str, timeout: std::time::Duration) -> BSResult<String> {
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::winhttp::{
+        WinHttpConnect, WinHttpOpen, WinHttpOpenRequest, WinHttpQueryOption,
+        WinHttpReceiveResponse, WinHttpSendRequest, WinHttpSetTimeouts,
+        WINHTTP_ACCESS_TYPE_DEFAULT_PROXY, WINHTTP_FLAG_SECURE, WINHTTP_OPTION_URL,
+    };
+
+    let parsed = match url::Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(e) => bail!("Cannot parse the URL {}. Reason: {}", url, e),
+    };
+    let host = match parsed.host_str() {
+        Some(host) => str_to_wide(host),
+        None => bail!("The URL {} has no host.", url),
+    };
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    let path = str_to_wide(&parsed[url::Position::BeforePath..url::Position::AfterQuery]);
+    let flags = match parsed.scheme() {
+        "https" => WINHTTP_FLAG_SECURE,
+        _ => 0,
+    };
+    let timeout = timeout.as_millis() as i32;
+
+    unsafe {
+        let session = InternetHandle(WinHttpOpen(
+            str_to_wide(crate::APP_NAME).as_ptr(),
+            WINHTTP_ACCESS_TYPE_DEFAULT_PROXY,
+            std::ptr::null(),
+            std::ptr::null(),
+            0,
+        ));
+        if session.0.is_null() {
+            bail!(
+                "Cannot open an HTTP session. Error code: {}",
+                GetLastError()
+            );
+        }
+        WinHttpSetTimeouts(session.0, timeout, timeout, timeout, timeout);
+
+        let connection = InternetHandle(WinHttpConnect(session.0, host.as_ptr(), port, 0));
+        if connection.0.is_null() {
+            bail!("Cannot connect to {}. Error code: {}", url, GetLastError());
+        }
+
+        // redirects are followed by default, the request ends up with the final URL
+        let request = InternetHandle(WinHttpOpenRequest(
+            connection.0,
+            str_to_wide("HEAD").as_ptr(),
+            path.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            std::ptr::null_mut(),
+            flags,
+        ));
+        if request.0.is_null()
+            || WinHttpSendRequest(
+                request.0,
+                std::ptr::null(),
+                0,
+                std::ptr::null_mut(),
+                0,
+                0,
+                0,
+            ) == 0
+            || WinHttpReceiveResponse(request.0, std::ptr::null_mut()) == 0
+        {
+            bail!(
+                "The request to {} failed. Error code: {}",
+                url,
+                GetLastError()
+            );
+        }
+
+        const MAX_URL_LEN: usize = 2048;
+        let mut final_url: Vec<u16> = vec![0; MAX_URL_LEN];
+        let mut final_url_size = (MAX_URL_LEN * std::mem::size_of::<u16>()) as u32;
+        if WinHttpQueryOption(
+            request.0,
+            WINHTTP_OPTION_URL,
+            final_url.as_mut_ptr() as *mut winapi::ctypes::c_void,
+            &mut final_url_size,
+        ) == 0
+        {
+            bail!(
+                "Cannot read where {} leads. Error code: {}",
+                url,
+                GetLastError()
+            );
+        }
+
+        Ok(wide_to_str(&final_url))
+    }
+}
+
+/// Whether the Shift key is held down at the moment of the call
+pub fn is_shift_key_down() -> bool {
+    is_key_down(winapi::um::winuser::VK_SHIFT)
+}
+
+/// Whether the Alt key is held down at the moment of the call
+pub fn is_alt_key_down() -> bool {
+    is_key_down(winapi::um::winuser::VK_MENU)
+}
+
+/// Whether the Ctrl key is held down at the moment of the call
+pub fn is_ctrl_key_down() -> bool {
+    is_key_down(winapi::um::winuser::VK_CONTROL)
+}
+
+fn is_key_down(virtual_key: i32) -> bool {
+    // the most significant bit is set while the key is down
+    (unsafe { winapi::um::winuser::GetAsyncKeyState(virtual_key) } as u16) & 0x8000 != 0
+}
+
+/// Shows the error that stopped the program in a dialog
+pub fn output_error_text(text: String) {
+    let wide_text = str_to_wide(&text);
+    let title = str_to_wide(&format!("{} error", crate::APP_NAME));
+    unsafe {
+        MessageBoxW(
+            std::ptr::null_mut(),
+            wide_text.as_ptr(),
+            title.as_ptr(),
+            winapi::um::winuser::MB_OK | winapi::um::winuser::MB_ICONERROR,
+        );
+    }
+}
+
+pub fn output_panic_text(text: String) {
+    let wide_text = str_to_wide(&text);
+    let title = str_to_wide(&"Panic!");
+    unsafe {
+        MessageBoxW(
+            std::ptr::null_mut(),
+            wide_text.as_ptr(),
+            title.as_ptr(),
+            winapi::um::winuser::MB_OK,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wide(string: &str) -> Vec<u16> {
+        string.encode_utf16().collect()
+    }
+
+    #[test]
+    fn wide_to_str_ends_at_the_first_nul() {
+        assert_eq!(wide_to_str(&str_to_wide("Firefox")), "Firefox");
+        let nul_in_between = [wide("Fire"), vec![0], wide("fox")].concat();
+        assert_eq!(wide_to_str(&nul_in_between), "Fire");
+        assert_eq!(wide_to_str(&[0, 0]), "");
+    }
+
+    #[test]
+    fn wide_to_str_without_nul_takes_the_whole_buffer() {
+        assert_eq!(wide_to_str(&wide("Microsoft Edge")), "Microsoft Edge");
+        assert_eq!(wide_to_str(&wide("😀 Brave")), "😀 Brave");
+        assert_eq!(wide_to_str(&[]), "");
+    }
+
+    #[test]
+    fn wide_to_str_replaces_a_lone_surrogate() {
+        let lone_surrogate = [wide("Opera"), vec![0xD800], wide(" GX"), vec![0]].concat();
+        assert_eq!(wide_to_str(&lone_surrogate), "Opera\u{FFFD} GX");
+    }
+}
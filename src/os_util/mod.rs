@@ -1,11 +0,0 @@
-#[cfg(target_os = "windows")]
-mod windows_browsers;
-#[cfg(target_os = "windows")]
-mod windows_util;
-#[cfg(target_os = "windows")]
-pub use windows_util::*;
-
-pub mod os_browsers {
-    #[cfg(target_os = "windows")]
-    pub use super::windows_browsers::*;
-}
@@ -0,0 +1,396 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::*;
+use crate::store::Store;
+
+const HOST_CHOICES_KEY: &str = "host_choices";
+const DEFAULT_BROWSER_KEY: &str = "default_browser";
+const MANUAL_ORDER_KEY: &str = "manual_order";
+const LAST_CHOICE_KEY: &str = "last_choice";
+const ALWAYS_USE_KEY: &str = "always_use";
+
+// Upper limit of hosts to remember, the oldest entries are dropped first
+const MAX_HOST_CHOICES: usize = 500;
+
+/// State the program learns while being used, as opposed to `Config`
+/// which is only written by the user. Kept in a `Store`, the fields are
+/// named after their keys so that the exported prefs read the same.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Prefs {
+    /// The last browser chosen for each URL host ordered from the oldest to the newest
+    pub host_choices: Vec<HostChoice>,
+
+    /// Id of the browser chosen with Alt+Enter, pre-selected when nothing else decides
+    pub default_browser: Option<String>,
+
+    /// Ids of the browsers in the order the user dragged them to, empty when never reordered
+    pub manual_order: Vec<String>,
+
+    /// Id of the browser chosen the last time in the list
+    pub last_choice: Option<String>,
+
+    /// The browsers the user ticked "Always use" for, by URL host
+    pub always_use: Vec<HostChoice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostChoice {
+    pub host: String,
+    pub browser_id: String,
+}
+
+impl Prefs {
+    /// Reads the preferences from the `store`,
+    /// when nothing was saved yet the defaults are returned.
+    pub fn load(store: &dyn Store) -> BSResult<Prefs> {
+        let mut prefs = Prefs::default();
+        if let Some(host_choices) = store.get(HOST_CHOICES_KEY) {
+            prefs.host_choices = host_choices.try_into()?;
+        }
+        if let Some(default_browser) = store.get(DEFAULT_BROWSER_KEY) {
+            prefs.default_browser = Some(default_browser.try_into()?);
+        }
+        if let Some(manual_order) = store.get(MANUAL_ORDER_KEY) {
+            prefs.manual_order = manual_order.try_into()?;
+        }
+        if let Some(last_choice) = store.get(LAST_CHOICE_KEY) {
+            prefs.last_choice = Some(last_choice.try_into()?);
+        }
+        if let Some(always_use) = store.get(ALWAYS_USE_KEY) {
+            prefs.always_use = always_use.try_into()?;
+        }
+
+        Ok(prefs)
+    }
+
+    pub fn save(&self, store: &mut dyn Store) -> BSResult<()> {
+        store.set(HOST_CHOICES_KEY, toml::Value::try_from(&self.host_choices)?);
+        // the forgotten ones, ie. by `prune_missing_browsers`, are removed from the store
+        match &self.default_browser {
+            Some(default_browser) => store.set(
+                DEFAULT_BROWSER_KEY,
+                toml::Value::from(default_browser.as_str()),
+            ),
+            None => store.remove(DEFAULT_BROWSER_KEY),
+        }
+        store.set(MANUAL_ORDER_KEY, toml::Value::try_from(&self.manual_order)?);
+        match &self.last_choice {
+            Some(last_choice) => {
+                store.set(LAST_CHOICE_KEY, toml::Value::from(last_choice.as_str()))
+            }
+            None => store.remove(LAST_CHOICE_KEY),
+        }
+        store.set(ALWAYS_USE_KEY, toml::Value::try_from(&self.always_use)?);
+        store.flush()
+    }
+
+    /// Forgets the browsers that are not in `browser_ids` anymore, ie. after they were
+    /// uninstalled, so that they fall back to the defaults. Returns whether anything changed.
+    pub fn prune_missing_browsers(&mut self, browser_ids: &[String]) -> bool {
+        let is_missing = |browser_id: &String| !browser_ids.contains(browser_id);
+        let mut changed = false;
+
+        if let Some(browser_id) = self.default_browser.as_ref().filter(|id| is_missing(id)) {
+            log::warn!(
+                "Forgetting {} = {} which was not found.",
+                DEFAULT_BROWSER_KEY,
+                browser_id
+            );
+            self.default_browser = None;
+            changed = true;
+        }
+        if let Some(browser_id) = self.last_choice.as_ref().filter(|id| is_missing(id)) {
+            log::warn!(
+                "Forgetting {} = {} which was not found.",
+                LAST_CHOICE_KEY,
+                browser_id
+            );
+            self.last_choice = None;
+            changed = true;
+        }
+
+        let host_choices_count = self.host_choices.len();
+        self.host_choices.retain(|choice| {
+            if is_missing(&choice.browser_id) {
+                log::warn!(
+                    "Forgetting the {} entry for {} pointing to {} which was not found.",
+                    HOST_CHOICES_KEY,
+                    choice.host,
+                    choice.browser_id
+                );
+            }
+            !is_missing(&choice.browser_id)
+        });
+        changed |= host_choices_count != self.host_choices.len();
+
+        let always_use_count = self.always_use.len();
+        self.always_use.retain(|choice| {
+            if is_missing(&choice.browser_id) {
+                log::warn!(
+                    "Forgetting the {} entry for {} pointing to {} which was not found.",
+                    ALWAYS_USE_KEY,
+                    choice.host,
+                    choice.browser_id
+                );
+            }
+            !is_missing(&choice.browser_id)
+        });
+        changed |= always_use_count != self.always_use.len();
+
+        // the missing browsers in the manual order are harmless but would pile up
+        let manual_order_len = self.manual_order.len();
+        self.manual_order
+            .retain(|browser_id| !is_missing(browser_id));
+        changed |= manual_order_len != self.manual_order.len();
+
+        changed
+    }
+
+    /// The id of the browser last chosen for the given `host`
+    pub fn host_choice(&self, host: &str) -> Option<&str> {
+        self.host_choices
+            .iter()
+            .find(|choice| choice.host == host)
+            .map(|choice| choice.browser_id.as_str())
+    }
+
+    /// Remembers the browser chosen for the `host` as the newest entry,
+    /// evicting the oldest ones above `MAX_HOST_CHOICES`.
+    pub fn set_host_choice(&mut self, host: &str, browser_id: &str) {
+        self.host_choices.retain(|choice| choice.host != host);
+        self.host_choices.push(HostChoice {
+            host: String::from(host),
+            browser_id: String::from(browser_id),
+        });
+
+        if self.host_choices.len() > MAX_HOST_CHOICES {
+            let overflow = self.host_choices.len() - MAX_HOST_CHOICES;
+            self.host_choices.drain(0..overflow);
+        }
+    }
+
+    /// The id of the browser to always use for the given `host`. Only the exact host
+    /// matches unless `include_subdomains`, then the entry of the closest parent host does too.
+    pub fn always_use_browser(&self, host: &str, include_subdomains: bool) -> Option<&str> {
+        let exact_match = self.always_use.iter().find(|choice| choice.host == host);
+        let parent_match = || {
+            self.always_use
+                .iter()
+                .filter(|choice| host.ends_with(&format!(".{}", choice.host)))
+                .max_by_key(|choice| choice.host.len())
+        };
+
+        match (exact_match, include_subdomains) {
+            (Some(choice), _) => Some(choice.browser_id.as_str()),
+            (None, true) => parent_match().map(|choice| choice.browser_id.as_str()),
+            (None, false) => None,
+        }
+    }
+
+    /// Opens the URLs of the `host` in the given browser from now on
+    pub fn set_always_use(&mut self, host: &str, browser_id: &str) {
+        self.always_use.retain(|choice| choice.host != host);
+        self.always_use.push(HostChoice {
+            host: String::from(host),
+            browser_id: String::from(browser_id),
+        });
+    }
+
+    /// Adds the `imported` preferences these do not have, ie. the browsers remembered for
+    /// other hosts, the ones set in both are kept. The imported host choices count as older.
+    pub fn merge(&mut self, imported: Prefs) {
+        let mut host_choices: Vec<HostChoice> = imported
+            .host_choices
+            .into_iter()
+            .filter(|choice| self.host_choice(&choice.host).is_none())
+            .collect();
+        host_choices.append(&mut self.host_choices);
+        let overflow = host_choices.len().saturating_sub(MAX_HOST_CHOICES);
+        host_choices.drain(0..overflow);
+        self.host_choices = host_choices;
+
+        for choice in imported.always_use {
+            if self.always_use_browser(&choice.host, false).is_none() {
+                self.always_use.push(choice);
+            }
+        }
+        if self.default_browser.is_none() {
+            self.default_browser = imported.default_browser;
+        }
+        if self.manual_order.is_empty() {
+            self.manual_order = imported.manual_order;
+        }
+        if self.last_choice.is_none() {
+            self.last_choice = imported.last_choice;
+        }
+    }
+
+    /// Forgets both the browser always used and the one last chosen for the `host`,
+    /// returns whether anything was remembered for it.
+    pub fn forget_host(&mut self, host: &str) -> bool {
+        let count = self.always_use.len() + self.host_choices.len();
+        self.always_use.retain(|choice| choice.host != host);
+        self.host_choices.retain(|choice| choice.host != host);
+
+        count != self.always_use.len() + self.host_choices.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+
+    #[test]
+    fn stale_default_browser_is_forgotten_once() {
+        let mut store = MemoryStore::default();
+        let mut prefs = Prefs {
+            default_browser: Some(String::from("uninstalled")),
+            last_choice: Some(String::from("uninstalled")),
+            manual_order: vec![String::from("installed"), String::from("uninstalled")],
+            ..Prefs::default()
+        };
+        prefs.set_host_choice("github.com", "uninstalled");
+        prefs.save(&mut store).unwrap();
+
+        let browser_ids = vec![String::from("installed")];
+        let mut prefs = Prefs::load(&store).unwrap();
+        assert!(prefs.prune_missing_browsers(&browser_ids));
+        prefs.save(&mut store).unwrap();
+
+        assert!(store.get(DEFAULT_BROWSER_KEY).is_none());
+        assert!(store.get(LAST_CHOICE_KEY).is_none());
+        let mut prefs = Prefs::load(&store).unwrap();
+        assert_eq!(prefs.default_browser, None);
+        assert_eq!(prefs.host_choice("github.com"), None);
+        assert_eq!(prefs.manual_order, vec![String::from("installed")]);
+        assert!(!prefs.prune_missing_browsers(&browser_ids));
+    }
+
+    fn host_choice_entry(host: &str, browser_id: &str) -> HostChoice {
+        HostChoice {
+            host: String::from(host),
+            browser_id: String::from(browser_id),
+        }
+    }
+
+    #[test]
+    fn save_then_load_keeps_the_prefs() {
+        let mut store = MemoryStore::default();
+        let mut prefs = Prefs {
+            default_browser: Some(String::from("firefox")),
+            last_choice: Some(String::from("chrome")),
+            manual_order: vec![String::from("chrome"), String::from("firefox")],
+            ..Prefs::default()
+        };
+        prefs.set_host_choice("github.com", "chrome");
+        prefs.set_always_use("docs.rs", "firefox");
+        prefs.save(&mut store).unwrap();
+
+        let loaded = Prefs::load(&store).unwrap();
+        assert_eq!(loaded.default_browser.as_deref(), Some("firefox"));
+        assert_eq!(loaded.last_choice.as_deref(), Some("chrome"));
+        assert_eq!(loaded.manual_order, prefs.manual_order);
+        assert_eq!(loaded.host_choice("github.com"), Some("chrome"));
+        assert_eq!(loaded.always_use_browser("docs.rs", false), Some("firefox"));
+    }
+
+    #[test]
+    fn load_from_an_empty_store_gives_the_defaults() {
+        let prefs = Prefs::load(&MemoryStore::default()).unwrap();
+        assert!(prefs.host_choices.is_empty());
+        assert_eq!(prefs.default_browser, None);
+        assert_eq!(prefs.last_choice, None);
+    }
+
+    #[test]
+    fn set_host_choice_evicts_the_oldest_hosts() {
+        let mut prefs = Prefs::default();
+        for index in 0..MAX_HOST_CHOICES + 2 {
+            prefs.set_host_choice(&format!("host{}.com", index), "firefox");
+        }
+
+        assert_eq!(prefs.host_choices.len(), MAX_HOST_CHOICES);
+        assert_eq!(prefs.host_choice("host0.com"), None);
+        assert_eq!(prefs.host_choice("host1.com"), None);
+        assert_eq!(prefs.host_choice("host2.com"), Some("firefox"));
+    }
+
+    #[test]
+    fn set_host_choice_moves_the_host_to_the_newest() {
+        let mut prefs = Prefs::default();
+        prefs.set_host_choice("github.com", "firefox");
+        prefs.set_host_choice("docs.rs", "firefox");
+        prefs.set_host_choice("github.com", "chrome");
+
+        assert_eq!(prefs.host_choices.len(), 2);
+        assert_eq!(prefs.host_choices[1].host, "github.com");
+        assert_eq!(prefs.host_choice("github.com"), Some("chrome"));
+    }
+
+    #[test]
+    fn merge_keeps_the_existing_prefs() {
+        let mut prefs = Prefs {
+            default_browser: Some(String::from("firefox")),
+            host_choices: vec![host_choice_entry("github.com", "firefox")],
+            ..Prefs::default()
+        };
+        let imported = Prefs {
+            default_browser: Some(String::from("chrome")),
+            last_choice: Some(String::from("chrome")),
+            host_choices: vec![
+                host_choice_entry("github.com", "chrome"),
+                host_choice_entry("docs.rs", "chrome"),
+            ],
+            always_use: vec![host_choice_entry("docs.rs", "chrome")],
+            ..Prefs::default()
+        };
+        prefs.merge(imported);
+
+        assert_eq!(prefs.default_browser.as_deref(), Some("firefox"));
+        assert_eq!(prefs.last_choice.as_deref(), Some("chrome"));
+        assert_eq!(prefs.host_choice("github.com"), Some("firefox"));
+        assert_eq!(prefs.host_choice("docs.rs"), Some("chrome"));
+        // the imported choices count as older
+        assert_eq!(prefs.host_choices[0].host, "docs.rs");
+        assert_eq!(prefs.always_use_browser("docs.rs", false), Some("chrome"));
+    }
+
+    #[test]
+    fn forget_host_removes_both_choices() {
+        let mut prefs = Prefs::default();
+        prefs.set_host_choice("github.com", "firefox");
+        prefs.set_always_use("github.com", "chrome");
+        prefs.set_host_choice("docs.rs", "firefox");
+
+        assert!(prefs.forget_host("github.com"));
+        assert_eq!(prefs.host_choice("github.com"), None);
+        assert_eq!(prefs.always_use_browser("github.com", false), None);
+        assert_eq!(prefs.host_choice("docs.rs"), Some("firefox"));
+        assert!(!prefs.forget_host("github.com"));
+    }
+
+    #[test]
+    fn always_use_browser_matches_subdomains_when_asked() {
+        let mut prefs = Prefs::default();
+        prefs.set_always_use("github.com", "firefox");
+        prefs.set_always_use("gist.github.com", "chrome");
+
+        assert_eq!(
+            prefs.always_use_browser("github.com", false),
+            Some("firefox")
+        );
+        assert_eq!(prefs.always_use_browser("api.github.com", false), None);
+        assert_eq!(
+            prefs.always_use_browser("api.github.com", true),
+            Some("firefox")
+        );
+        // the closest parent host wins
+        assert_eq!(
+            prefs.always_use_browser("raw.gist.github.com", true),
+            Some("chrome")
+        );
+        assert_eq!(prefs.always_use_browser("notgithub.com", true), None);
+    }
+}
@@ -0,0 +1,529 @@
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::error::*;
+use crate::os::shared::BrowserEntry;
+use crate::prefs::Prefs;
+use crate::rules::{self, Rule};
+
+/// A mechanism able to pick the browser for a URL, listed under `routing_order`
+/// in the config file in the order they are consulted.
+///
+/// The steps before `Chooser` open the browser they pick right away,
+/// the steps after it only pre-select their browser in the list.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingStep {
+    /// The `[[rules]]` matching the host of the URL
+    CustomRules,
+
+    /// The browser the user ticked "Always use" for with the host of the URL
+    AlwaysUse,
+
+    /// The browser set for the scheme of the URL under `[scheme_defaults]`
+    SchemeDefaults,
+
+    /// The browser last chosen for the host of the URL, needs `remember_per_host`
+    PerHost,
+
+    /// The browser made the default one with Alt+Enter
+    DefaultBrowser,
+
+    /// The browser chosen the last time in the list
+    LastChoice,
+
+    /// Shows the list for the user to choose
+    Chooser,
+}
+
+impl RoutingStep {
+    /// The name of the step in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            RoutingStep::CustomRules => "custom_rules",
+            RoutingStep::AlwaysUse => "always_use",
+            RoutingStep::SchemeDefaults => "scheme_defaults",
+            RoutingStep::PerHost => "per_host",
+            RoutingStep::DefaultBrowser => "default_browser",
+            RoutingStep::LastChoice => "last_choice",
+            RoutingStep::Chooser => "chooser",
+        }
+    }
+}
+
+/// Rules, scheme defaults and the hosts to always use a browser for open their
+/// browser right away while the remembered choices only pre-select theirs.
+pub const DEFAULT_ROUTING_ORDER: [RoutingStep; 7] = [
+    RoutingStep::CustomRules,
+    RoutingStep::SchemeDefaults,
+    RoutingStep::AlwaysUse,
+    RoutingStep::Chooser,
+    RoutingStep::PerHost,
+    RoutingStep::DefaultBrowser,
+    RoutingStep::LastChoice,
+];
+
+/// Checks the `routing_order` read from the config, every step can be listed at most once
+/// and `chooser` is required. The steps left out are not consulted.
+pub fn validate_order(order: &[RoutingStep]) -> BSResult<()> {
+    for (index, step) in order.iter().enumerate() {
+        if order[..index].contains(step) {
+            bail!("routing_order lists \"{}\" more than once.", step.name());
+        }
+    }
+
+    if !order.contains(&RoutingStep::Chooser) {
+        bail!("routing_order has to list \"chooser\".");
+    }
+
+    Ok(())
+}
+
+/// Whether the `step` opens the browser it picks right away, being listed before `chooser`
+pub fn opens_directly(order: &[RoutingStep], step: RoutingStep) -> bool {
+    let position = |wanted: RoutingStep| order.iter().position(|step| *step == wanted);
+    match (position(step), position(RoutingStep::Chooser)) {
+        (Some(step_position), Some(chooser_position)) => step_position < chooser_position,
+        _ => false,
+    }
+}
+
+/// The outcome of `decide` and what led to it
+#[derive(Debug)]
+pub enum Decision<'a, B> {
+    /// A rule from the config matched the URL
+    Rule(Rule, &'a B),
+
+    /// The browser set for the scheme of the URL, which is given
+    SchemeDefault(String, &'a B),
+
+    /// The browser the user ticked "Always use" for with the host of the URL, which is given
+    AlwaysUse(String, &'a B),
+
+    /// The browser last chosen for the host of the URL, which is given
+    HostChoice(String, &'a B),
+
+    /// The browser made the default one with Alt+Enter
+    DefaultBrowser(&'a B),
+
+    /// The browser chosen the last time in the list
+    LastChoice(&'a B),
+
+    /// The only browser is opened because of `auto_open_single`
+    SingleBrowser(&'a B),
+
+    /// The user has to choose, with the browser to pre-select if any
+    Chooser(Option<&'a B>),
+}
+
+impl<'a, B> Decision<'a, B> {
+    /// The browser opening the URL, `None` when the user has to choose
+    pub fn browser(&self) -> Option<&'a B> {
+        match self {
+            Decision::Rule(_, browser)
+            | Decision::SchemeDefault(_, browser)
+            | Decision::AlwaysUse(_, browser)
+            | Decision::HostChoice(_, browser)
+            | Decision::DefaultBrowser(browser)
+            | Decision::LastChoice(browser)
+            | Decision::SingleBrowser(browser) => Some(browser),
+            Decision::Chooser(_) => None,
+        }
+    }
+}
+
+/// Decides which of the `browsers` opens the `url` without asking the user.
+/// Returns `None` when the user has to choose.
+pub fn resolve_browser<'a, B: BrowserEntry>(
+    browsers: &'a [B],
+    config: &Config,
+    prefs: &Prefs,
+    url: &str,
+) -> Option<&'a B> {
+    decide(browsers, config, prefs, url).browser()
+}
+
+/// Like `resolve_browser` but also tells why the browser was chosen.
+///
+/// The steps of `routing_order` are consulted in order until one picks a browser.
+/// With a single browser `auto_open_single` opens it once the `chooser` step is reached.
+pub fn decide<'a, B: BrowserEntry>(
+    browsers: &'a [B],
+    config: &Config,
+    prefs: &Prefs,
+    url: &str,
+) -> Decision<'a, B> {
+    let mut steps = config.routing_order.iter();
+    for step in steps.by_ref() {
+        if *step == RoutingStep::Chooser {
+            break;
+        }
+
+        if let Some(decision) = consult(*step, browsers, config, prefs, url) {
+            return decision;
+        }
+    }
+
+    // with a single browser there is nothing to choose from
+    if config.auto_open_single && browsers.len() == 1 {
+        return Decision::SingleBrowser(&browsers[0]);
+    }
+
+    let preselected = steps.find_map(|step| {
+        consult(*step, browsers, config, prefs, url).and_then(|decision| decision.browser())
+    });
+    Decision::Chooser(preselected)
+}
+
+fn consult<'a, B: BrowserEntry>(
+    step: RoutingStep,
+    browsers: &'a [B],
+    config: &Config,
+    prefs: &Prefs,
+    url: &str,
+) -> Option<Decision<'a, B>> {
+    let find_browser = |reference: &str| {
+        browsers
+            .iter()
+            .find(|browser| browser.is_referenced_by(reference))
+    };
+    let find_browser_by_id =
+        |browser_id: &str| browsers.iter().find(|browser| browser.id() == browser_id);
+
+    match step {
+        RoutingStep::CustomRules => find_rule_browser(browsers, &config.rules, url)
+            .map(|(rule, browser)| Decision::Rule(rule, browser)),
+        RoutingStep::SchemeDefaults => {
+            let scheme = url::Url::parse(url).ok()?.scheme().to_owned();
+            let (_, reference) = config
+                .scheme_defaults
+                .iter()
+                .find(|(default_scheme, _)| default_scheme.to_lowercase() == scheme)?;
+            // the ones pointing to a missing browser are reported by `validate_references`
+            find_browser(reference).map(|browser| Decision::SchemeDefault(scheme, browser))
+        }
+        RoutingStep::AlwaysUse => {
+            let host = crate::url_utils::host_of(url)?;
+            let browser_id = prefs.always_use_browser(&host, config.always_use_subdomains)?;
+            let browser = find_browser_by_id(browser_id)?;
+            Some(Decision::AlwaysUse(host, browser))
+        }
+        RoutingStep::PerHost if config.remember_per_host => {
+            let host = crate::url_utils::host_of(url)?;
+            let browser = find_browser_by_id(prefs.host_choice(&host)?)?;
+            Some(Decision::HostChoice(host, browser))
+        }
+        RoutingStep::PerHost => None,
+        RoutingStep::DefaultBrowser => prefs
+            .default_browser
+            .as_deref()
+            .and_then(find_browser_by_id)
+            .map(Decision::DefaultBrowser),
+        RoutingStep::LastChoice => prefs
+            .last_choice
+            .as_deref()
+            .and_then(find_browser_by_id)
+            .map(Decision::LastChoice),
+        RoutingStep::Chooser => None,
+    }
+}
+
+/// Rules pointing to a browser that is not installed anymore are ignored
+/// as if they did not exist, letting a less specific rule match instead.
+fn find_rule_browser<'a, B: BrowserEntry>(
+    browsers: &'a [B],
+    rules: &[Rule],
+    url: &str,
+) -> Option<(Rule, &'a B)> {
+    let installed_rules: Vec<Rule> = rules
+        .iter()
+        .filter(|rule| is_installed(browsers, &rule.browser))
+        .cloned()
+        .collect();
+
+    rules::find_matching_rule(&installed_rules, url).and_then(|rule| {
+        browsers
+            .iter()
+            .find(|browser| browser.is_referenced_by(&rule.browser))
+            .map(|browser| (rule.clone(), browser))
+    })
+}
+
+/// Looks for the rules and the scheme defaults of the `config` pointing to a browser which is
+/// not among the `browsers`, ie. after it was uninstalled, returning a human readable warning
+/// for each. `decide` ignores them, these are meant to be reported once at startup.
+pub fn validate_references<B: BrowserEntry>(browsers: &[B], config: &Config) -> Vec<String> {
+    let rule_warnings = config
+        .rules
+        .iter()
+        .filter(|rule| !is_installed(browsers, &rule.browser))
+        .map(|rule| {
+            format!(
+                "The rule \"{}\" under [[rules]] points to {} which was not found, the rule is ignored.",
+                rule.pattern, rule.browser
+            )
+        });
+    let scheme_warnings = config
+        .scheme_defaults
+        .iter()
+        .filter(|(_, reference)| !is_installed(browsers, reference))
+        .map(|(scheme, reference)| {
+            format!(
+                "{} under [scheme_defaults] points to {} which was not found, it is ignored.",
+                scheme, reference
+            )
+        });
+
+    rule_warnings.chain(scheme_warnings).collect()
+}
+
+fn is_installed<B: BrowserEntry>(browsers: &[B], reference: &str) -> bool {
+    browsers
+        .iter()
+        .any(|browser| browser.is_referenced_by(reference))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::os::shared::TestBrowser;
+
+    fn browsers() -> Vec<TestBrowser> {
+        vec![
+            TestBrowser::new("Firefox", "firefox.exe"),
+            TestBrowser::new("Google Chrome", "chrome.exe"),
+        ]
+    }
+
+    fn rule(pattern: &str, browser: &str) -> Rule {
+        Rule {
+            pattern: String::from(pattern),
+            browser: String::from(browser),
+        }
+    }
+
+    fn decided_name(decision: &Decision<TestBrowser>) -> Option<String> {
+        decision.browser().map(|browser| browser.display_name())
+    }
+
+    #[test]
+    fn stale_default_browser_shows_the_chooser() {
+        let browsers = browsers();
+        let prefs = Prefs {
+            default_browser: Some(String::from("uninstalled")),
+            ..Prefs::default()
+        };
+
+        let decision = decide(&browsers, &Config::default(), &prefs, "https://github.com");
+        assert!(matches!(decision, Decision::Chooser(None)));
+    }
+
+    #[test]
+    fn stale_rule_lets_a_less_specific_rule_match() {
+        let browsers = browsers();
+        let config = Config {
+            rules: vec![
+                rule("gist.github.com", "uninstalled.exe"),
+                rule("*.github.com", "firefox.exe"),
+            ],
+            ..Config::default()
+        };
+
+        let decision = decide(
+            &browsers,
+            &config,
+            &Prefs::default(),
+            "https://gist.github.com",
+        );
+        assert!(matches!(decision, Decision::Rule(..)));
+        assert_eq!(decided_name(&decision).as_deref(), Some("Firefox"));
+    }
+
+    #[test]
+    fn validate_references_warns_about_the_missing_browsers() {
+        let mut config = Config {
+            rules: vec![
+                rule("github.com", "firefox.exe"),
+                rule("gitlab.com", "uninstalled.exe"),
+            ],
+            ..Config::default()
+        };
+        config
+            .scheme_defaults
+            .insert(String::from("mailto"), String::from("thunderbird.exe"));
+
+        let warnings = validate_references(&browsers(), &config);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("gitlab.com"));
+        assert!(warnings[1].contains("mailto"));
+    }
+
+    #[test]
+    fn default_order_opens_the_rules_right_away() {
+        let browsers = browsers();
+        let config = Config {
+            rules: vec![rule("github.com", "chrome.exe")],
+            ..Config::default()
+        };
+        let prefs = Prefs {
+            default_browser: Some(browsers[0].id()),
+            ..Prefs::default()
+        };
+
+        let decision = decide(&browsers, &config, &prefs, "https://github.com");
+        assert!(matches!(decision, Decision::Rule(..)));
+        assert_eq!(decided_name(&decision).as_deref(), Some("Google Chrome"));
+    }
+
+    #[test]
+    fn default_order_opens_the_scheme_defaults_right_away() {
+        let browsers = browsers();
+        let mut config = Config::default();
+        config
+            .scheme_defaults
+            .insert(String::from("mailto"), String::from("firefox.exe"));
+
+        let decision = decide(&browsers, &config, &Prefs::default(), "mailto:a@b.c");
+        assert!(matches!(decision, Decision::SchemeDefault(..)));
+        assert_eq!(decided_name(&decision).as_deref(), Some("Firefox"));
+    }
+
+    #[test]
+    fn default_order_opens_the_always_used_browser_right_away() {
+        let browsers = browsers();
+        let mut prefs = Prefs::default();
+        prefs.set_always_use("github.com", &browsers[1].id());
+
+        let decision = decide(
+            &browsers,
+            &Config::default(),
+            &prefs,
+            "https://github.com/x",
+        );
+        assert!(matches!(decision, Decision::AlwaysUse(..)));
+        assert_eq!(decided_name(&decision).as_deref(), Some("Google Chrome"));
+    }
+
+    #[test]
+    fn default_order_only_preselects_the_remembered_choices() {
+        let browsers = browsers();
+        let config = Config {
+            remember_per_host: true,
+            ..Config::default()
+        };
+        let mut prefs = Prefs {
+            default_browser: Some(browsers[0].id()),
+            last_choice: Some(browsers[0].id()),
+            ..Prefs::default()
+        };
+        prefs.set_host_choice("github.com", &browsers[1].id());
+
+        // the host choice comes before the default browser
+        match decide(&browsers, &config, &prefs, "https://github.com") {
+            Decision::Chooser(Some(browser)) => assert_eq!(browser.display_name(), "Google Chrome"),
+            decision => panic!("unexpected decision {:?}", decision),
+        }
+        match decide(&browsers, &config, &prefs, "https://gitlab.com") {
+            Decision::Chooser(Some(browser)) => assert_eq!(browser.display_name(), "Firefox"),
+            decision => panic!("unexpected decision {:?}", decision),
+        }
+    }
+
+    #[test]
+    fn steps_before_the_chooser_open_right_away() {
+        let browsers = browsers();
+        let config = Config {
+            rules: vec![rule("github.com", "chrome.exe")],
+            routing_order: vec![
+                RoutingStep::LastChoice,
+                RoutingStep::CustomRules,
+                RoutingStep::Chooser,
+            ],
+            ..Config::default()
+        };
+        let prefs = Prefs {
+            last_choice: Some(browsers[0].id()),
+            ..Prefs::default()
+        };
+
+        let decision = decide(&browsers, &config, &prefs, "https://github.com");
+        assert!(matches!(decision, Decision::LastChoice(..)));
+        assert_eq!(decided_name(&decision).as_deref(), Some("Firefox"));
+    }
+
+    #[test]
+    fn steps_left_out_are_not_consulted() {
+        let browsers = browsers();
+        let config = Config {
+            rules: vec![rule("github.com", "chrome.exe")],
+            routing_order: vec![RoutingStep::Chooser, RoutingStep::PerHost],
+            ..Config::default()
+        };
+        let prefs = Prefs {
+            default_browser: Some(browsers[0].id()),
+            ..Prefs::default()
+        };
+
+        let decision = decide(&browsers, &config, &prefs, "https://github.com");
+        assert!(matches!(decision, Decision::Chooser(None)));
+    }
+
+    #[test]
+    fn per_host_needs_remember_per_host() {
+        let browsers = browsers();
+        let config = Config {
+            routing_order: vec![RoutingStep::PerHost, RoutingStep::Chooser],
+            ..Config::default()
+        };
+        let mut prefs = Prefs::default();
+        prefs.set_host_choice("github.com", &browsers[1].id());
+
+        let decision = decide(&browsers, &config, &prefs, "https://github.com");
+        assert!(matches!(decision, Decision::Chooser(None)));
+
+        let config = Config {
+            remember_per_host: true,
+            ..config
+        };
+        let decision = decide(&browsers, &config, &prefs, "https://github.com");
+        assert!(matches!(decision, Decision::HostChoice(..)));
+    }
+
+    #[test]
+    fn single_browser_is_opened_once_the_chooser_is_reached() {
+        let browsers = vec![TestBrowser::new("Firefox", "firefox.exe")];
+
+        let decision = decide(
+            &browsers,
+            &Config::default(),
+            &Prefs::default(),
+            "https://a.b",
+        );
+        assert!(matches!(decision, Decision::SingleBrowser(..)));
+
+        let config = Config {
+            auto_open_single: false,
+            ..Config::default()
+        };
+        let decision = decide(&browsers, &config, &Prefs::default(), "https://a.b");
+        assert!(matches!(decision, Decision::Chooser(None)));
+    }
+
+    #[test]
+    fn validate_order_accepts_the_default_order() {
+        assert!(validate_order(&DEFAULT_ROUTING_ORDER).is_ok());
+        assert!(validate_order(&[RoutingStep::Chooser]).is_ok());
+    }
+
+    #[test]
+    fn validate_order_rejects_invalid_orders() {
+        assert!(validate_order(&[]).is_err());
+        assert!(validate_order(&[RoutingStep::CustomRules, RoutingStep::LastChoice]).is_err());
+        assert!(validate_order(&[
+            RoutingStep::CustomRules,
+            RoutingStep::Chooser,
+            RoutingStep::CustomRules,
+        ])
+        .is_err());
+    }
+}
@@ -0,0 +1,282 @@
+use serde::Deserialize;
+
+/// A URL routing rule as defined in the config file under `[[rules]]`.
+///
+/// When the URL being opened matches `pattern` the browser
+/// referenced by `browser` is opened right away, without showing the UI.
+///
+/// ### Resolution order
+/// When several rules match the same URL:
+///  - the most specific pattern wins, specificity being the number of
+///    non wildcard characters in the pattern, path prefix included
+///    (`gist.github.com` beats `*.github.com`)
+///  - between equally specific patterns the one listed first wins
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    /// Glob pattern matched against the host of the URL where `*` matches
+    /// any sequence of characters, ie. `*.github.com`. Case insensitive.
+    /// It can be followed by a path prefix the path of the URL has to start with,
+    /// ie. `github.com/rust-lang`, which is case sensitive like URL paths are.
+    pub pattern: String,
+
+    /// The browser to open, either the executable file name (`firefox.exe`)
+    /// or the full path to the executable.
+    pub browser: String,
+}
+
+impl Rule {
+    fn specificity(&self) -> usize {
+        self.pattern.chars().filter(|c| *c != '*').count()
+    }
+
+    /// The host pattern and the path prefix, starting with `/`, when there is one
+    fn split_pattern(&self) -> (&str, Option<&str>) {
+        match self.pattern.find('/') {
+            Some(slash) => (&self.pattern[..slash], Some(&self.pattern[slash..])),
+            None => (&self.pattern, None),
+        }
+    }
+
+    fn matches(&self, host: &str, path: &str) -> bool {
+        let (host_pattern, path_prefix) = self.split_pattern();
+
+        glob_matches(&host_pattern.to_lowercase(), &host.to_lowercase())
+            && path_prefix.is_none_or(|prefix| path.starts_with(prefix))
+    }
+
+    /// Whether both rules can match the same URLs
+    fn overlaps(&self, other: &Rule) -> bool {
+        let (host_pattern, path_prefix) = self.split_pattern();
+        let (other_host_pattern, other_path_prefix) = other.split_pattern();
+        let paths_overlap = match (path_prefix, other_path_prefix) {
+            (Some(prefix), Some(other_prefix)) => {
+                prefix.starts_with(other_prefix) || other_prefix.starts_with(prefix)
+            }
+            _ => true,
+        };
+
+        paths_overlap && patterns_overlap(host_pattern, other_host_pattern)
+    }
+}
+
+/// Returns the rule deciding which browser opens the given `url`
+/// as per the resolution order documented on `Rule`.
+pub fn find_matching_rule<'a>(rules: &'a [Rule], url: &str) -> Option<&'a Rule> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+
+    let mut best_match: Option<&Rule> = None;
+    for rule in rules
+        .iter()
+        .filter(|rule| rule.matches(host, parsed.path()))
+    {
+        match best_match {
+            Some(best) if best.specificity() >= rule.specificity() => (),
+            _ => best_match = Some(rule),
+        }
+    }
+
+    best_match
+}
+
+/// Looks for rules that can match the same URLs while pointing to
+/// different browsers, returning a human readable warning for each pair found.
+pub fn validate(rules: &[Rule]) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for (index, rule) in rules.iter().enumerate() {
+        for other in rules.iter().skip(index + 1) {
+            if !rule.overlaps(other) {
+                continue;
+            }
+
+            let same_browser = rule.browser.to_lowercase() == other.browser.to_lowercase();
+            if rule.pattern.to_lowercase() == other.pattern.to_lowercase() {
+                warnings.push(match same_browser {
+                    true => format!(
+                        "Rule \"{}\" -> {} is defined more than once.",
+                        rule.pattern, rule.browser
+                    ),
+                    false => format!(
+                        "Rules \"{}\" -> {} and \"{}\" -> {} have the same pattern, the first one is used.",
+                        rule.pattern, rule.browser, other.pattern, other.browser
+                    ),
+                });
+            } else if !same_browser {
+                let winner = match other.specificity() > rule.specificity() {
+                    true => other,
+                    false => rule,
+                };
+                warnings.push(format!(
+                    "Rules \"{}\" -> {} and \"{}\" -> {} can match the same URLs, \"{}\" takes precedence.",
+                    rule.pattern, rule.browser, other.pattern, other.browser, winner.pattern
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Two patterns overlap when some host is matched by both of them,
+/// ie. `a*` and `*b` both match `ab`.
+fn patterns_overlap(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    // reachable[i][j] tells whether some text is matched by both `a[..i]` and `b[..j]`,
+    // each character of it being either the same literal in both patterns or a literal
+    // of one of them taken by a `*` of the other one. A `*` can also match nothing.
+    let mut reachable = vec![vec![false; b.len() + 1]; a.len() + 1];
+    reachable[0][0] = true;
+    for i in 0..=a.len() {
+        for j in 0..=b.len() {
+            if !reachable[i][j] {
+                continue;
+            }
+
+            let (a_char, b_char) = (a.get(i), b.get(j));
+            if a_char == Some(&'*') {
+                reachable[i + 1][j] = true;
+            }
+            if b_char == Some(&'*') {
+                reachable[i][j + 1] = true;
+            }
+            match (a_char, b_char) {
+                (Some('*'), Some(_)) => reachable[i][j + 1] = true,
+                (Some(_), Some('*')) => reachable[i + 1][j] = true,
+                (Some(a_char), Some(b_char)) if a_char == b_char => reachable[i + 1][j + 1] = true,
+                _ => (),
+            }
+        }
+    }
+
+    reachable[a.len()][b.len()]
+}
+
+/// Matches `text` against `pattern` where `*` stands for any sequence of characters
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // classic wildcard matching keeping track of the last `*`
+    // to backtrack to when the characters after it stop matching
+    let (mut p, mut t) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            last_star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = last_star {
+            p = star_p + 1;
+            t = star_t + 1;
+            last_star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, browser: &str) -> Rule {
+        Rule {
+            pattern: String::from(pattern),
+            browser: String::from(browser),
+        }
+    }
+
+    fn matching_browser<'a>(rules: &'a [Rule], url: &str) -> Option<&'a str> {
+        find_matching_rule(rules, url).map(|rule| rule.browser.as_str())
+    }
+
+    #[test]
+    fn glob_matches_any_sequence() {
+        assert!(glob_matches("*.github.com", "gist.github.com"));
+        assert!(glob_matches("*.github.com", "a.b.github.com"));
+        assert!(glob_matches("git*.com", "github.com"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("*.github.com", "github.com"));
+        assert!(!glob_matches("github.com", "github.co"));
+    }
+
+    #[test]
+    fn most_specific_rule_wins() {
+        let rules = vec![
+            rule("*.github.com", "firefox.exe"),
+            rule("gist.github.com", "chrome.exe"),
+        ];
+
+        assert_eq!(
+            matching_browser(&rules, "https://gist.github.com/x"),
+            Some("chrome.exe")
+        );
+        assert_eq!(
+            matching_browser(&rules, "https://api.github.com"),
+            Some("firefox.exe")
+        );
+        assert_eq!(matching_browser(&rules, "https://gitlab.com"), None);
+    }
+
+    #[test]
+    fn first_rule_wins_between_equally_specific_ones() {
+        let rules = vec![
+            rule("*.example.com", "firefox.exe"),
+            rule("www.example.*", "chrome.exe"),
+        ];
+
+        assert_eq!(
+            matching_browser(&rules, "https://www.example.com"),
+            Some("firefox.exe")
+        );
+    }
+
+    #[test]
+    fn path_prefix_makes_a_rule_more_specific() {
+        let rules = vec![
+            rule("github.com", "firefox.exe"),
+            rule("github.com/rust-lang", "chrome.exe"),
+        ];
+
+        assert_eq!(
+            matching_browser(&rules, "https://GitHub.com/rust-lang/rust"),
+            Some("chrome.exe")
+        );
+        // the paths are case sensitive
+        assert_eq!(
+            matching_browser(&rules, "https://github.com/Rust-Lang"),
+            Some("firefox.exe")
+        );
+    }
+
+    #[test]
+    fn patterns_overlap_when_a_host_matches_both() {
+        assert!(patterns_overlap("a*", "*b"));
+        assert!(patterns_overlap("*.github.com", "gist.*"));
+        assert!(patterns_overlap("*.github.com", "GIST.github.com"));
+        assert!(patterns_overlap("*", "github.com"));
+        assert!(!patterns_overlap("*.github.com", "*.gitlab.com"));
+        assert!(!patterns_overlap("a*b", "b*a"));
+        assert!(!patterns_overlap("github.com", "gitlab.com"));
+    }
+
+    #[test]
+    fn validate_warns_about_overlapping_rules_only() {
+        let rules = vec![
+            rule("a*", "firefox.exe"),
+            rule("*b", "chrome.exe"),
+            rule("github.com", "chrome.exe"),
+        ];
+
+        let warnings = validate(&rules);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("\"a*\""));
+    }
+}
@@ -0,0 +1,518 @@
+/*
+  The browser list on its own, for the programs embedding the library: it is
+  shown for the given URLs and the chosen browser is handed back instead of
+  being launched. The `browser-selector` program runs a longer event loop of
+  its own in main.rs, for its daemon mode, the links handed over by later
+  launches and the rest. Both hand the window events to `BrowserList` which
+  handles the keyboard, the filter and the resizing the same way.
+*/
+#[cfg(target_os = "windows")]
+use winit::platform::windows::WindowBuilderExtWindows;
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    platform::run_return::EventLoopExtRunReturn,
+    window::{Window, WindowBuilder},
+};
+
+use crate::config::Config;
+use crate::error::BSResult;
+use crate::os::os_browsers;
+use crate::os::shared::BrowserEntry;
+use crate::prefs::Prefs;
+use crate::store::FileStore;
+use crate::ui::{self, BrowserSelectorUI, UserInterface};
+use crate::{fuzzy, i18n, os, routing, sorting, url_utils};
+
+/// Events posted to the event loop of `run_selector` from the UI event handlers
+#[derive(Debug, Clone)]
+enum SelectorEvent {
+    /// The user chose the list item with the given uuid
+    BrowserSelected(String),
+}
+
+/// Shows the browser list for the `urls`, the first one in the header, and returns
+/// the browser the user chose without launching it, `None` when the window was closed.
+///
+/// The browsers are the `available_browsers` for the `config`, in the same order as in the
+/// `browser-selector` program, see `sorting::order_list`. The browser the routing would pick
+/// for the first URL is selected, otherwise the OS default one. Initializes the XAML island
+/// thus it has to run on the main thread, before any other `winit` event loop is created.
+pub fn run_selector(urls: Vec<String>, config: Config) -> BSResult<Option<os_browsers::Browser>> {
+    let config_dir = crate::config_directory();
+    let prefs = FileStore::open(&config_dir)
+        .and_then(|store| Prefs::load(&store))
+        .unwrap_or_else(|e| {
+            log::error!("Error loading preferences. Reason: {}", e);
+            Prefs::default()
+        });
+    let launch_counts = match config.sort {
+        sorting::SortOrder::Frequency => crate::stats::load_counts(&config_dir).unwrap_or_default(),
+        _ => Default::default(),
+    };
+    let mut browsers = crate::available_browsers(&config)?;
+    sorting::order_list(&mut browsers, &config, &prefs.manual_order, &launch_counts);
+
+    let url = urls.first().cloned().unwrap_or_default();
+    let preselected_id = match routing::decide(&browsers, &config, &prefs, &url) {
+        routing::Decision::Chooser(preselected) => preselected,
+        decision => decision.browser(),
+    }
+    .or_else(|| browsers.iter().find(|browser| browser.is_default()))
+    .map(|browser| browser.id());
+
+    let mut ui = BrowserSelectorUI::new()?;
+    let mut event_loop = EventLoop::<SelectorEvent>::with_user_event();
+    let window_builder = WindowBuilder::new()
+        .with_title(env!("CARGO_PKG_NAME"))
+        .with_always_on_top(true);
+    #[cfg(target_os = "windows")]
+    let window_builder = window_builder.with_drag_and_drop(false);
+    let window = window_builder.build(&event_loop)?;
+    ui.create(&window)?;
+    // the icons are read at the size of the monitor the window opens on
+    let list_items: Vec<ui::ListItem<os_browsers::Browser>> = browsers
+        .iter()
+        .map(|browser| list_item_from_browser(browser, &config, window.scale_factor()))
+        .collect();
+    if let Err(e) = ui.set_theme(&window, &config.theme) {
+        log::error!("Could not apply the theme. Reason: {}", e);
+    }
+    ui.show_shortcut_numbers(config.shortcut_numbers)?;
+    ui.set_item_layout(
+        config.effective_font_size(),
+        config.effective_item_padding(),
+    )?;
+    ui.set_list(&list_items)?;
+    if let Some(index) = browsers
+        .iter()
+        .position(|browser| Some(browser.id()) == preselected_id)
+    {
+        ui.select_list_item_by_index(index as u32)?;
+    }
+
+    match config.mask_url {
+        true => ui.set_masked_url(&url, url_utils::mask_url(&url).as_str())?,
+        false => ui.set_url(&url)?,
+    }
+    if urls.len() > 1 {
+        let count = urls.len();
+        ui.set_action_text(&i18n::t_with("open_links_prompt", &[("count", &count)]))?;
+    }
+    let event_proxy = event_loop.create_proxy();
+    ui.on_list_item_selected(move |uuid| {
+        if let Err(e) = event_proxy.send_event(SelectorEvent::BrowserSelected(String::from(uuid))) {
+            log::error!("Could not handle the selection. Reason: {}", e);
+        }
+    })?;
+    if config.details_panel {
+        ui.show_details_panel()?;
+    }
+
+    if let Err(e) = ui.fit_to_content(&window) {
+        log::error!(
+            "Could not fit the window to the browser list. Reason: {}",
+            e
+        );
+    }
+    center_on_cursor_monitor(&window);
+    os::util::bring_to_foreground(&window);
+
+    let mut list = BrowserList::new(list_items);
+    let mut chosen_uuid: Option<String> = None;
+    event_loop.run_return(|event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            Event::UserEvent(SelectorEvent::BrowserSelected(uuid)) => {
+                chosen_uuid = Some(uuid);
+                *control_flow = ControlFlow::Exit;
+            }
+            Event::WindowEvent { event, .. } => {
+                match list.handle_window_event(&mut ui, &window, &event) {
+                    Some(ListEvent::Chosen(uuid)) => {
+                        chosen_uuid = Some(uuid);
+                        *control_flow = ControlFlow::Exit;
+                    }
+                    Some(ListEvent::Dismissed) => *control_flow = ControlFlow::Exit,
+                    Some(ListEvent::Navigated) | None => (),
+                }
+            }
+            Event::LoopDestroyed => release_ui(&mut ui),
+            _ => (),
+        }
+    });
+
+    Ok(chosen_uuid.and_then(|uuid| browsers.into_iter().find(|browser| browser.id() == uuid)))
+}
+
+/// What the user did in the browser list, see `BrowserList::handle_window_event`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListEvent {
+    /// The list item with the given uuid was chosen with Enter or its number key
+    Chosen(String),
+
+    /// Escape was pressed
+    Dismissed,
+
+    /// The selection was moved with the arrow keys
+    Navigated,
+}
+
+/// The items of the browser list and the filter typed over it, the keyboard
+/// handling shared by `run_selector` and the event loop of the program
+pub struct BrowserList<T: Clone> {
+    /// All the items in the order they are shown without a filter
+    pub items: Vec<ui::ListItem<T>>,
+
+    /// The items matching the `filter` as they are shown, the number keys choose among these
+    pub visible_items: Vec<ui::ListItem<T>>,
+
+    /// Typing a part of a browser name hides the others
+    pub filter: String,
+}
+
+impl<T: Clone> BrowserList<T> {
+    pub fn new(items: Vec<ui::ListItem<T>>) -> BrowserList<T> {
+        BrowserList {
+            visible_items: items.clone(),
+            items,
+            filter: String::new(),
+        }
+    }
+
+    /// Filters the `items` again after they or the `filter` changed and shows the result
+    pub fn refilter(&mut self, ui: &mut BrowserSelectorUI<T>) -> BSResult<()> {
+        self.visible_items = filter_list_items(&self.items, &self.filter);
+        ui.set_list(&self.visible_items)
+    }
+
+    /// Reacts to the window `event` the same way in every event loop showing the list:
+    /// follows the window size, moves the selection, filters by the typed characters and
+    /// chooses with Enter or the number keys. Returns what the caller has to act upon,
+    /// `None` as well for the events the list does not handle.
+    pub fn handle_window_event(
+        &mut self,
+        ui: &mut BrowserSelectorUI<T>,
+        window: &Window,
+        event: &WindowEvent,
+    ) -> Option<ListEvent> {
+        match event {
+            WindowEvent::Resized(size) => {
+                if let Err(e) = ui.update_layout_size(window, size) {
+                    log::error!("Could not resize the window content. Reason: {}", e);
+                }
+                None
+            }
+            // moving to a monitor with a different DPI resizes the window
+            // but the XAML island is a child window which has to follow on its own,
+            // the XAML content picks up the new scale from its host window
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                if let Err(e) = ui.update_layout_size(window, new_inner_size) {
+                    log::error!("Could not resize the window content. Reason: {}", e);
+                }
+                None
+            }
+            WindowEvent::KeyboardInput { input, .. }
+                if input.state == winit::event::ElementState::Pressed =>
+            {
+                self.handle_key(ui, input.virtual_keycode?)
+            }
+            WindowEvent::ReceivedCharacter(character) => {
+                self.handle_character(ui, window, *character);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn handle_key(
+        &mut self,
+        ui: &mut BrowserSelectorUI<T>,
+        key: winit::event::VirtualKeyCode,
+    ) -> Option<ListEvent> {
+        use winit::event::VirtualKeyCode;
+
+        let step = match key {
+            VirtualKeyCode::Up => -1,
+            VirtualKeyCode::Down => 1,
+            VirtualKeyCode::PageUp | VirtualKeyCode::PageDown => {
+                let pages = match key {
+                    VirtualKeyCode::PageUp => -1.,
+                    _ => 1.,
+                };
+                if let Err(e) = ui.scroll_list_by_pages(pages) {
+                    log::error!("Could not scroll the browser list. Reason: {}", e);
+                }
+                return None;
+            }
+            VirtualKeyCode::Escape => return Some(ListEvent::Dismissed),
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                return match ui.get_selected_list_item() {
+                    Ok(item) => item.map(|item| ListEvent::Chosen(item.uuid)),
+                    Err(e) => {
+                        log::error!("Could not read the selection. Reason: {}", e);
+                        None
+                    }
+                };
+            }
+            // the keys past the last item are ignored
+            key => {
+                return shortcut_number(key)
+                    .and_then(|number| self.visible_items.get(number - 1))
+                    .map(|item| ListEvent::Chosen(item.uuid.clone()));
+            }
+        };
+
+        let selected_index = ui.get_selected_list_item_index().unwrap_or(-1);
+        if let Some(index) = next_list_index(selected_index, step, self.visible_items.len()) {
+            if let Err(e) = ui.select_list_item_by_index(index) {
+                log::error!("Could not move the selection. Reason: {}", e);
+            }
+        }
+        Some(ListEvent::Navigated)
+    }
+
+    fn handle_character(
+        &mut self,
+        ui: &mut BrowserSelectorUI<T>,
+        window: &Window,
+        character: char,
+    ) {
+        // the digits are left to the number key shortcuts
+        match character {
+            '\u{8}' if !self.filter.is_empty() => {
+                self.filter.pop();
+            }
+            character if character.is_control() || character.is_ascii_digit() => return,
+            character => self.filter.push(character),
+        }
+
+        if let Err(e) = self.refilter(ui) {
+            log::error!("Could not filter the browsers. Reason: {}", e);
+        }
+        if let Err(e) = ui.fit_to_content(window) {
+            log::error!(
+                "Could not fit the window to the browser list. Reason: {}",
+                e
+            );
+        }
+        if let Err(e) = ui.set_action_text(&self.filter) {
+            log::error!("Could not show the filter. Reason: {}", e);
+        }
+    }
+}
+
+/// Releases the XAML resources, which has to happen before the window is destroyed
+/// otherwise closing it can cause memory violations
+pub fn release_ui<T: Clone>(ui: &mut BrowserSelectorUI<T>) {
+    if let Err(e) = ui.close() {
+        log::error!("Error releasing the UI resources. Reason: {}", e);
+    }
+}
+
+/// The list item showing the `browser`, marked as a favorite and put under the header
+/// of its vendor as set in the `config`. The icon is sized for the `scale_factor`.
+pub fn list_item_from_browser<B: BrowserEntry + Clone>(
+    browser: &B,
+    config: &Config,
+    scale_factor: f64,
+) -> ui::ListItem<B> {
+    let image = BrowserSelectorUI::<B>::load_image(browser.icon_source().as_str(), scale_factor)
+        .unwrap_or_default();
+
+    ui::ListItem {
+        title: browser.display_name(),
+        subtitle: browser
+            .subtitle_parts()
+            .into_iter()
+            .filter(|itm| !itm.is_empty())
+            .collect::<Vec<String>>()
+            .join(" | "),
+        details: browser
+            .details()
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .collect(),
+        image,
+        uuid: browser.id(),
+        is_favorite: sorting::is_favorite(browser, &config.favorites),
+        tooltip: browser.command_line(),
+        group: match config.group_by {
+            sorting::GroupBy::Vendor => Some(browser.vendor()).filter(|vendor| !vendor.is_empty()),
+            sorting::GroupBy::None => None,
+        },
+        state: std::rc::Rc::new(browser.clone()),
+    }
+}
+
+/// The items whose title matches the `filter`, see `fuzzy::fuzzy_score`, the best matches
+/// first. The items matching as well keep their order, all of them when the filter is empty.
+/// The best matches are not kept under the vendor headers, only the whole list is.
+pub fn filter_list_items<T: Clone>(
+    items: &[ui::ListItem<T>],
+    filter: &str,
+) -> Vec<ui::ListItem<T>> {
+    if filter.is_empty() {
+        return items.to_vec();
+    }
+
+    let mut scored_items: Vec<(i64, &ui::ListItem<T>)> = items
+        .iter()
+        .filter_map(|item| fuzzy::fuzzy_score(filter, &item.title).map(|score| (score, item)))
+        .collect();
+    scored_items.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+    scored_items
+        .into_iter()
+        .map(|(_, item)| ui::ListItem {
+            group: None,
+            ..item.clone()
+        })
+        .collect()
+}
+
+/// The index selected after moving `step` items away from `selected_index` in a list of
+/// `len` items, wrapping around at both ends. With nothing selected (-1) going down selects
+/// the first item and going up the last one. `None` when the list is empty.
+pub fn next_list_index(selected_index: i32, step: i32, len: usize) -> Option<u32> {
+    if len == 0 {
+        return None;
+    }
+
+    let len = len as i32;
+    let index = match selected_index {
+        index if index < 0 && step < 0 => len - 1,
+        index if index < 0 => 0,
+        index => (index + step).rem_euclid(len),
+    };
+
+    Some(index as u32)
+}
+
+/// The number of the list item chosen with the given number key, from 1 to 9
+pub fn shortcut_number(key: winit::event::VirtualKeyCode) -> Option<usize> {
+    use winit::event::VirtualKeyCode::*;
+
+    [
+        (Key1, Numpad1),
+        (Key2, Numpad2),
+        (Key3, Numpad3),
+        (Key4, Numpad4),
+        (Key5, Numpad5),
+        (Key6, Numpad6),
+        (Key7, Numpad7),
+        (Key8, Numpad8),
+        (Key9, Numpad9),
+    ]
+    .iter()
+    .position(|(number_key, numpad_key)| key == *number_key || key == *numpad_key)
+    .map(|index| index + 1)
+}
+
+/// Centers the `window` on the monitor showing the mouse cursor,
+/// on the primary monitor when the cursor position is unknown.
+pub fn center_on_cursor_monitor(window: &Window) {
+    let cursor_monitor = os::util::get_cursor_position().and_then(|cursor| {
+        window.available_monitors().find(|monitor| {
+            let (position, size) = (monitor.position(), monitor.size());
+            cursor.x >= position.x
+                && cursor.x < position.x + size.width as i32
+                && cursor.y >= position.y
+                && cursor.y < position.y + size.height as i32
+        })
+    });
+    let monitor = match cursor_monitor.or_else(|| window.primary_monitor()) {
+        Some(monitor) => monitor,
+        None => return,
+    };
+
+    // the window is scaled to the DPI of the monitor once it lands there
+    let scale = monitor.scale_factor() / window.scale_factor();
+    let window_size = window.outer_size();
+    let (width, height) = (
+        (window_size.width as f64 * scale) as i32,
+        (window_size.height as f64 * scale) as i32,
+    );
+    let (position, size) = (monitor.position(), monitor.size());
+    window.set_outer_position(winit::dpi::PhysicalPosition::new(
+        position.x + (size.width as i32 - width) / 2,
+        position.y + (size.height as i32 - height) / 2,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_item(title: &str, group: Option<&str>) -> ui::ListItem<()> {
+        ui::ListItem {
+            title: String::from(title),
+            subtitle: String::new(),
+            details: Vec::new(),
+            image: Default::default(),
+            uuid: title.to_lowercase(),
+            is_favorite: false,
+            tooltip: String::new(),
+            group: group.map(String::from),
+            state: std::rc::Rc::new(()),
+        }
+    }
+
+    fn titles(items: &[ui::ListItem<()>]) -> Vec<&str> {
+        items.iter().map(|item| item.title.as_str()).collect()
+    }
+
+    #[test]
+    fn empty_filter_keeps_the_whole_list() {
+        let items = vec![
+            list_item("Google Chrome", Some("Google")),
+            list_item("Firefox", Some("Mozilla")),
+        ];
+
+        let filtered = filter_list_items(&items, "");
+        assert_eq!(titles(&filtered), vec!["Google Chrome", "Firefox"]);
+        assert_eq!(filtered[0].group.as_deref(), Some("Google"));
+    }
+
+    #[test]
+    fn best_matches_come_first_without_their_groups() {
+        let items = vec![
+            list_item("Google Chrome", Some("Google")),
+            list_item("Chromium", Some("The Chromium Authors")),
+            list_item("Firefox", Some("Mozilla")),
+        ];
+
+        let filtered = filter_list_items(&items, "chr");
+        assert_eq!(titles(&filtered), vec!["Chromium", "Google Chrome"]);
+        assert!(filtered.iter().all(|item| item.group.is_none()));
+    }
+
+    #[test]
+    fn equal_scores_keep_their_order() {
+        let items = vec![
+            list_item("Firefox Developer Edition", None),
+            list_item("Google Chrome", None),
+            list_item("Firefox", None),
+            list_item("Firefox Nightly", None),
+        ];
+
+        let filtered = filter_list_items(&items, "fire");
+        assert_eq!(
+            titles(&filtered),
+            vec!["Firefox Developer Edition", "Firefox", "Firefox Nightly"]
+        );
+    }
+
+    #[test]
+    fn next_list_index_wraps_around() {
+        assert_eq!(next_list_index(-1, 1, 3), Some(0));
+        assert_eq!(next_list_index(-1, -1, 3), Some(2));
+        assert_eq!(next_list_index(2, 1, 3), Some(0));
+        assert_eq!(next_list_index(0, -1, 3), Some(2));
+        assert_eq!(next_list_index(0, 1, 0), None);
+    }
+}
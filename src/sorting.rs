@@ -0,0 +1,259 @@
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::os::shared::BrowserEntry;
+
+/// How the browsers are ordered in the list, set with `sort` in the config file
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    /// Alphabetically by the browser name
+    #[default]
+    Name,
+
+    /// The OS default browser first, followed by the others sorted by name.
+    /// When the default browser isn't in the list this is the same as `Name`.
+    DefaultFirst,
+
+    /// The browsers chosen the most times first, the ties sorted by name.
+    /// The times are counted in the stats file, see `stats::record_launch`.
+    Frequency,
+}
+
+/// How the browsers are grouped under headers in the list, set with `group_by` in the config file
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    /// A flat list
+    #[default]
+    None,
+
+    /// Under the name of their publisher, the browsers without one last
+    Vendor,
+}
+
+/// Sorts the `browsers` in the given `order`, `launch_counts` holds the times
+/// each browser was chosen by id for `SortOrder::Frequency`
+pub fn sort_browsers<B: BrowserEntry>(
+    browsers: &mut [B],
+    order: &SortOrder,
+    launch_counts: &HashMap<String, u64>,
+) {
+    browsers.sort_by_key(|browser| browser.display_name().to_lowercase());
+
+    // the sorts are stable thus the rest keep their order by name
+    let launch_count = |browser: &B| launch_counts.get(&browser.id()).copied().unwrap_or(0);
+    match order {
+        SortOrder::Name => (),
+        SortOrder::DefaultFirst => browsers.sort_by_key(|browser| !browser.is_default()),
+        SortOrder::Frequency => browsers.sort_by_key(|browser| Reverse(launch_count(browser))),
+    }
+}
+
+/// Moves the browsers referenced in `favorites`, by executable file name or full path,
+/// to the top of the list. Both the favorites and the others keep their order.
+pub fn pin_favorites<B: BrowserEntry>(browsers: &mut [B], favorites: &[String]) {
+    browsers.sort_by_key(|browser| !is_favorite(browser, favorites));
+}
+
+pub fn is_favorite<B: BrowserEntry>(browser: &B, favorites: &[String]) -> bool {
+    favorites
+        .iter()
+        .any(|reference| browser.is_referenced_by(reference))
+}
+
+/// Moves the browsers to the positions the user dragged them to, given as the list of
+/// browser ids in `manual_order`. The browsers without a position keep their order at the end.
+pub fn apply_manual_order<B: BrowserEntry>(browsers: &mut [B], manual_order: &[String]) {
+    browsers.sort_by_key(|browser| {
+        let id = browser.id();
+        manual_order
+            .iter()
+            .position(|ordered_id| *ordered_id == id)
+            .unwrap_or(usize::MAX)
+    });
+}
+
+/// Puts the browsers of the same vendor next to each other, the vendors by name and
+/// the browsers without one last. The browsers of a vendor keep their order.
+pub fn group_by_vendor<B: BrowserEntry>(browsers: &mut [B]) {
+    browsers.sort_by_key(|browser| {
+        let vendor = browser.vendor().to_lowercase();
+        (vendor.is_empty(), vendor)
+    });
+}
+
+/// Orders the `browsers` of the list the way the `config` and the user want it: by the
+/// `sort` of the config, then by the `manual_order` the user dragged them in, with the
+/// `favorites` on top and grouped by the `group_by`. An `only_show` list keeps its order.
+pub fn order_list<B: BrowserEntry>(
+    browsers: &mut [B],
+    config: &Config,
+    manual_order: &[String],
+    launch_counts: &HashMap<String, u64>,
+) {
+    if config.only_show.is_none() {
+        sort_browsers(browsers, &config.sort, launch_counts);
+    }
+    apply_manual_order(browsers, manual_order);
+    pin_favorites(browsers, &config.favorites);
+    if config.group_by == GroupBy::Vendor {
+        group_by_vendor(browsers);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::os::shared::TestBrowser;
+
+    fn names(browsers: &[TestBrowser]) -> Vec<String> {
+        browsers
+            .iter()
+            .map(|browser| browser.display_name())
+            .collect()
+    }
+
+    #[test]
+    fn stale_favorite_is_ignored() {
+        let mut browsers = vec![
+            TestBrowser::new("Firefox", "firefox.exe"),
+            TestBrowser::new("Google Chrome", "chrome.exe"),
+        ];
+        let favorites = vec![String::from("uninstalled.exe")];
+
+        pin_favorites(&mut browsers, &favorites);
+        assert_eq!(names(&browsers), vec!["Firefox", "Google Chrome"]);
+        assert!(!browsers
+            .iter()
+            .any(|browser| is_favorite(browser, &favorites)));
+    }
+
+    fn browser(name: &str, exe_name: &str, vendor: &str) -> TestBrowser {
+        TestBrowser {
+            vendor: String::from(vendor),
+            ..TestBrowser::new(name, exe_name)
+        }
+    }
+
+    fn browsers() -> Vec<TestBrowser> {
+        vec![
+            browser("opera", "opera.exe", ""),
+            browser("Google Chrome", "chrome.exe", "Google LLC"),
+            browser("Firefox", "firefox.exe", "Mozilla"),
+            browser("Chrome Canary", "canary.exe", "Google LLC"),
+        ]
+    }
+
+    #[test]
+    fn sort_by_name_ignores_the_case() {
+        let mut browsers = browsers();
+        sort_browsers(&mut browsers, &SortOrder::Name, &HashMap::new());
+        assert_eq!(
+            names(&browsers),
+            vec!["Chrome Canary", "Firefox", "Google Chrome", "opera"]
+        );
+    }
+
+    #[test]
+    fn sort_default_first_keeps_the_others_by_name() {
+        let mut browsers = browsers();
+        browsers[2].is_default = true;
+
+        sort_browsers(&mut browsers, &SortOrder::DefaultFirst, &HashMap::new());
+        assert_eq!(
+            names(&browsers),
+            vec!["Firefox", "Chrome Canary", "Google Chrome", "opera"]
+        );
+    }
+
+    #[test]
+    fn sort_by_frequency_breaks_the_ties_by_name() {
+        let mut browsers = browsers();
+        let launch_counts: HashMap<String, u64> = vec![
+            (browsers[0].id(), 3),
+            (browsers[1].id(), 7),
+            (browsers[2].id(), 3),
+        ]
+        .into_iter()
+        .collect();
+
+        sort_browsers(&mut browsers, &SortOrder::Frequency, &launch_counts);
+        assert_eq!(
+            names(&browsers),
+            vec!["Google Chrome", "Firefox", "opera", "Chrome Canary"]
+        );
+    }
+
+    #[test]
+    fn pin_favorites_keeps_both_orders() {
+        let mut browsers = browsers();
+        let favorites = vec![
+            String::from("canary.exe"),
+            String::from("C:/Browsers/Firefox.exe"),
+        ];
+
+        pin_favorites(&mut browsers, &favorites);
+        assert_eq!(
+            names(&browsers),
+            vec!["Firefox", "Chrome Canary", "opera", "Google Chrome"]
+        );
+        assert!(is_favorite(&browsers[0], &favorites));
+        assert!(!is_favorite(&browsers[2], &favorites));
+    }
+
+    #[test]
+    fn group_by_vendor_puts_the_unknown_vendor_last() {
+        let mut browsers = browsers();
+        group_by_vendor(&mut browsers);
+        assert_eq!(
+            names(&browsers),
+            vec!["Google Chrome", "Chrome Canary", "Firefox", "opera"]
+        );
+    }
+
+    #[test]
+    fn apply_manual_order_puts_the_unordered_ones_last() {
+        let mut browsers = browsers();
+        let manual_order = vec![browsers[3].id(), browsers[0].id()];
+
+        apply_manual_order(&mut browsers, &manual_order);
+        assert_eq!(
+            names(&browsers),
+            vec!["Chrome Canary", "opera", "Google Chrome", "Firefox"]
+        );
+    }
+
+    #[test]
+    fn order_list_pins_the_favorites_over_the_manual_order() {
+        let mut browsers = browsers();
+        let manual_order = vec![browsers[3].id(), browsers[2].id()];
+        let config = Config {
+            favorites: vec![String::from("opera.exe")],
+            ..Config::default()
+        };
+
+        order_list(&mut browsers, &config, &manual_order, &HashMap::new());
+        assert_eq!(
+            names(&browsers),
+            vec!["opera", "Chrome Canary", "Firefox", "Google Chrome"]
+        );
+    }
+
+    #[test]
+    fn order_list_keeps_the_only_show_order() {
+        let mut browsers = browsers();
+        let config = Config {
+            only_show: Some(vec![String::from("opera.exe")]),
+            ..Config::default()
+        };
+
+        order_list(&mut browsers, &config, &[], &HashMap::new());
+        assert_eq!(
+            names(&browsers),
+            vec!["opera", "Google Chrome", "Firefox", "Chrome Canary"]
+        );
+    }
+}
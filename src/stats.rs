@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+
+use crate::error::*;
+use crate::store::{FileStore, Store};
+
+// Kept apart from the preferences as it changes with every launch
+const STATS_FILE_NAME: &str = "stats.toml";
+const LAUNCH_COUNTS_KEY: &str = "launch_counts";
+
+/// How many times each browser was chosen, by browser id, read from the stats file
+/// in the `config_dir`. The browsers never chosen are missing.
+pub fn load_counts(config_dir: &str) -> BSResult<HashMap<String, u64>> {
+    read_counts(&FileStore::open_file(config_dir, STATS_FILE_NAME)?)
+}
+
+/// Counts one more launch of the browser with the given id in the stats file in the `config_dir`
+pub fn record_launch(config_dir: &str, browser_id: &str) -> BSResult<()> {
+    let mut store = FileStore::open_file(config_dir, STATS_FILE_NAME)?;
+    let mut counts = read_counts(&store)?;
+    *counts.entry(String::from(browser_id)).or_insert(0) += 1;
+
+    store.set(LAUNCH_COUNTS_KEY, toml::Value::try_from(&counts)?);
+    store.flush()
+}
+
+fn read_counts(store: &dyn Store) -> BSResult<HashMap<String, u64>> {
+    match store.get(LAUNCH_COUNTS_KEY) {
+        Some(counts) => Ok(counts.try_into()?),
+        None => Ok(HashMap::new()),
+    }
+}
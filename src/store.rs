@@ -0,0 +1,101 @@
+use crate::error::*;
+
+const STORE_FILE_NAME: &str = "prefs.toml";
+
+/// Key value storage for the state the program learns while being used.
+/// Values are only written to the underlying storage by `flush`.
+pub trait Store {
+    fn get(&self, key: &str) -> Option<toml::Value>;
+    fn set(&mut self, key: &str, value: toml::Value);
+    fn remove(&mut self, key: &str);
+    fn flush(&mut self) -> BSResult<()>;
+}
+
+/// Store backed by a TOML file in the config directory
+pub struct FileStore {
+    path: std::path::PathBuf,
+    values: toml::value::Table,
+}
+
+impl FileStore {
+    /// An empty store saved in the given `config_dir`, replacing what was saved before
+    pub fn new(config_dir: &str) -> FileStore {
+        FileStore {
+            path: std::path::Path::new(config_dir).join(STORE_FILE_NAME),
+            values: toml::value::Table::new(),
+        }
+    }
+
+    /// Reads the values saved in the given `config_dir`,
+    /// when nothing was saved yet the store starts empty.
+    pub fn open(config_dir: &str) -> BSResult<FileStore> {
+        FileStore::open_file(config_dir, STORE_FILE_NAME)
+    }
+
+    /// Same as `open` for the values kept in another file of the `config_dir`
+    pub fn open_file(config_dir: &str, file_name: &str) -> BSResult<FileStore> {
+        let mut store = FileStore {
+            path: std::path::Path::new(config_dir).join(file_name),
+            values: toml::value::Table::new(),
+        };
+        if store.path.exists() {
+            store.values = toml::from_str(&std::fs::read_to_string(&store.path)?)?;
+        }
+
+        Ok(store)
+    }
+}
+
+impl Store for FileStore {
+    fn get(&self, key: &str) -> Option<toml::Value> {
+        self.values.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: toml::Value) {
+        self.values.insert(String::from(key), value);
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.values.remove(key);
+    }
+
+    fn flush(&mut self) -> BSResult<()> {
+        // written next to the file then renamed over it so that
+        // a failed write never leaves a truncated file behind
+        let temp_path = self.path.with_extension("toml.tmp");
+        // as a value the tables are written after the keys of the same level,
+        // ie. `last_choice` after the `[[host_choices]]`
+        let contents = toml::to_string(&toml::Value::Table(self.values.clone()))?;
+        std::fs::write(&temp_path, contents)?;
+        std::fs::rename(&temp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+/// Store keeping the values in memory only, meant for tests
+/// so that they never touch the real state in `%APPDATA%`.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStore {
+    values: toml::value::Table,
+}
+
+#[cfg(test)]
+impl Store for MemoryStore {
+    fn get(&self, key: &str) -> Option<toml::Value> {
+        self.values.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: toml::Value) {
+        self.values.insert(String::from(key), value);
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.values.remove(key);
+    }
+
+    fn flush(&mut self) -> BSResult<()> {
+        Ok(())
+    }
+}
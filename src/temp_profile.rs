@@ -0,0 +1,65 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::*;
+use crate::os::shared::BrowserFamily;
+
+const DEFAULT_BASE_DIR_NAME: &str = "browser-selector-profiles";
+
+/// Returns the directory under which temporary profiles are created,
+/// which is the `configured` one or a sub directory of the OS temp directory.
+pub fn base_dir(configured: &Option<String>) -> PathBuf {
+    match configured {
+        Some(dir) => PathBuf::from(dir),
+        None => std::env::temp_dir().join(DEFAULT_BASE_DIR_NAME),
+    }
+}
+
+/// Creates a new empty profile directory under `base_dir` and returns the
+/// arguments that make a browser of the given `family` use it.
+/// Browsers not known to support custom profile directories get no arguments
+/// and are opened with their usual profile.
+pub fn create_profile_args(family: &BrowserFamily, base_dir: &Path) -> BSResult<Vec<String>> {
+    if *family == BrowserFamily::Unknown {
+        return Ok(Vec::new());
+    }
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let profile_dir = base_dir.join(format!("{}-{}", created_at, std::process::id()));
+    std::fs::create_dir_all(&profile_dir)?;
+    let profile_dir = profile_dir.to_string_lossy().to_string();
+
+    Ok(match family {
+        BrowserFamily::Chromium => vec![format!("--user-data-dir={}", profile_dir)],
+        // -no-remote prevents handing the URL over to an already running instance
+        BrowserFamily::Firefox => vec![
+            String::from("-no-remote"),
+            String::from("-profile"),
+            profile_dir,
+        ],
+        BrowserFamily::Unknown => Vec::new(),
+    })
+}
+
+/// Removes the profile directories left behind by previous runs.
+/// Profiles still used by a running browser can't be removed
+/// and are left in place to be picked up by a later cleanup.
+pub fn cleanup(base_dir: &Path) {
+    let entries = match std::fs::read_dir(base_dir) {
+        Ok(entries) => entries,
+        Err(_) => return, // nothing was created yet
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if let Err(e) = std::fs::remove_dir_all(entry.path()) {
+            log::error!(
+                "Could not remove temporary profile {}. Reason: {}",
+                entry.path().to_string_lossy(),
+                e
+            );
+        }
+    }
+}
@@ -1,5 +1,9 @@
+#[cfg(not(target_os = "windows"))]
+mod unsupported_ui;
+#[cfg(target_os = "windows")]
 mod windows_ui;
 
+use crate::config::ThemeConfig;
 use crate::error::BSResult;
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
@@ -11,36 +15,117 @@ use std::rc::Rc;
 */
 #[cfg(target_os = "windows")]
 pub type Image = bindings::windows::ui::xaml::controls::Image;
+#[cfg(not(target_os = "windows"))]
+#[derive(Clone, Default)]
+pub struct Image;
+#[cfg(not(target_os = "windows"))]
+pub use unsupported_ui::BrowserSelectorUI;
 #[cfg(target_os = "windows")]
 pub use windows_ui::BrowserSelectorUI;
 #[cfg(target_os = "windows")]
 mod windows_desktop_window_xaml_source;
 
 pub trait UserInterface<T: Clone> {
-    fn new() -> BSResult<BrowserSelectorUI<T>>;
+    fn new() -> BSResult<Self>
+    where
+        Self: Sized;
     fn create(&mut self, winit_wnd: &Window) -> BSResult<()>;
+    fn close(&mut self) -> BSResult<()>;
 
     fn set_list(&mut self, list: &[ListItem<T>]) -> BSResult<()>;
+
+    /// Adds the `item` at the end of the list keeping the selection,
+    /// the first item added to an empty list gets selected.
+    fn append_list_item(&mut self, item: &ListItem<T>) -> BSResult<()>;
+    /// Sizes the text of the header and of the list items, `item_padding` is added
+    /// above and below the text of each item
+    fn set_item_layout(&mut self, font_size: f64, item_padding: f64) -> BSResult<()>;
+    fn show_shortcut_numbers(&mut self, visible: bool) -> BSResult<()>;
     fn set_url(&self, url: &str) -> BSResult<()>;
+    fn set_masked_url(&self, url: &str, masked_url: &str) -> BSResult<()>;
+
+    /// The URL in the header with the changes the user made to it
+    fn get_url(&self) -> String;
+    fn set_action_text(&self, text: &str) -> BSResult<()>;
 
+    /// Offers to always open the URLs of the `host` in the chosen browser with
+    /// an unticked checkbox, `None` hides it
+    fn set_always_use_host(&self, host: Option<&str>) -> BSResult<()>;
+    fn is_always_use_checked(&self) -> BSResult<bool>;
+
+    fn set_theme(&self, window: &Window, theme: &ThemeConfig) -> BSResult<()>;
     fn update_layout_size(&self, window: &Window, size: &PhysicalSize<u32>) -> BSResult<()>;
-    fn load_image(path: &str) -> BSResult<Image>;
 
+    /// Resizes the `window` to the height of its content, at most the height
+    /// of the work area of the monitor it is on. The width is kept.
+    fn fit_to_content(&self, window: &Window) -> BSResult<()>;
+
+    /// The icon at `path` read at the size it is shown at on a monitor with
+    /// the `scale_factor`, see `Window::scale_factor`
+    fn load_image(path: &str, scale_factor: f64) -> BSResult<Image>;
+
+    /// Selects the item at `index` and scrolls the list to show it, the group headers
+    /// are not counted by the indexes of the items
     fn select_list_item_by_index(&self, index: u32) -> BSResult<()>;
+
+    /// Fades in the accent color behind the item with the `uuid`, confirming
+    /// the choice before the window closes
+    fn highlight_list_item(&self, uuid: &str) -> BSResult<()>;
+
+    /// Scrolls the list by the height of its visible part times `pages`,
+    /// negative values scroll up. The selection is kept.
+    fn scroll_list_by_pages(&self, pages: f64) -> BSResult<()>;
     fn get_selected_list_item_index(&self) -> BSResult<i32>;
     fn get_selected_list_item(&self) -> BSResult<Option<ListItem<T>>>;
 
-    fn on_list_item_selected(
+    fn on_list_item_selected(&self, event_handler: impl FnMut(&str) + 'static) -> BSResult<()>;
+
+    /// Calls `event_handler` when the user clicks the button copying the URL
+    fn on_copy_url(&self, event_handler: impl FnMut() + 'static) -> BSResult<()>;
+
+    /// Lets the user select several items and confirm them with a button,
+    /// `event_handler` receives the uuids of the selected items.
+    fn enable_multi_select(&self, event_handler: impl FnMut(Vec<String>) + 'static)
+        -> BSResult<()>;
+
+    /// Replaces the list by the `message` and a button calling `event_handler`, for when
+    /// there is nothing to choose from. The URL can then be selected to be copied.
+    fn show_empty_state(
         &self,
-        event_handler: impl FnMut(&str) -> () + 'static,
+        message: &str,
+        button_text: &str,
+        event_handler: impl FnMut() + 'static,
     ) -> BSResult<()>;
+
+    /// Adds a panel next to the list showing the `details` of the selected item
+    fn show_details_panel(&self) -> BSResult<()>;
+
+    /// Calls `event_handler` when the user moves the mouse over the window or clicks in it
+    fn on_user_interaction(&self, event_handler: impl FnMut() + 'static) -> BSResult<()>;
+
+    /// Lets the user reorder the list by dragging the items, `event_handler`
+    /// receives the uuids of all the items in their new order.
+    fn on_list_reordered(&self, event_handler: impl FnMut(Vec<String>) + 'static) -> BSResult<()>;
 }
 
 #[derive(Clone)]
 pub struct ListItem<T: Clone> {
     pub title: String,
     pub subtitle: String,
+
+    // label and value pairs shown in the details panel
+    pub details: Vec<(String, String)>,
     pub image: Image,
     pub uuid: String,
+
+    // pinned to the top of the list, marked with a star
+    pub is_favorite: bool,
+
+    // shown when hovering the item, tells apart the builds of the same browser
+    pub tooltip: String,
+
+    // the header the item is listed under, `None` for the last group when grouping,
+    // the list has no headers when no item has a group
+    pub group: Option<String>,
     pub state: Rc<T>,
 }
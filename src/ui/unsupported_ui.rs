@@ -0,0 +1,143 @@
+/*
+  Stand-in for the platforms without a UI yet, see `os::unsupported`.
+  Creating it fails thus the other methods are never reached.
+*/
+use std::marker::PhantomData;
+use winit::dpi::PhysicalSize;
+use winit::window::Window;
+
+use super::{Image, ListItem, UserInterface};
+use crate::config::ThemeConfig;
+use crate::error::*;
+
+pub struct BrowserSelectorUI<ItemStateType: Clone> {
+    item_state: PhantomData<ItemStateType>,
+}
+
+impl<ItemStateType: Clone> UserInterface<ItemStateType> for BrowserSelectorUI<ItemStateType> {
+    fn new() -> BSResult<Self> {
+        Err(BSError::new(
+            "The browser list can currently only be shown on Windows.",
+        ))
+    }
+
+    fn create(&mut self, _window: &Window) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn close(&mut self) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn set_list(&mut self, _list: &[ListItem<ItemStateType>]) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn append_list_item(&mut self, _item: &ListItem<ItemStateType>) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn set_item_layout(&mut self, _font_size: f64, _item_padding: f64) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn show_shortcut_numbers(&mut self, _visible: bool) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn set_url(&self, _url: &str) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn set_masked_url(&self, _url: &str, _masked_url: &str) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn get_url(&self) -> String {
+        String::new()
+    }
+
+    fn set_action_text(&self, _text: &str) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn set_always_use_host(&self, _host: Option<&str>) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn is_always_use_checked(&self) -> BSResult<bool> {
+        Ok(false)
+    }
+
+    fn set_theme(&self, _window: &Window, _theme: &ThemeConfig) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn update_layout_size(&self, _window: &Window, _size: &PhysicalSize<u32>) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn fit_to_content(&self, _window: &Window) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn load_image(_path: &str, _scale_factor: f64) -> BSResult<Image> {
+        Ok(Image)
+    }
+
+    fn select_list_item_by_index(&self, _index: u32) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn highlight_list_item(&self, _uuid: &str) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn scroll_list_by_pages(&self, _pages: f64) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn get_selected_list_item_index(&self) -> BSResult<i32> {
+        Ok(-1)
+    }
+
+    fn get_selected_list_item(&self) -> BSResult<Option<ListItem<ItemStateType>>> {
+        Ok(None)
+    }
+
+    fn on_list_item_selected(&self, _event_handler: impl FnMut(&str) + 'static) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn on_copy_url(&self, _event_handler: impl FnMut() + 'static) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn enable_multi_select(
+        &self,
+        _event_handler: impl FnMut(Vec<String>) + 'static,
+    ) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn show_empty_state(
+        &self,
+        _message: &str,
+        _button_text: &str,
+        _event_handler: impl FnMut() + 'static,
+    ) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn show_details_panel(&self) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn on_user_interaction(&self, _event_handler: impl FnMut() + 'static) -> BSResult<()> {
+        Ok(())
+    }
+
+    fn on_list_reordered(&self, _event_handler: impl FnMut(Vec<String>) + 'static) -> BSResult<()> {
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
-use std::convert::TryInto;
-use std::mem::MaybeUninit;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 
 // For clarity purposes keep all WinRT imports under wrt::
 // winrt is a different crate dealing with types for calling the imported resources
@@ -9,39 +9,48 @@ mod wrt {
         DesktopWindowXamlSource, IDesktopWindowXamlSourceFactory, WindowsXamlManager,
     };
 
-    pub use bindings::windows::storage::streams::{DataWriter, IBuffer, IDataWriterFactory};
-
     pub use bindings::windows::foundation::{
-        IPropertyValue, IReference, IStringable, PropertyType, PropertyValue,
-    };
-    pub use bindings::windows::graphics::imaging::{
-        BitmapAlphaMode, BitmapPixelFormat, ISoftwareBitmapFactory, SoftwareBitmap,
+        IPropertyValue, IReference, IStringable, PropertyType, PropertyValue, Size, TimeSpan,
+        TypedEventHandler,
     };
+    pub use bindings::windows::ui::view_management::{UIColorType, UISettings};
     pub use bindings::windows::ui::xaml::controls::{
-        Button, ColumnDefinition, Grid, IButtonFactory, IGridFactory, IListBoxFactory,
-        IListViewFactory, IRelativePanelFactory, IScrollViewerStatics, IStackPanelFactory, Image,
-        ItemClickEventArgs, ItemClickEventHandler, ItemsControl, ListBox, ListView,
-        ListViewSelectionMode, Orientation, Panel, RelativePanel, RowDefinition, ScrollMode,
-        ScrollViewer, StackPanel, TextBlock,
+        Border, Button, CheckBox, ColumnDefinition, Control, DragItemsCompletedEventArgs, Grid,
+        IButtonFactory, ICheckBoxFactory, IGridFactory, IListBoxFactory, IListViewFactory,
+        IListViewItemFactory, IRelativePanelFactory, IScrollViewerStatics, IStackPanelFactory,
+        Image, ItemClickEventArgs, ItemClickEventHandler, ItemsControl, ListBox, ListView,
+        ListViewBase, ListViewItem, ListViewSelectionMode, Orientation, Panel, RelativePanel,
+        RowDefinition, ScrollBarVisibility, ScrollMode, ScrollViewer, SelectionChangedEventArgs,
+        SelectionChangedEventHandler, StackPanel, TextBlock, TextBox, TextChangedEventArgs,
+        TextChangedEventHandler, ToolTipService,
     };
+    pub use bindings::windows::ui::xaml::input::{PointerEventHandler, PointerRoutedEventArgs};
     pub use bindings::windows::ui::xaml::interop::{TypeKind, TypeName};
-    pub use bindings::windows::ui::xaml::media::imaging::{BitmapImage, SoftwareBitmapSource};
-    pub use bindings::windows::ui::xaml::media::ImageSource;
+    pub use bindings::windows::ui::xaml::media::SolidColorBrush;
     pub use bindings::windows::ui::xaml::{
-        FrameworkElement, GridLength, GridUnitType, RoutedEventHandler, Thickness, UIElement,
-        VerticalAlignment,
+        BrushTransition, CornerRadius, DependencyObject, ElementTheme, FocusState,
+        FrameworkElement, GridLength, GridUnitType, HorizontalAlignment, RoutedEventArgs,
+        RoutedEventHandler, TextWrapping, Thickness, UIElement, VerticalAlignment, Visibility,
     };
+    pub use bindings::windows::ui::Color;
 }
 
 mod winapi {
-    pub use winapi::shared::windef::{HGDIOBJ, HICON, HWND};
-    pub use winapi::um::wingdi::{DeleteObject, GetBitmapBits, GetObjectW, BITMAP, DIBSECTION};
-    pub use winapi::um::winuser::{GetIconInfo, SetWindowPos, UpdateWindow, ICONINFO};
+    pub use winapi::shared::windef::HWND;
+    pub use winapi::um::winuser::{
+        GetMonitorInfoW, GetWindowLongPtrW, MonitorFromWindow, SetLayeredWindowAttributes,
+        SetWindowLongPtrW, SetWindowPos, UpdateWindow, GWL_EXSTYLE, LWA_ALPHA, MONITORINFO,
+        MONITOR_DEFAULTTONEAREST, WS_EX_LAYERED,
+    };
 }
 
+use crate::config::{ThemeConfig, ThemeMode};
 use crate::error::*;
-use crate::os_util::{as_u8_slice, get_hwnd};
+use crate::i18n;
+use crate::os::os_browsers;
+use crate::os::util::{get_hwnd, is_dark_mode};
 use crate::ui::windows_desktop_window_xaml_source::IDesktopWindowXamlSourceNative;
+use crate::url_utils;
 
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
@@ -76,15 +85,54 @@ pub struct XamlIslandWindow {
 }
 
 impl Default for XamlIslandWindow {
+    // the COM references start out null, `close` skips releasing those
     fn default() -> XamlIslandWindow {
-        unsafe {
-            XamlIslandWindow {
-                hwnd_parent: std::ptr::null_mut(),
-                hwnd: std::ptr::null_mut(),
-                idesktop_source: std::mem::zeroed(),
-                desktop_source: std::mem::zeroed(),
-                win_xaml_mgr: std::mem::zeroed(),
-            }
+        XamlIslandWindow {
+            hwnd_parent: std::ptr::null_mut(),
+            hwnd: std::ptr::null_mut(),
+            idesktop_source: IDesktopWindowXamlSourceNative::default(),
+            desktop_source: wrt::DesktopWindowXamlSource::default(),
+            win_xaml_mgr: wrt::WindowsXamlManager::default(),
+        }
+    }
+}
+
+impl XamlIslandWindow {
+    /// Releases the XAML resources in the reverse order of their creation,
+    /// the `WindowsXamlManager` being the last. Calling it more than once is harmless.
+    pub fn close(&mut self) -> winrt::Result<()> {
+        if !self.desktop_source.is_null() {
+            self.desktop_source.close()?;
+        }
+        self.idesktop_source = IDesktopWindowXamlSourceNative::default();
+        self.desktop_source = wrt::DesktopWindowXamlSource::default();
+
+        if !self.win_xaml_mgr.is_null() {
+            self.win_xaml_mgr.close()?;
+        }
+        self.win_xaml_mgr = wrt::WindowsXamlManager::default();
+        self.hwnd = std::ptr::null_mut();
+        self.hwnd_parent = std::ptr::null_mut();
+
+        Ok(())
+    }
+}
+
+impl Drop for XamlIslandWindow {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            log::error!("Error closing the XAML island. Reason: {}", e.message());
+        }
+    }
+}
+
+// the fields of `UI` would be dropped in their order, the XAML island before the controls it
+// hosts, thus the controls are released first by `close`. The `Exit` of the event loop
+// closes the UI in `Event::LoopDestroyed` already, this covers the early returns.
+impl<ItemStateType: Clone> Drop for BrowserSelectorUI<ItemStateType> {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            log::error!("Error releasing the UI resources. Reason: {}", e);
         }
     }
 }
@@ -92,13 +140,104 @@ impl Default for XamlIslandWindow {
 #[derive(Default)]
 pub struct UI<T: Clone> {
     pub xaml_isle: XamlIslandWindow,
-    pub list: Vec<crate::ui::ListItem<T>>,
+    // shared with the event handlers which have to see the items set afterwards
+    pub list: Rc<RefCell<Vec<crate::ui::ListItem<T>>>>,
+
+    // draws the background and the rounded corners around the container
+    pub root: wrt::Border,
     pub container: wrt::Panel,
+
+    // whether the first items are numbered with their keyboard shortcut
+    pub shortcut_numbers: bool,
+
+    // the text size and the spacing of the list items, see `set_item_layout`
+    pub item_layout: ItemLayout,
+
+    // the URL in the header including the changes the user typed in
+    pub url: Rc<RefCell<String>>,
+
+    // whether the URL is masked while the mouse is not over it, see `set_masked_url`
+    pub url_masked: Rc<Cell<bool>>,
+}
+
+/// The text size and the spacing of the list items and the header, in effective pixels
+#[derive(Debug, Clone, Copy)]
+pub struct ItemLayout {
+    pub font_size: f64,
+
+    // added above and below the text of each list item
+    pub padding: f64,
+}
+
+impl Default for ItemLayout {
+    fn default() -> ItemLayout {
+        ItemLayout {
+            font_size: DEFAULT_FONT_SIZE,
+            padding: 0.,
+        }
+    }
 }
 
 const LIST_CONTROL_NAME: &str = "browserList";
+const LIST_SCROLL_VIEWER_NAME: &str = "browserListScrollViewer";
 const URL_CONTROL_NAME: &str = "urlControl";
+const ACTION_TEXT_CONTROL_NAME: &str = "actionText";
+const DETAILS_PANEL_NAME: &str = "detailsPanel";
+
+// the size XAML gives to the text of the controls unless told otherwise
+const DEFAULT_FONT_SIZE: f64 = 14.;
+
+// follows the title of the browsers pinned with `favorites`
+const FAVORITE_MARK: &str = "\u{2605}";
+
+// the size of the browser icons in the list, in effective pixels
+const LIST_ICON_SIZE: f64 = 32.;
+
+// the items from 1 to 9 can be chosen with the number keys
+const MAX_SHORTCUT_NUMBER: usize = 9;
+const DETAILS_PANEL_WIDTH: f64 = 260.;
+const DETAILS_ICON_SIZE: f64 = 64.;
+
+// the list scrolls past this height instead of growing the window, in effective pixels
+const MAX_LIST_HEIGHT: f64 = 480.;
 const HEADER_PANEL_NAME: &str = "headerPanel";
+const COPY_URL_BUTTON_NAME: &str = "copyUrlButton";
+const ALWAYS_USE_CHECKBOX_NAME: &str = "alwaysUseCheckBox";
+
+// the window backgrounds of the Windows light and dark app modes
+const LIGHT_BACKGROUND: wrt::Color = wrt::Color {
+    a: 255,
+    r: 255,
+    g: 255,
+    b: 255,
+};
+const DARK_BACKGROUND: wrt::Color = wrt::Color {
+    a: 255,
+    r: 32,
+    g: 32,
+    b: 32,
+};
+
+// the list items are transparent rather than without a background
+// so that the mouse is over them between their texts too
+const TRANSPARENT_BACKGROUND: wrt::Color = wrt::Color {
+    a: 0,
+    r: 128,
+    g: 128,
+    b: 128,
+};
+// a translucent gray reads on the light and dark backgrounds alike
+const HOVER_BACKGROUND: wrt::Color = wrt::Color {
+    a: 40,
+    r: 128,
+    g: 128,
+    b: 128,
+};
+// the opacity of the accent color behind the chosen list item, see `highlight_list_item`
+const HIGHLIGHT_ALPHA: u8 = 160;
+
+// how long the background of a list item takes to change, in 100 nanosecond units
+const BACKGROUND_TRANSITION_DURATION: i64 = 100 * 10_000;
 
 impl<ItemStateType: Clone> UserInterface<ItemStateType> for BrowserSelectorUI<ItemStateType> {
     fn new() -> BSResult<Self> {
@@ -111,8 +250,13 @@ impl<ItemStateType: Clone> UserInterface<ItemStateType> for BrowserSelectorUI<It
         // handler is registered (very unlikely)'
         let state = UI {
             xaml_isle: init_win_ui_xaml()?,
-            list: Vec::<ListItem<ItemStateType>>::new(),
+            list: Rc::new(RefCell::new(Vec::<ListItem<ItemStateType>>::new())),
+            root: wrt::Border::default(),
             container: wrt::Panel::default(),
+            shortcut_numbers: false,
+            item_layout: ItemLayout::default(),
+            url: Rc::new(RefCell::new(String::new())),
+            url_masked: Rc::new(Cell::new(false)),
         };
 
         Ok(BrowserSelectorUI { state })
@@ -126,13 +270,102 @@ impl<ItemStateType: Clone> UserInterface<ItemStateType> for BrowserSelectorUI<It
             winapi::UpdateWindow(self.state.xaml_isle.hwnd_parent as winapi::HWND);
         }
 
-        let ui_container = create_ui(&self.state)?;
+        let root = create_ui(&self.state)?;
 
         self.state
             .xaml_isle
             .desktop_source
-            .set_content(ui_container.to_owned())?;
-        self.state.container = ComInterface::query::<wrt::Panel>(&ui_container);
+            .set_content(ComInterface::query::<wrt::UIElement>(&root))?;
+        self.state.container = ComInterface::query::<wrt::Panel>(&root.child()?);
+        self.state.root = root;
+
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, URL_CONTROL_NAME)?
+        {
+            let text_box = ComInterface::query::<wrt::TextBox>(&ui_element);
+            let edited_text_box = text_box.clone();
+            let edited_url = self.state.url.clone();
+            text_box.text_changed(wrt::TextChangedEventHandler::new(
+                move |_: &winrt::Object, _: &wrt::TextChangedEventArgs| -> winrt::Result<()> {
+                    // the text set by the program, ie. the masked URL, is not an edit
+                    if edited_text_box.focus_state()? != wrt::FocusState::Unfocused {
+                        *edited_url.borrow_mut() = edited_text_box.text()?.to_string();
+                    }
+                    Ok(())
+                },
+            ))?;
+
+            // a masked URL is only revealed while the mouse is over it or while it is edited,
+            // the URL is cloned as setting the text borrows it again through `text_changed`
+            let revealing_text_box = text_box.clone();
+            let revealed_url = self.state.url.clone();
+            let revealed_url_masked = self.state.url_masked.clone();
+            text_box.pointer_entered(wrt::PointerEventHandler::new(
+                move |_: &winrt::Object, _: &wrt::PointerRoutedEventArgs| -> winrt::Result<()> {
+                    if !revealed_url_masked.get() {
+                        return Ok(());
+                    }
+                    let full_url = revealed_url.borrow().clone();
+                    revealing_text_box.set_text(full_url.as_str())
+                },
+            ))?;
+
+            let masking_text_box = text_box.clone();
+            let masked_url = self.state.url.clone();
+            let url_masked = self.state.url_masked.clone();
+            text_box.pointer_exited(wrt::PointerEventHandler::new(
+                move |_: &winrt::Object, _: &wrt::PointerRoutedEventArgs| -> winrt::Result<()> {
+                    if !url_masked.get()
+                        || masking_text_box.focus_state()? != wrt::FocusState::Unfocused
+                    {
+                        return Ok(());
+                    }
+                    let masked_url = url_utils::mask_url(masked_url.borrow().as_str());
+                    masking_text_box.set_text(masked_url.as_str())
+                },
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> BSResult<()> {
+        // the controls have to be released before the XAML island hosting them
+        self.state.list.borrow_mut().clear();
+        self.state.container = wrt::Panel::default();
+        self.state.root = wrt::Border::default();
+        self.state.xaml_isle.close()?;
+
+        Ok(())
+    }
+
+    fn set_theme(&self, window: &Window, theme: &ThemeConfig) -> BSResult<()> {
+        // Windows 10 does not round the window corners by itself
+        let radius = theme.corner_radius.max(0.);
+        self.state.root.set_corner_radius(wrt::CornerRadius {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        })?;
+        set_window_opacity(window, theme.effective_opacity())?;
+
+        // the text colors follow the requested theme of the root
+        let dark = match theme.mode {
+            ThemeMode::System => is_dark_mode(),
+            ThemeMode::Light => false,
+            ThemeMode::Dark => true,
+        };
+        let background = wrt::SolidColorBrush::new()?;
+        background.set_color(match dark {
+            true => DARK_BACKGROUND,
+            false => LIGHT_BACKGROUND,
+        })?;
+        self.state.root.set_background(background)?;
+        self.state.root.set_requested_theme(match dark {
+            true => wrt::ElementTheme::Dark,
+            false => wrt::ElementTheme::Light,
+        })?;
 
         Ok(())
     }
@@ -143,37 +376,233 @@ impl<ItemStateType: Clone> UserInterface<ItemStateType> for BrowserSelectorUI<It
         Ok(())
     }
 
+    fn fit_to_content(&self, window: &Window) -> BSResult<()> {
+        let scale_factor = window.scale_factor();
+        let inner_size = window.inner_size();
+
+        // XAML measures in effective pixels, the window is sized in physical ones
+        self.state.root.measure(wrt::Size {
+            width: (inner_size.width as f64 / scale_factor) as f32,
+            height: std::f32::INFINITY,
+        })?;
+        let content_height = self.state.root.desired_size()?.height as f64 * scale_factor;
+
+        // the title bar and the borders take some of the work area too
+        let frame_height = window.outer_size().height.saturating_sub(inner_size.height);
+        let max_height = work_area_height(window).saturating_sub(frame_height);
+        let height = (content_height.ceil() as u32).min(max_height);
+        if height > 0 && height != inner_size.height {
+            window.set_inner_size(PhysicalSize::new(inner_size.width, height));
+        }
+
+        Ok(())
+    }
+
     fn set_list(&mut self, list: &[ListItem<ItemStateType>]) -> BSResult<()> {
         if let Some(ui_element) =
             recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)?
         {
             let listview = ComInterface::query::<wrt::ListView>(&ui_element);
-            self.state.list = list.clone().to_vec();
-            set_listview_items(&listview, list)?;
+            *self.state.list.borrow_mut() = list.to_vec();
+            set_listview_items(
+                &listview,
+                list,
+                self.state.shortcut_numbers,
+                self.state.item_layout,
+            )?;
+            if let Some(row) = row_of_item(list, 0) {
+                listview.set_selected_index(row as i32)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn append_list_item(&mut self, item: &ListItem<ItemStateType>) -> BSResult<()> {
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)?
+        {
+            let listview = ComInterface::query::<wrt::ListView>(&ui_element);
+            let selected_index = self.get_selected_list_item_index()?.max(0) as usize;
+            self.state.list.borrow_mut().push(item.clone());
+            let list = self.state.list.borrow();
+            if is_grouped(&list) {
+                // the item goes under its header, which may not be the last one
+                set_listview_items(
+                    &listview,
+                    &list,
+                    self.state.shortcut_numbers,
+                    self.state.item_layout,
+                )?;
+                if let Some(row) = row_of_item(&list, selected_index) {
+                    listview.set_selected_index(row as i32)?;
+                }
+                return Ok(());
+            }
+
+            let shortcut_number = Some(list.len())
+                .filter(|number| self.state.shortcut_numbers && *number <= MAX_SHORTCUT_NUMBER);
+            listview
+                .items()?
+                .append(winrt::Object::from(create_list_item(
+                    item,
+                    shortcut_number,
+                    self.state.item_layout,
+                )?))?;
+            if listview.selected_index()? < 0 {
+                listview.set_selected_index(0)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_item_layout(&mut self, font_size: f64, item_padding: f64) -> BSResult<()> {
+        self.state.item_layout = ItemLayout {
+            font_size,
+            padding: item_padding,
+        };
+
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, ACTION_TEXT_CONTROL_NAME)?
+        {
+            ComInterface::query::<wrt::TextBlock>(&ui_element).set_font_size(font_size)?;
+        }
+        let header_controls = [
+            URL_CONTROL_NAME,
+            COPY_URL_BUTTON_NAME,
+            ALWAYS_USE_CHECKBOX_NAME,
+        ];
+        for control_name in &header_controls {
+            if let Some(ui_element) =
+                recursive_find_child_by_tag(&self.state.container, control_name)?
+            {
+                ComInterface::query::<wrt::Control>(&ui_element).set_font_size(font_size)?;
+            }
+        }
+
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)?
+        {
+            let listview = ComInterface::query::<wrt::ListView>(&ui_element);
+            let selected_index = listview.selected_index()?;
+            set_listview_items(
+                &listview,
+                &self.state.list.borrow(),
+                self.state.shortcut_numbers,
+                self.state.item_layout,
+            )?;
+            listview.set_selected_index(selected_index)?;
+        }
+
+        Ok(())
+    }
+
+    fn show_shortcut_numbers(&mut self, visible: bool) -> BSResult<()> {
+        self.state.shortcut_numbers = visible;
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)?
+        {
+            let listview = ComInterface::query::<wrt::ListView>(&ui_element);
+            let selected_index = listview.selected_index()?;
+            set_listview_items(
+                &listview,
+                &self.state.list.borrow(),
+                visible,
+                self.state.item_layout,
+            )?;
+            listview.set_selected_index(selected_index)?;
         }
 
         Ok(())
     }
 
     fn set_url(&self, new_url: &str) -> BSResult<()> {
+        *self.state.url.borrow_mut() = String::from(new_url);
+        self.state.url_masked.set(false);
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, URL_CONTROL_NAME)?
+        {
+            let text_box = ComInterface::query::<wrt::TextBox>(&ui_element);
+            text_box.set_text(new_url)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_url(&self) -> String {
+        self.state.url.borrow().trim().to_owned()
+    }
+
+    fn set_masked_url(&self, url: &str, masked_url: &str) -> BSResult<()> {
         if let Some(ui_element) =
             recursive_find_child_by_tag(&self.state.container, URL_CONTROL_NAME)?
+        {
+            *self.state.url.borrow_mut() = String::from(url);
+            // the full URL is revealed by the pointer handlers registered in `create`
+            self.state.url_masked.set(true);
+            let text_box = ComInterface::query::<wrt::TextBox>(&ui_element);
+            text_box.set_text(masked_url)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_action_text(&self, text: &str) -> BSResult<()> {
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, ACTION_TEXT_CONTROL_NAME)?
         {
             let text_block = ComInterface::query::<wrt::TextBlock>(&ui_element);
-            text_block.set_text(new_url)?;
+            text_block.set_text(text)?;
         }
 
         Ok(())
     }
 
-    fn load_image(path: &str) -> BSResult<Image> {
-        let hicon = crate::os_util::get_exe_file_icon(path)?;
-        let bmp = hicon_to_software_bitmap(hicon)?;
+    fn set_always_use_host(&self, host: Option<&str>) -> BSResult<()> {
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, ALWAYS_USE_CHECKBOX_NAME)?
+        {
+            let check_box = ComInterface::query::<wrt::CheckBox>(&ui_element);
+            // ticking it for one host does not carry over to the next one
+            check_box.set_is_checked(
+                wrt::PropertyValue::create_boolean(false)?.query::<wrt::IReference<bool>>(),
+            )?;
+            match host {
+                Some(host) => {
+                    let text = i18n::t_with("always_use_for", &[("host", &host)]);
+                    check_box.set_content(wrt::PropertyValue::create_string(text.as_str())?)?;
+                    check_box.set_visibility(wrt::Visibility::Visible)?;
+                }
+                None => check_box.set_visibility(wrt::Visibility::Collapsed)?,
+            }
+        }
 
-        match software_bitmap_to_xaml_image(bmp) {
-            Ok(image) => Ok(image),
-            Err(winrt_error) => Err(BSError::from(winrt_error)),
+        Ok(())
+    }
+
+    fn is_always_use_checked(&self) -> BSResult<bool> {
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, ALWAYS_USE_CHECKBOX_NAME)?
+        {
+            let check_box = ComInterface::query::<wrt::CheckBox>(&ui_element);
+            let checked = check_box.is_checked()?;
+            return Ok(check_box.visibility()? == wrt::Visibility::Visible
+                && !checked.is_null()
+                && checked.value()?);
         }
+
+        Ok(false)
+    }
+
+    fn load_image(path: &str, scale_factor: f64) -> BSResult<Image> {
+        // read at the size of its physical pixels so that XAML does not stretch it
+        let size = (LIST_ICON_SIZE * scale_factor).round() as u32;
+        let image = os_browsers::load_image_for_exe(path, size)?;
+        image.set_width(LIST_ICON_SIZE)?;
+        image.set_height(LIST_ICON_SIZE)?;
+
+        Ok(image)
     }
 
     fn select_list_item_by_index(&self, index: u32) -> BSResult<()> {
@@ -182,8 +611,56 @@ impl<ItemStateType: Clone> UserInterface<ItemStateType> for BrowserSelectorUI<It
                 .unwrap()
                 .unwrap()
                 .query();
+        let row = match row_of_item(&self.state.list.borrow(), index as usize) {
+            Some(row) => row as i32,
+            None => bail!("There is no list item at index {}", index),
+        };
+
+        list_control.set_selected_index(row)?;
+
+        // the container is missing until the list is laid out, it is then shown from the top
+        let container: wrt::UIElement = list_control.container_from_index(row)?.query();
+        if !container.is_null() {
+            container.start_bring_into_view()?;
+        }
+
+        Ok(())
+    }
+
+    fn highlight_list_item(&self, uuid: &str) -> BSResult<()> {
+        let list_control: wrt::ListView =
+            recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)
+                .unwrap()
+                .unwrap()
+                .query();
+        let list = self.state.list.borrow();
+        let row = match list
+            .iter()
+            .position(|item| item.uuid == uuid)
+            .and_then(|index| row_of_item(&list, index))
+        {
+            Some(row) => row as u32,
+            None => bail!("There is no list item with the uuid {}", uuid),
+        };
 
-        list_control.set_selected_index(index as i32)?;
+        // the accent color fades in through the transition set by `create_list_item`
+        let mut color = wrt::UISettings::new()?.get_color_value(wrt::UIColorType::Accent)?;
+        color.a = HIGHLIGHT_ALPHA;
+        let item_panel: wrt::StackPanel = list_control.items()?.get_at(row)?.query();
+        item_panel.set_background(solid_color_brush(color)?)?;
+
+        Ok(())
+    }
+
+    fn scroll_list_by_pages(&self, pages: f64) -> BSResult<()> {
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, LIST_SCROLL_VIEWER_NAME)?
+        {
+            let scroll_viewer = ComInterface::query::<wrt::ScrollViewer>(&ui_element);
+            let offset =
+                scroll_viewer.vertical_offset()? + pages * scroll_viewer.viewport_height()?;
+            scroll_viewer.scroll_to_vertical_offset(offset.max(0.))?;
+        }
 
         Ok(())
     }
@@ -194,23 +671,39 @@ impl<ItemStateType: Clone> UserInterface<ItemStateType> for BrowserSelectorUI<It
                 .unwrap()
                 .unwrap()
                 .query();
+        let selected_row = list_control.selected_index()?;
+        if selected_row < 0 {
+            return Ok(-1);
+        }
 
-        Ok(list_control.selected_index()?)
+        let list = self.state.list.borrow();
+        Ok(item_of_row(&list, selected_row as usize)
+            .map(|index| index as i32)
+            .unwrap_or(-1))
     }
     fn get_selected_list_item(&self) -> BSResult<Option<ListItem<ItemStateType>>> {
-        let selected_index = self.get_selected_list_item_index()?;
-        if selected_index < 0 {
+        let list_control: wrt::ListView =
+            recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)
+                .unwrap()
+                .unwrap()
+                .query();
+        let selected_item = list_control.selected_item()?;
+        if selected_item.is_null() {
             return Ok(None);
         }
 
-        let cloned_item = self.state.list[selected_index as usize].clone();
-        Ok(Some(cloned_item))
+        // looked up by the tag as the items can be reordered by the user
+        let uuid = ui_element_get_tag_as_string(&selected_item)?;
+        Ok(self
+            .state
+            .list
+            .borrow()
+            .iter()
+            .find(|item| Some(&item.uuid) == uuid.as_ref())
+            .cloned())
     }
 
-    fn on_list_item_selected(
-        &self,
-        mut event_handler: impl FnMut(&str) -> () + 'static,
-    ) -> BSResult<()> {
+    fn on_list_item_selected(&self, mut event_handler: impl FnMut(&str) + 'static) -> BSResult<()> {
         let list_control: wrt::ListView =
             recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)
                 .unwrap()
@@ -230,6 +723,257 @@ impl<ItemStateType: Clone> UserInterface<ItemStateType> for BrowserSelectorUI<It
 
         Ok(())
     }
+
+    fn on_copy_url(&self, mut event_handler: impl FnMut() + 'static) -> BSResult<()> {
+        if let Some(ui_element) =
+            recursive_find_child_by_tag(&self.state.container, COPY_URL_BUTTON_NAME)?
+        {
+            ComInterface::query::<wrt::Button>(&ui_element).click(wrt::RoutedEventHandler::new(
+                move |_: &winrt::Object, _: &wrt::RoutedEventArgs| -> winrt::Result<()> {
+                    event_handler();
+                    Ok(())
+                },
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn enable_multi_select(
+        &self,
+        mut event_handler: impl FnMut(Vec<String>) + 'static,
+    ) -> BSResult<()> {
+        let list_control: wrt::ListView =
+            recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)
+                .unwrap()
+                .unwrap()
+                .query();
+        // a click toggles the item selection instead of opening the browser
+        list_control.set_selection_mode(wrt::ListViewSelectionMode::Multiple)?;
+        list_control.set_is_item_click_enabled(false)?;
+
+        let grid: wrt::Grid = self.state.container.query();
+        let button_row_definition = wrt::RowDefinition::new()?;
+        button_row_definition.set_height(wrt::GridLength {
+            value: 1.0,
+            grid_unit_type: wrt::GridUnitType::Auto,
+        })?;
+        grid.row_definitions()?.append(button_row_definition)?;
+
+        let open_button = winrt::factory::<wrt::Button, wrt::IButtonFactory>()?
+            .create_instance(winrt::Object::default(), &mut winrt::Object::default())?;
+        open_button.set_content(wrt::PropertyValue::create_string(i18n::t(
+            "open_in_selected",
+        ))?)?;
+        open_button.set_horizontal_alignment(wrt::HorizontalAlignment::Right)?;
+        open_button.set_margin(wrt::Thickness {
+            top: 15.,
+            left: 0.,
+            right: 0.,
+            bottom: 0.,
+        })?;
+        wrt::Grid::set_row(
+            ComInterface::query::<wrt::FrameworkElement>(&open_button),
+            2,
+        )?;
+
+        let selecting_list = list_control.clone();
+        open_button.click(wrt::RoutedEventHandler::new(
+            move |_: &winrt::Object, _: &wrt::RoutedEventArgs| -> winrt::Result<()> {
+                let mut uuids = Vec::<String>::new();
+                let iterator = selecting_list.selected_items()?.first()?;
+                while iterator.has_current()? {
+                    if let Ok(Some(uuid)) = ui_element_get_tag_as_string(&iterator.current()?) {
+                        uuids.push(uuid);
+                    }
+                    iterator.move_next()?;
+                }
+                if !uuids.is_empty() {
+                    event_handler(uuids);
+                }
+
+                Ok(())
+            },
+        ))?;
+        grid.children()?.append(open_button)?;
+
+        Ok(())
+    }
+
+    fn show_empty_state(
+        &self,
+        message: &str,
+        button_text: &str,
+        mut event_handler: impl FnMut() + 'static,
+    ) -> BSResult<()> {
+        if let Some(list_control) =
+            recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)?
+        {
+            list_control.set_visibility(wrt::Visibility::Collapsed)?;
+        }
+        let message_block = wrt::TextBlock::new()?;
+        message_block.set_text(message)?;
+        message_block.set_text_wrapping(wrt::TextWrapping::Wrap)?;
+
+        let button = winrt::factory::<wrt::Button, wrt::IButtonFactory>()?
+            .create_instance(winrt::Object::default(), &mut winrt::Object::default())?;
+        button.set_content(wrt::PropertyValue::create_string(button_text)?)?;
+        button.set_margin(wrt::Thickness {
+            top: 15.,
+            left: 0.,
+            right: 0.,
+            bottom: 0.,
+        })?;
+        button.click(wrt::RoutedEventHandler::new(
+            move |_: &winrt::Object, _: &wrt::RoutedEventArgs| -> winrt::Result<()> {
+                event_handler();
+                Ok(())
+            },
+        ))?;
+
+        // takes the row of the list
+        let empty_state_panel = create_stack_panel()?;
+        empty_state_panel.set_margin(wrt::Thickness {
+            top: 15.,
+            left: 0.,
+            right: 0.,
+            bottom: 0.,
+        })?;
+        empty_state_panel.children()?.append(message_block)?;
+        empty_state_panel.children()?.append(button)?;
+        wrt::Grid::set_row(
+            ComInterface::query::<wrt::FrameworkElement>(&empty_state_panel),
+            1,
+        )?;
+        self.state.container.children()?.append(empty_state_panel)?;
+
+        Ok(())
+    }
+
+    fn show_details_panel(&self) -> BSResult<()> {
+        let list_control: wrt::ListView =
+            recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)
+                .unwrap()
+                .unwrap()
+                .query();
+        let grid: wrt::Grid = self.state.container.query();
+        let details_column_definition = wrt::ColumnDefinition::new()?;
+        details_column_definition.set_width(wrt::GridLength {
+            value: DETAILS_PANEL_WIDTH,
+            grid_unit_type: wrt::GridUnitType::Pixel,
+        })?;
+        grid.column_definitions()?
+            .append(details_column_definition)?;
+
+        // the header stays above both the list and the panel
+        if let Some(header_panel) =
+            recursive_find_child_by_tag(&self.state.container, HEADER_PANEL_NAME)?
+        {
+            wrt::Grid::set_column_span(
+                ComInterface::query::<wrt::FrameworkElement>(&header_panel),
+                2,
+            )?;
+        }
+
+        let details_panel = create_stack_panel()?;
+        details_panel.set_margin(wrt::Thickness {
+            top: 15.,
+            left: 15.,
+            right: 0.,
+            bottom: 0.,
+        })?;
+        ui_element_set_string_tag(&details_panel, DETAILS_PANEL_NAME)?;
+        wrt::Grid::set_row(
+            ComInterface::query::<wrt::FrameworkElement>(&details_panel),
+            1,
+        )?;
+        wrt::Grid::set_column(
+            ComInterface::query::<wrt::FrameworkElement>(&details_panel),
+            1,
+        )?;
+        grid.children()?.append(details_panel.clone())?;
+
+        let list = self.state.list.clone();
+        let selected_list = list_control.clone();
+        let updating_panel = details_panel.clone();
+        list_control.selection_changed(wrt::SelectionChangedEventHandler::new(
+            move |_: &winrt::Object, _: &wrt::SelectionChangedEventArgs| -> winrt::Result<()> {
+                let uuid = ui_element_get_tag_as_string(&selected_list.selected_item()?)
+                    .unwrap_or_default();
+                let list = list.borrow();
+                let item = list.iter().find(|item| Some(&item.uuid) == uuid.as_ref());
+                set_details_panel_item(&updating_panel, item)
+            },
+        ))?;
+
+        let selected_item = self.get_selected_list_item()?;
+        set_details_panel_item(&details_panel, selected_item.as_ref())?;
+
+        Ok(())
+    }
+
+    fn on_user_interaction(&self, event_handler: impl FnMut() + 'static) -> BSResult<()> {
+        // the list handles the clicks on its items thus the
+        // moves are what reliably reaches the root element
+        let event_handler = Rc::new(RefCell::new(event_handler));
+        let moved_handler = event_handler.clone();
+        self.state
+            .root
+            .pointer_moved(wrt::PointerEventHandler::new(
+                move |_: &winrt::Object, _: &wrt::PointerRoutedEventArgs| -> winrt::Result<()> {
+                    (moved_handler.borrow_mut())();
+                    Ok(())
+                },
+            ))?;
+        self.state
+            .root
+            .pointer_pressed(wrt::PointerEventHandler::new(
+                move |_: &winrt::Object, _: &wrt::PointerRoutedEventArgs| -> winrt::Result<()> {
+                    (event_handler.borrow_mut())();
+                    Ok(())
+                },
+            ))?;
+
+        Ok(())
+    }
+
+    fn on_list_reordered(
+        &self,
+        mut event_handler: impl FnMut(Vec<String>) + 'static,
+    ) -> BSResult<()> {
+        let list_control: wrt::ListView =
+            recursive_find_child_by_tag(&self.state.container, LIST_CONTROL_NAME)
+                .unwrap()
+                .unwrap()
+                .query();
+        list_control.set_can_drag_items(true)?;
+        list_control.set_can_reorder_items(true)?;
+        list_control.set_allow_drop(true)?;
+
+        let reordered_list = list_control.clone();
+        list_control.drag_items_completed(wrt::TypedEventHandler::<
+            wrt::ListViewBase,
+            wrt::DragItemsCompletedEventArgs,
+        >::new(
+            move |_: &wrt::ListViewBase,
+                  _: &wrt::DragItemsCompletedEventArgs|
+                  -> winrt::Result<()> {
+                let mut uuids = Vec::<String>::new();
+                let iterator = reordered_list.items()?.first()?;
+                while iterator.has_current()? {
+                    if let Ok(Some(uuid)) = ui_element_get_tag_as_string(&iterator.current()?) {
+                        uuids.push(uuid);
+                    }
+                    iterator.move_next()?;
+                }
+                event_handler(uuids);
+
+                Ok(())
+            },
+        ))?;
+
+        Ok(())
+    }
 }
 
 pub fn init_win_ui_xaml() -> winrt::Result<XamlIslandWindow> {
@@ -275,9 +1019,99 @@ pub fn update_xaml_island_size(
     Ok(())
 }
 
-pub fn create_ui<T: Clone>(ui: &UI<T>) -> winrt::Result<wrt::UIElement> {
-    let header_panel = create_header("You are about to open:", "")?;
-    let list = create_list(&ui.list)?;
+/// The height in physical pixels of the work area, the monitor without the taskbar,
+/// of the monitor showing most of the `window`.
+fn work_area_height(window: &Window) -> u32 {
+    unsafe {
+        let monitor = winapi::MonitorFromWindow(get_hwnd(window), winapi::MONITOR_DEFAULTTONEAREST);
+        let mut monitor_info: winapi::MONITORINFO = std::mem::zeroed();
+        monitor_info.cbSize = std::mem::size_of::<winapi::MONITORINFO>() as u32;
+        if winapi::GetMonitorInfoW(monitor, &mut monitor_info) == 0 {
+            return window
+                .current_monitor()
+                .map(|monitor| monitor.size().height)
+                .unwrap_or(std::u32::MAX);
+        }
+
+        (monitor_info.rcWork.bottom - monitor_info.rcWork.top).max(0) as u32
+    }
+}
+
+/// Fills the details panel with a larger icon and the details of the `item`,
+/// empties it when no item is selected.
+fn set_details_panel_item<T: Clone>(
+    details_panel: &wrt::StackPanel,
+    item: Option<&ListItem<T>>,
+) -> winrt::Result<()> {
+    details_panel.children()?.clear()?;
+    let item = match item {
+        Some(item) => item,
+        None => return Ok(()),
+    };
+
+    // the list item image already has a parent, only its source can be shared
+    let icon = wrt::Image::new()?;
+    icon.set_source(item.image.source()?)?;
+    icon.set_width(DETAILS_ICON_SIZE)?;
+    icon.set_height(DETAILS_ICON_SIZE)?;
+    icon.set_horizontal_alignment(wrt::HorizontalAlignment::Left)?;
+    details_panel.children()?.append(icon)?;
+
+    for (label, value) in &item.details {
+        let label_block = wrt::TextBlock::new()?;
+        label_block.set_text(label.as_str())?;
+        label_block.set_margin(wrt::Thickness {
+            top: 10.,
+            left: 0.,
+            right: 0.,
+            bottom: 0.,
+        })?;
+        label_block.set_opacity(0.6)?;
+
+        let value_block = wrt::TextBlock::new()?;
+        value_block.set_text(value.as_str())?;
+        value_block.set_text_wrapping(wrt::TextWrapping::Wrap)?;
+
+        details_panel.children()?.append(label_block)?;
+        details_panel.children()?.append(value_block)?;
+    }
+
+    Ok(())
+}
+
+/// Makes the whole window translucent, `opacity` goes from 0.0 to 1.0
+pub fn set_window_opacity(window: &Window, opacity: f64) -> BSResult<()> {
+    if opacity >= 1. {
+        return Ok(());
+    }
+
+    let hwnd = get_hwnd(window);
+    unsafe {
+        let ex_style = winapi::GetWindowLongPtrW(hwnd, winapi::GWL_EXSTYLE);
+        winapi::SetWindowLongPtrW(
+            hwnd,
+            winapi::GWL_EXSTYLE,
+            ex_style | winapi::WS_EX_LAYERED as isize,
+        );
+        if winapi::SetLayeredWindowAttributes(
+            hwnd,
+            0,
+            (opacity * 255.).round() as u8,
+            winapi::LWA_ALPHA,
+        ) == 0
+        {
+            bail!("Could not set the window opacity.");
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the controls of the main window wrapped in a `Border`
+/// which draws the background and the rounded corners.
+pub fn create_ui<T: Clone>(ui: &UI<T>) -> winrt::Result<wrt::Border> {
+    let header_panel = create_header(i18n::t("open_prompt"), "")?;
+    let list = create_list_scroll_viewer(create_list(&ui.list.borrow(), ui.item_layout)?)?;
     let grid = create_main_layout_grid()?;
 
     wrt::Grid::set_row(
@@ -294,7 +1128,13 @@ pub fn create_ui<T: Clone>(ui: &UI<T>) -> winrt::Result<wrt::UIElement> {
     grid.children()?.append(header_panel)?;
     grid.children()?.append(list)?;
 
-    Ok(grid.into())
+    let background = wrt::SolidColorBrush::new()?;
+    background.set_color(wrt::UISettings::new()?.get_color_value(wrt::UIColorType::Background)?)?;
+    let root = wrt::Border::new()?;
+    root.set_background(background)?;
+    root.set_child(grid)?;
+
+    Ok(root)
 }
 
 /// Creates a WinUI Grid control with a single column and two rows
@@ -324,17 +1164,17 @@ pub fn create_main_layout_grid() -> winrt::Result<wrt::Grid> {
     Ok(grid)
 }
 
-pub fn create_list_item(
-    title: &str,
-    subtext: &str,
-    image: &wrt::Image,
-    tag: &str,
+/// The row of the list showing the `item`, numbered with its `shortcut_number` when given
+pub fn create_list_item<T: Clone>(
+    item: &ListItem<T>,
+    shortcut_number: Option<usize>,
+    layout: ItemLayout,
 ) -> winrt::Result<wrt::UIElement> {
     let list_item_margins = wrt::Thickness {
-        top: 0.,
+        top: layout.padding,
         left: 15.,
         right: 0.,
-        bottom: 0.,
+        bottom: layout.padding,
     };
     let root_stack_panel = create_stack_panel()?;
     root_stack_panel.set_orientation(wrt::Orientation::Horizontal)?;
@@ -343,24 +1183,70 @@ pub fn create_list_item(
     name_version_stack_panel.set_margin(&list_item_margins)?;
 
     let title_block = wrt::TextBlock::new()?;
-    title_block.set_text(title as &str)?;
+    match item.is_favorite {
+        true => title_block.set_text(format!("{} {}", item.title, FAVORITE_MARK).as_str())?,
+        false => title_block.set_text(item.title.as_str())?,
+    }
+    title_block.set_font_size(layout.font_size)?;
 
     let subtitle_block = wrt::TextBlock::new()?;
-    subtitle_block.set_text(subtext as &str)?;
+    subtitle_block.set_text(item.subtitle.as_str())?;
+    subtitle_block.set_font_size(layout.font_size)?;
 
     name_version_stack_panel.children()?.append(title_block)?;
     name_version_stack_panel
         .children()?
         .append(subtitle_block)?;
-    root_stack_panel.children()?.append(image)?;
+    if let Some(number) = shortcut_number {
+        let shortcut_block = wrt::TextBlock::new()?;
+        shortcut_block.set_text(number.to_string().as_str())?;
+        shortcut_block.set_font_size(layout.font_size)?;
+        shortcut_block.set_width(16.)?;
+        shortcut_block.set_opacity(0.6)?;
+        shortcut_block.set_vertical_alignment(wrt::VerticalAlignment::Center)?;
+        root_stack_panel.children()?.append(shortcut_block)?;
+    }
+    root_stack_panel.children()?.append(&item.image)?;
     root_stack_panel
         .children()?
         .append(name_version_stack_panel)?;
-    ui_element_set_string_tag(&root_stack_panel, tag).unwrap();
+    ui_element_set_string_tag(&root_stack_panel, item.uuid.as_str()).unwrap();
+
+    let background_transition = wrt::BrushTransition::new()?;
+    background_transition.set_duration(wrt::TimeSpan {
+        duration: BACKGROUND_TRANSITION_DURATION,
+    })?;
+    root_stack_panel.set_background_transition(background_transition)?;
+    root_stack_panel.set_background(solid_color_brush(TRANSPARENT_BACKGROUND)?)?;
+    // the handlers get the item as the sender, holding it would keep it alive
+    root_stack_panel.pointer_entered(wrt::PointerEventHandler::new(
+        |sender: &winrt::Object, _: &wrt::PointerRoutedEventArgs| -> winrt::Result<()> {
+            ComInterface::query::<wrt::StackPanel>(sender)
+                .set_background(solid_color_brush(HOVER_BACKGROUND)?)
+        },
+    ))?;
+    root_stack_panel.pointer_exited(wrt::PointerEventHandler::new(
+        |sender: &winrt::Object, _: &wrt::PointerRoutedEventArgs| -> winrt::Result<()> {
+            ComInterface::query::<wrt::StackPanel>(sender)
+                .set_background(solid_color_brush(TRANSPARENT_BACKGROUND)?)
+        },
+    ))?;
+    // given as a string the tooltip control is only created once the item is hovered
+    wrt::ToolTipService::set_tool_tip(
+        ComInterface::query::<wrt::DependencyObject>(&root_stack_panel),
+        wrt::PropertyValue::create_string(item.tooltip.as_str())?,
+    )?;
 
     Ok(root_stack_panel.into())
 }
 
+fn solid_color_brush(color: wrt::Color) -> winrt::Result<wrt::SolidColorBrush> {
+    let brush = wrt::SolidColorBrush::new()?;
+    brush.set_color(color)?;
+
+    Ok(brush)
+}
+
 pub fn create_stack_panel() -> winrt::Result<wrt::StackPanel> {
     let stack_panel = winrt::factory::<wrt::StackPanel, wrt::IStackPanelFactory>()?
         .create_instance(winrt::Object::default(), &mut winrt::Object::default())?;
@@ -368,7 +1254,10 @@ pub fn create_stack_panel() -> winrt::Result<wrt::StackPanel> {
     Ok(stack_panel)
 }
 
-pub fn create_list<T: Clone>(list: &Vec<ListItem<T>>) -> winrt::Result<wrt::UIElement> {
+pub fn create_list<T: Clone>(
+    list: &Vec<ListItem<T>>,
+    layout: ItemLayout,
+) -> winrt::Result<wrt::UIElement> {
     let list_control = winrt::factory::<wrt::ListView, wrt::IListViewFactory>()?
         .create_instance(winrt::Object::default(), &mut winrt::Object::default())?;
     list_control.set_margin(wrt::Thickness {
@@ -380,8 +1269,11 @@ pub fn create_list<T: Clone>(list: &Vec<ListItem<T>>) -> winrt::Result<wrt::UIEl
     list_control.set_selection_mode(wrt::ListViewSelectionMode::Single)?;
     list_control.set_vertical_alignment(wrt::VerticalAlignment::Stretch)?;
 
-    set_listview_items(&list_control, list)?;
-    list_control.set_selected_index(0)?;
+    set_listview_items(&list_control, list, false, layout)?;
+    // selecting in an empty list fails
+    if let Some(row) = row_of_item(list, 0) {
+        list_control.set_selected_index(row as i32)?;
+    }
 
     ui_element_set_string_tag(&list_control, LIST_CONTROL_NAME).unwrap();
     // ^-- .unwrap() is not consistent with the rest of error handling
@@ -390,199 +1282,175 @@ pub fn create_list<T: Clone>(list: &Vec<ListItem<T>>) -> winrt::Result<wrt::UIEl
     Ok(list_control.into())
 }
 
+/// Wraps the `list` so that it scrolls instead of growing past `MAX_LIST_HEIGHT`,
+/// the scroll viewer of the list itself is turned off to leave the scrolling to the wrapper
+pub fn create_list_scroll_viewer(list: wrt::UIElement) -> winrt::Result<wrt::UIElement> {
+    let scroll_viewer = wrt::ScrollViewer::new()?;
+    scroll_viewer.set_vertical_scroll_mode(wrt::ScrollMode::Enabled)?;
+    scroll_viewer.set_vertical_scroll_bar_visibility(wrt::ScrollBarVisibility::Auto)?;
+    scroll_viewer.set_horizontal_scroll_mode(wrt::ScrollMode::Disabled)?;
+    scroll_viewer.set_horizontal_scroll_bar_visibility(wrt::ScrollBarVisibility::Disabled)?;
+    scroll_viewer.set_max_height(MAX_LIST_HEIGHT)?;
+
+    wrt::ScrollViewer::set_vertical_scroll_mode(
+        ComInterface::query::<wrt::DependencyObject>(&list),
+        wrt::ScrollMode::Disabled,
+    )?;
+    scroll_viewer.set_content(&list)?;
+    ui_element_set_string_tag(&scroll_viewer, LIST_SCROLL_VIEWER_NAME).unwrap();
+
+    Ok(scroll_viewer.into())
+}
+
+/// Replaces the items of the `list_control`, numbering the first ones when `shortcut_numbers`
+/// is on so that the numbers always match the position of the items on screen. The items
+/// are put under the headers of their groups when they have some, see `list_rows`.
 pub fn set_listview_items<T: Clone>(
     list_control: &wrt::ListView,
     list: &[ListItem<T>],
+    shortcut_numbers: bool,
+    layout: ItemLayout,
 ) -> winrt::Result<()> {
-    for item in list {
+    list_control.items()?.clear()?;
+    for row in list_rows(list) {
+        let row_element = match row {
+            ListRow::Header(name) => create_group_header(name, layout)?,
+            ListRow::Item(index) => {
+                let shortcut_number = Some(index + 1)
+                    .filter(|number| shortcut_numbers && *number <= MAX_SHORTCUT_NUMBER);
+                create_list_item(&list[index], shortcut_number, layout)?
+            }
+        };
         list_control
             .items()?
-            .append(winrt::Object::from(create_list_item(
-                item.title.as_str(),
-                item.subtitle.as_str(),
-                &item.image,
-                item.uuid.as_str(),
-            )?))?;
+            .append(winrt::Object::from(row_element))?;
     }
 
     Ok(())
 }
 
-pub fn create_header(open_action_text: &str, url: &str) -> winrt::Result<wrt::StackPanel> {
-    let stack_panel = winrt::factory::<wrt::StackPanel, wrt::IStackPanelFactory>()?
-        .create_instance(winrt::Object::default(), &mut winrt::Object::default())?;
-    let call_to_action_top_row = wrt::TextBlock::new()?;
-    let call_to_action_bottom_row = wrt::TextBlock::new()?;
-
-    call_to_action_top_row.set_text(open_action_text)?;
-    call_to_action_bottom_row.set_text(url)?;
+/// A row of the list view, the items are counted without the headers
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListRow<'a> {
+    Header(&'a str),
+    Item(usize),
+}
 
-    call_to_action_bottom_row.set_tag(wrt::PropertyValue::create_string(URL_CONTROL_NAME)?)?;
-    stack_panel.set_tag(wrt::PropertyValue::create_string(HEADER_PANEL_NAME)?)?;
+/// Whether the items of the `list` are shown under group headers
+fn is_grouped<T: Clone>(list: &[ListItem<T>]) -> bool {
+    list.iter().any(|item| item.group.is_some())
+}
 
-    stack_panel.children()?.append(call_to_action_top_row)?;
-    stack_panel.children()?.append(call_to_action_bottom_row)?;
+/// The rows showing the `list`, a header comes before each run of items of the same
+/// group when any item has one, the items without a group are put under "Other"
+fn list_rows<T: Clone>(list: &[ListItem<T>]) -> Vec<ListRow> {
+    let grouped = is_grouped(list);
+    let mut rows = Vec::with_capacity(list.len());
+    for (index, item) in list.iter().enumerate() {
+        let starts_group = index == 0 || list[index - 1].group != item.group;
+        if grouped && starts_group {
+            let name = match &item.group {
+                Some(name) => name.as_str(),
+                None => i18n::t("other_vendor"),
+            };
+            rows.push(ListRow::Header(name));
+        }
+        rows.push(ListRow::Item(index));
+    }
 
-    Ok(stack_panel)
+    rows
 }
 
-/// From the given WinRT SoftwareBitmap it returns
-/// the corresponding WinUI Image XAML control that can be inserted
-/// as a node in any UIElement derived object
-pub fn software_bitmap_to_xaml_image(bmp: wrt::SoftwareBitmap) -> winrt::Result<wrt::Image> {
-    // ToDO: Can we achieve the same thing without this conversion?
-    // Background: ImageSource.SetBitmapAsync will throw an exception if
-    // the bitmap set is not Pixel Format: BGRA8, BitmapAlphaMode: Premulitplied
-    // Does it work setting these flags without any pixel conversion?
-    let bgra8_bmp = match bmp.bitmap_pixel_format()? {
-        wrt::BitmapPixelFormat::Bgra8 => wrt::SoftwareBitmap::convert_with_alpha(
-            bmp,
-            wrt::BitmapPixelFormat::Bgra8,
-            wrt::BitmapAlphaMode::Premultiplied,
-        )?,
-        _ => bmp,
-    };
+/// The row of the list view showing the item at `index`
+fn row_of_item<T: Clone>(list: &[ListItem<T>], index: usize) -> Option<usize> {
+    list_rows(list)
+        .iter()
+        .position(|row| *row == ListRow::Item(index))
+}
 
-    let image_control = wrt::Image::new()?;
-    let img_src: wrt::SoftwareBitmapSource = wrt::SoftwareBitmapSource::new()?;
-    img_src.set_bitmap_async(bgra8_bmp)?;
-    image_control.set_source(wrt::ImageSource::from(img_src))?;
-
-    return Ok(image_control);
-}
-
-/// Converts a HICON to a SoftwareBitmap that can be used with WinUI controls
-///
-/// Notes:
-/// - There probably is a simpler way to achieve this
-/// - The function does not implement all possiblities described in the Windows API doc
-/// thus it is possible that it might not work for certain icon formats
-pub fn hicon_to_software_bitmap(hicon: winapi::HICON) -> BSResult<wrt::SoftwareBitmap> {
-    let mut icon_info: winapi::ICONINFO = unsafe { MaybeUninit::uninit().assume_init() };
-    let icon_result = unsafe { winapi::GetIconInfo(hicon, &mut icon_info) };
-    if icon_result == 0 {
-        bail!("Couldn't get icon info for HICON {:?}", hicon);
-    }
-
-    let dib_struct_size = std::mem::size_of::<winapi::DIBSECTION>()
-        .try_into()
-        .unwrap_or(0);
-    let bitmap_struct_size = std::mem::size_of::<winapi::BITMAP>()
-        .try_into()
-        .unwrap_or(0);
-
-    let mut dib: winapi::DIBSECTION = unsafe { MaybeUninit::uninit().assume_init() };
-    let bytes_read = unsafe {
-        winapi::GetObjectW(
-            icon_info.hbmColor as *mut _ as *mut std::ffi::c_void,
-            dib_struct_size,
-            &mut dib as *mut _ as *mut std::ffi::c_void,
-        )
-    };
+/// The index of the item shown in the `row` of the list view, `None` for the headers
+fn item_of_row<T: Clone>(list: &[ListItem<T>], row: usize) -> Option<usize> {
+    match list_rows(list).get(row) {
+        Some(ListRow::Item(index)) => Some(*index),
+        _ => None,
+    }
+}
 
-    if bytes_read == 0 {
-        unsafe {
-            winapi::DeleteObject(icon_info.hbmColor as winapi::HGDIOBJ);
-            winapi::DeleteObject(icon_info.hbmMask as winapi::HGDIOBJ);
-        }
+/// The header row of a group of the list, disabled so that it cannot be clicked,
+/// selected or reached with the keyboard
+fn create_group_header(name: &str, layout: ItemLayout) -> winrt::Result<wrt::UIElement> {
+    let header_block = wrt::TextBlock::new()?;
+    header_block.set_text(name)?;
+    header_block.set_font_size(layout.font_size)?;
+    header_block.set_opacity(0.6)?;
 
-        bail!("Error: winapi::GetObject returned 0 on ICONINFO.hbmColor bitmap.");
-    }
+    let header_item = winrt::factory::<wrt::ListViewItem, wrt::IListViewItemFactory>()?
+        .create_instance(winrt::Object::default(), &mut winrt::Object::default())?;
+    header_item.set_content(header_block)?;
+    header_item.set_is_enabled(false)?;
+    header_item.set_margin(wrt::Thickness {
+        top: 10.,
+        left: 0.,
+        right: 0.,
+        bottom: 0.,
+    })?;
 
-    // BITMAP size is 32 bytes
-    // DIBSECTION is 104 bytes
-    let bmp_size_in_bytes =
-        (dib.dsBm.bmHeight * dib.dsBm.bmWidth) * (dib.dsBm.bmBitsPixel as i32 / 8);
+    Ok(header_item.into())
+}
 
-    let pixel_bytes_result = match bytes_read {
-        bytes_read if bytes_read == bitmap_struct_size => {
-            // when GetObject returns the size of the BITMAP structure
-            // then dib.dsBm is a device dependent bitmap we need to use GetBitmapBits
-            let mut img_bytes = Vec::<u8>::new();
-            img_bytes.resize(bmp_size_in_bytes as usize, 0);
+pub fn create_header(open_action_text: &str, url: &str) -> winrt::Result<wrt::StackPanel> {
+    let stack_panel = winrt::factory::<wrt::StackPanel, wrt::IStackPanelFactory>()?
+        .create_instance(winrt::Object::default(), &mut winrt::Object::default())?;
+    let call_to_action_top_row = wrt::TextBlock::new()?;
+    // editable to tweak the URL before opening it, ie. to remove a tracking parameter
+    let call_to_action_bottom_row = wrt::TextBox::new()?;
 
-            let img_bytes_read = unsafe {
-                winapi::GetBitmapBits(
-                    icon_info.hbmColor,
-                    bmp_size_in_bytes,
-                    img_bytes.as_mut_slice().as_mut_ptr() as *mut std::ffi::c_void,
-                )
-            };
-            // TODO: Replace GetBitmapBits with GetDibBits because GetBitmapBits is deprecated
+    call_to_action_top_row.set_text(open_action_text)?;
+    call_to_action_bottom_row.set_text(url)?;
 
-            if img_bytes_read == 0 {
-                Err("winapi::GetBitmapBits read 0 bytes from the ICONINFO.hbmColor")
-            } else {
-                Ok(img_bytes)
-            }
-        }
-        bytes_read if bytes_read == dib_struct_size => {
-            if dib.dsBm.bmBits as usize != 0 {
-                Ok(unsafe {
-                    std::slice::from_raw_parts::<u8>(
-                        dib.dsBm.bmBits as *const u8,
-                        bmp_size_in_bytes as usize,
-                    )
-                    .to_vec()
-                })
-            } else {
-                Err("Unexpected NULL pointer for image bits from DIBSECTION.dsBm.bmBits")
-            }
-        }
-        0 => Err("winapi::GetObject returned 0 on ICONINFO.hbmColor bitmap."),
-        _ => Err(
-            "Unexpected response from winapi::GetObject, was expecting read bytes \
-            to match either the BITMAP struct size or the DIBSECTION struct size.",
-        ),
-    };
+    call_to_action_top_row.set_tag(wrt::PropertyValue::create_string(ACTION_TEXT_CONTROL_NAME)?)?;
+    call_to_action_bottom_row.set_tag(wrt::PropertyValue::create_string(URL_CONTROL_NAME)?)?;
+    stack_panel.set_tag(wrt::PropertyValue::create_string(HEADER_PANEL_NAME)?)?;
 
-    let pixel_bytes = match pixel_bytes_result {
-        Ok(bytes) => bytes,
-        Err(error) => unsafe {
-            winapi::DeleteObject(icon_info.hbmColor as winapi::HGDIOBJ);
-            winapi::DeleteObject(icon_info.hbmMask as winapi::HGDIOBJ);
-            bail!(error);
-        },
-    };
+    let copy_url_button = winrt::factory::<wrt::Button, wrt::IButtonFactory>()?
+        .create_instance(winrt::Object::default(), &mut winrt::Object::default())?;
+    copy_url_button.set_content(wrt::PropertyValue::create_string(i18n::t("copy_url"))?)?;
+    copy_url_button.set_margin(wrt::Thickness {
+        top: 5.,
+        left: 0.,
+        right: 0.,
+        bottom: 0.,
+    })?;
+    copy_url_button.set_tag(wrt::PropertyValue::create_string(COPY_URL_BUTTON_NAME)?)?;
 
-    let raw_pixels = pixel_bytes
-        .chunks_exact(4)
-        .map(|chunk| {
-            u32::from_ne_bytes(
-                chunk
-                    .try_into()
-                    .expect("Expected chunk size to be 4 bytes when converting to u32"),
-            )
-        })
-        .collect::<Vec<u32>>();
-
-    let data_writer = wrt::DataWriter::new()?;
-    data_writer.write_bytes(as_u8_slice(&raw_pixels[..]))?;
-
-    let i_buffer = data_writer.detach_buffer()?;
-    let software_bitmap = wrt::SoftwareBitmap::create_copy_with_alpha_from_buffer(
-        i_buffer,
-        wrt::BitmapPixelFormat::Bgra8,
-        dib.dsBm.bmWidth,
-        dib.dsBm.bmHeight,
-        wrt::BitmapAlphaMode::Straight,
-    )?;
-    // About the BitmapPixelFormat::Bgra8:
-    // Hard coding pixel format to BGRA with 1 byte per color seems to work but it should be
-    // detected since there are no guarantees the Windows API will always return this format
+    // shown by `set_always_use_host` when the host of the URL is known
+    let always_use_check_box = winrt::factory::<wrt::CheckBox, wrt::ICheckBoxFactory>()?
+        .create_instance(winrt::Object::default(), &mut winrt::Object::default())?;
+    always_use_check_box.set_visibility(wrt::Visibility::Collapsed)?;
+    always_use_check_box.set_tag(wrt::PropertyValue::create_string(ALWAYS_USE_CHECKBOX_NAME)?)?;
 
-    unsafe {
-        winapi::DeleteObject(icon_info.hbmColor as winapi::HGDIOBJ);
-        winapi::DeleteObject(icon_info.hbmMask as winapi::HGDIOBJ);
-    }
+    stack_panel.children()?.append(call_to_action_top_row)?;
+    stack_panel.children()?.append(call_to_action_bottom_row)?;
+    stack_panel.children()?.append(copy_url_button)?;
+    stack_panel.children()?.append(always_use_check_box)?;
 
-    return Ok(software_bitmap);
+    Ok(stack_panel)
 }
 
 fn recursive_find_child_by_tag(
     parent: &impl winrt::ComInterface,
     needle: &str,
 ) -> winrt::Result<Option<wrt::UIElement>> {
+    // the content of a scroll viewer is searched as if it was its only child
+    let scroll_viewer: wrt::ScrollViewer = parent.query();
+    if !scroll_viewer.is_null() {
+        let content: wrt::UIElement = scroll_viewer.content()?.query();
+        return match ui_element_get_tag_as_string(&content) {
+            Ok(Some(tag_value)) if tag_value == needle => Ok(Some(content)),
+            _ => recursive_find_child_by_tag(&content, needle),
+        };
+    }
+
     let items_control: wrt::Panel = parent.query();
     if items_control.is_null() {
         return Err(winrt::Error::new(
@@ -656,3 +1524,63 @@ fn ui_element_set_string_tag(el: &impl ComInterface, tag: &str) -> BSResult<()>
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_item(title: &str, group: Option<&str>) -> ListItem<()> {
+        ListItem {
+            title: String::from(title),
+            subtitle: String::new(),
+            details: Vec::new(),
+            image: Default::default(),
+            uuid: title.to_lowercase(),
+            is_favorite: false,
+            tooltip: String::new(),
+            group: group.map(String::from),
+            state: Rc::new(()),
+        }
+    }
+
+    #[test]
+    fn list_rows_without_groups_are_the_items() {
+        let list = vec![list_item("Firefox", None), list_item("Chrome", None)];
+        assert_eq!(list_rows(&list), vec![ListRow::Item(0), ListRow::Item(1)]);
+        assert_eq!(row_of_item(&list, 1), Some(1));
+        assert_eq!(item_of_row(&list, 0), Some(0));
+        assert_eq!(item_of_row(&list, 2), None);
+    }
+
+    #[test]
+    fn list_rows_put_a_header_before_each_group() {
+        let list = vec![
+            list_item("Chrome", Some("Google")),
+            list_item("Chrome Beta", Some("Google")),
+            list_item("Firefox", Some("Mozilla")),
+            list_item("Tor", None),
+        ];
+        let rows = list_rows(&list);
+        assert_eq!(
+            rows[..5],
+            [
+                ListRow::Header("Google"),
+                ListRow::Item(0),
+                ListRow::Item(1),
+                ListRow::Header("Mozilla"),
+                ListRow::Item(2),
+            ]
+        );
+        // the items without a group come last under the "Other" header
+        assert!(matches!(rows[5], ListRow::Header(_)));
+        assert_eq!(rows[6], ListRow::Item(3));
+
+        assert_eq!(row_of_item(&list, 0), Some(1));
+        assert_eq!(row_of_item(&list, 2), Some(4));
+        assert_eq!(row_of_item(&list, 3), Some(6));
+        assert_eq!(row_of_item(&list, 4), None);
+        assert_eq!(item_of_row(&list, 0), None);
+        assert_eq!(item_of_row(&list, 4), Some(2));
+        assert_eq!(item_of_row(&list, 6), Some(3));
+    }
+}
@@ -0,0 +1,322 @@
+use crate::error::*;
+
+const MASK_CHARACTER: char = '•';
+
+// Schemes of the links opened by the web browsers, the others are offered
+// to the programs registered for them, ie. the mail programs for mailto
+const BROWSER_SCHEMES: &[&str] = &["http", "https", "file"];
+
+// Schemes opened as they are even without `//` after them, ie. `mailto:`,
+// the other inputs without `//` get `https://`, ie. `localhost:8080`
+const KNOWN_SCHEMES: &[&str] = &[
+    "http", "https", "file", "ftp", "mailto", "tel", "sms", "news", "about", "data", "magnet",
+];
+
+// Query parameters only telling the site where the visitor comes from
+const TRACKING_PARAMETERS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "igshid", "yclid", "mc_cid", "mc_eid", "_ga", "_hsenc",
+    "_hsmi", "mkt_tok",
+];
+const TRACKING_PARAMETER_PREFIXES: &[&str] = &["utm_"];
+
+// Hosts of the URL shorteners whose links are resolved with `resolve_short_urls`
+const SHORTENER_HOSTS: &[&str] = &[
+    "bit.ly",
+    "t.co",
+    "tinyurl.com",
+    "goo.gl",
+    "ow.ly",
+    "buff.ly",
+    "is.gd",
+    "lnkd.in",
+    "rebrand.ly",
+    "t.ly",
+    "cutt.ly",
+    "shorturl.at",
+    "aka.ms",
+];
+
+/// Returns a display version of the given `url` where everything after the host
+/// (path, query, fragment) is replaced with mask characters.
+/// If the URL cannot be parsed the whole string is masked.
+pub fn mask_url(url: &str) -> String {
+    let parsed = match url::Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(_) => return url.chars().map(|_| MASK_CHARACTER).collect(),
+    };
+
+    let origin = match parsed.host_str() {
+        Some(host) => match parsed.port() {
+            Some(port) => format!("{}://{}:{}", parsed.scheme(), host, port),
+            None => format!("{}://{}", parsed.scheme(), host),
+        },
+        None => return url.chars().map(|_| MASK_CHARACTER).collect(),
+    };
+
+    let rest = &parsed[url::Position::BeforePath..];
+    if rest.is_empty() || rest == "/" {
+        return origin;
+    }
+
+    let masked_rest: String = rest.chars().skip(1).map(|_| MASK_CHARACTER).collect();
+    format!("{}/{}", origin, masked_rest)
+}
+
+/// Returns the lowercase host of the given `url` or `None` when the URL has no host
+pub fn host_of(url: &str) -> Option<String> {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|parsed| parsed.host_str().map(|host| host.to_lowercase()))
+}
+
+/// Returns the lowercase scheme of the `url`, ie. `mailto`, `None` when it cannot be parsed
+pub fn scheme_of(url: &str) -> Option<String> {
+    url::Url::parse(url)
+        .ok()
+        .map(|parsed| parsed.scheme().to_lowercase())
+}
+
+/// Whether the links of the `scheme` are opened by the web browsers, see `BROWSER_SCHEMES`
+pub fn is_browser_scheme(scheme: &str) -> bool {
+    BROWSER_SCHEMES.contains(&scheme)
+}
+
+/// Whether the `url` edited by the user still looks like a URL, it needs at least a scheme
+pub fn is_plausible_url(url: &str) -> bool {
+    !url.trim().is_empty() && url::Url::parse(url.trim()).is_ok()
+}
+
+/// The `input` given to open made into a URL: `https://` is added when it has no scheme,
+/// ie. `example.com`, the Windows paths become `file://` URLs and the characters not allowed
+/// in URLs are percent-encoded. Fails for the inputs which are not URLs, ie. a single word.
+pub fn normalize_url(input: &str) -> BSResult<String> {
+    let input = input.trim();
+    if input.is_empty() {
+        bail!("No URL was given.");
+    }
+
+    if is_windows_path(input) {
+        return match url::Url::from_file_path(input) {
+            Ok(url) => Ok(url.to_string()),
+            Err(()) => bail!("{} is not a path that can be opened.", input),
+        };
+    }
+
+    let has_scheme = url_scheme(input).is_some();
+    let parsed = match has_scheme {
+        true => url::Url::parse(input),
+        false => url::Url::parse(&format!("https://{}", input)),
+    };
+    match parsed {
+        Ok(url) if has_scheme || has_plausible_host(&url) => Ok(url.to_string()),
+        Ok(_) => bail!("{} is not a URL.", input),
+        Err(e) => bail!("{} is not a URL. Reason: {}", input, e),
+    }
+}
+
+/// The scheme the `input` starts with, either a known one or one followed by `//`
+fn url_scheme(input: &str) -> Option<&str> {
+    let colon = input.find(':')?;
+    let scheme = &input[..colon];
+    let is_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+    let is_known = KNOWN_SCHEMES.contains(&scheme.to_lowercase().as_str());
+
+    match is_scheme && (is_known || input[colon + 1..].starts_with("//")) {
+        true => Some(scheme),
+        false => None,
+    }
+}
+
+/// Whether the `input` is a path such as `C:\file.html` or `\\server\share\file.html`
+fn is_windows_path(input: &str) -> bool {
+    let bytes = input.as_bytes();
+    let is_drive_path = bytes.len() > 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/');
+
+    is_drive_path || input.starts_with("\\\\")
+}
+
+/// Whether the host of the `url` made from an input without a scheme looks like one,
+/// a domain with a dot, `localhost` or an IP address
+fn has_plausible_host(url: &url::Url) -> bool {
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain.contains('.') || domain == "localhost",
+        Some(_) => true,
+        None => false,
+    }
+}
+
+/// The `url` without its tracking query parameters, ie. `utm_source` or `fbclid`, the other
+/// parameters keep their order. The `url` is returned as it is when it cannot be parsed.
+pub fn clean_url(url: &str) -> String {
+    let mut parsed = match url::Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(_) => return String::from(url),
+    };
+
+    let pairs_count = parsed.query_pairs().count();
+    let kept_pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(name, _)| !is_tracking_parameter(name))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    // serializing again could change the encoding of an URL with nothing to remove
+    if kept_pairs.len() == pairs_count {
+        return String::from(url);
+    }
+
+    match kept_pairs.is_empty() {
+        true => parsed.set_query(None),
+        false => {
+            parsed.query_pairs_mut().clear().extend_pairs(kept_pairs);
+        }
+    }
+    parsed.to_string()
+}
+
+fn is_tracking_parameter(name: &str) -> bool {
+    let name = name.to_lowercase();
+    TRACKING_PARAMETERS.contains(&name.as_str())
+        || TRACKING_PARAMETER_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+/// Whether the `url` is a link of a known URL shortener, ie. bit.ly
+pub fn is_short_url(url: &str) -> bool {
+    host_of(url)
+        .map(|host| SHORTENER_HOSTS.contains(&host.as_str()))
+        .unwrap_or(false)
+}
+
+/// Where the short `url` leads to, `None` when it cannot be found out within the `timeout`.
+/// Sends a request over the network thus it should not be called from the UI thread.
+pub fn resolve_short_url(url: &str, timeout: std::time::Duration) -> Option<String> {
+    match crate::os::util::follow_redirects(url, timeout) {
+        Ok(resolved_url) if resolved_url != url => Some(resolved_url),
+        Ok(_) => None,
+        Err(e) => {
+            log::error!("Could not resolve the short URL {}. Reason: {}", url, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_url_adds_https_to_a_bare_host() {
+        assert_eq!(
+            normalize_url("example.com").unwrap(),
+            "https://example.com/"
+        );
+        assert_eq!(
+            normalize_url(" example.com/docs?page=2 ").unwrap(),
+            "https://example.com/docs?page=2"
+        );
+    }
+
+    #[test]
+    fn normalize_url_takes_localhost_with_a_port_as_a_host() {
+        assert_eq!(
+            normalize_url("localhost:8080").unwrap(),
+            "https://localhost:8080/"
+        );
+    }
+
+    #[test]
+    fn normalize_url_keeps_the_known_schemes() {
+        assert_eq!(
+            normalize_url("mailto:someone@example.com").unwrap(),
+            "mailto:someone@example.com"
+        );
+        assert_eq!(
+            normalize_url("http://example.com/").unwrap(),
+            "http://example.com/"
+        );
+    }
+
+    // `Url::from_file_path` only takes the paths of the platform it runs on
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn normalize_url_makes_the_windows_paths_file_urls() {
+        assert_eq!(
+            normalize_url("C:\\Users\\me\\My page.html").unwrap(),
+            "file:///C:/Users/me/My%20page.html"
+        );
+        assert_eq!(
+            normalize_url("\\\\server\\share\\page.html").unwrap(),
+            "file://server/share/page.html"
+        );
+    }
+
+    #[test]
+    fn normalize_url_rejects_a_single_word() {
+        assert!(normalize_url("browser").is_err());
+        assert!(normalize_url("  ").is_err());
+    }
+
+    #[test]
+    fn clean_url_keeps_the_other_parameters_in_order() {
+        assert_eq!(
+            clean_url("https://example.com/page?id=7&utm_source=news&lang=en&fbclid=abc#top"),
+            "https://example.com/page?id=7&lang=en#top"
+        );
+    }
+
+    #[test]
+    fn clean_url_drops_the_query_of_trackers_only() {
+        assert_eq!(
+            clean_url("https://example.com/page?utm_source=news&UTM_Medium=mail&gclid=1"),
+            "https://example.com/page"
+        );
+    }
+
+    #[test]
+    fn clean_url_returns_the_same_url_with_nothing_to_strip() {
+        for url in &[
+            "HTTPS://Example.com/a%2Fb?q=a+b&tag=%7E",
+            "https://example.com",
+            "not a url?utm_source=news",
+            "",
+        ] {
+            assert_eq!(clean_url(url), *url);
+        }
+    }
+
+    #[test]
+    fn mask_url_shows_a_host_only_url_as_it_is() {
+        assert_eq!(mask_url("https://example.com"), "https://example.com");
+        assert_eq!(mask_url("https://example.com/"), "https://example.com");
+    }
+
+    #[test]
+    fn mask_url_keeps_the_port() {
+        assert_eq!(mask_url("http://localhost:8080/"), "http://localhost:8080");
+        assert_eq!(
+            mask_url("https://example.com:8443/docs"),
+            "https://example.com:8443/••••"
+        );
+    }
+
+    #[test]
+    fn mask_url_masks_the_path_query_and_fragment() {
+        assert_eq!(
+            mask_url("https://example.com/a/b?q=1#top"),
+            "https://example.com/•••••••••••"
+        );
+    }
+
+    #[test]
+    fn mask_url_masks_all_of_a_url_without_a_host() {
+        assert_eq!(mask_url("not a url"), "•••••••••");
+        assert_eq!(mask_url("mailto:someone@example.com"), "•".repeat(26));
+    }
+}